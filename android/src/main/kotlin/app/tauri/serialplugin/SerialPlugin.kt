@@ -11,6 +11,7 @@ import app.tauri.plugin.Plugin
 import app.tauri.serialplugin.manager.SerialPortManager
 import app.tauri.serialplugin.models.*
 import android.webkit.WebView
+import android.util.Base64
 import android.util.Log
 import java.util.concurrent.ConcurrentHashMap
 
@@ -23,6 +24,8 @@ class PortConfigArgs {
     var parity: String? = null
     var stopBits: String? = null
     var timeout: Int = 1000
+    var initialDtr: Boolean? = null
+    var initialRts: Boolean? = null
 }
 
 @InvokeArg
@@ -36,6 +39,26 @@ class CloseArgs {
     lateinit var path: String
 }
 
+@InvokeArg
+class ClearBufferArgs {
+    lateinit var path: String
+    var bufferType: String = "Input"
+}
+
+@InvokeArg
+class UsbFilterArgs {
+    var vid: Int = 0
+    var pid: Int = 0
+    var driver: String? = null
+}
+
+@InvokeArg
+class ReadArgs {
+    lateinit var path: String
+    var timeout: Int = 1000
+    var size: Int = 1024
+}
+
 @TauriPlugin
 class SerialPlugin(private val activity: Activity) : Plugin(activity) {
     private var webView: WebView? = null
@@ -92,23 +115,80 @@ class SerialPlugin(private val activity: Activity) : Plugin(activity) {
                 flowControl = args.flowControl?.let { FlowControl.valueOf(it) } ?: FlowControl.NONE,
                 parity = args.parity?.let { Parity.valueOf(it) } ?: Parity.NONE,
                 stopBits = args.stopBits?.let { StopBits.valueOf(it) } ?: StopBits.ONE,
-                timeout = args.timeout
+                timeout = args.timeout,
+                initialDtr = args.initialDtr,
+                initialRts = args.initialRts
             )
 
-            serialPortManager.openPort(serialConfig)
+            if (!serialPortManager.openPort(serialConfig)) {
+                invoke.reject("USB permission for ${args.path} has not been granted yet, see requestPermission")
+                return
+            }
             invoke.resolve()
         } catch (e: Exception) {
             invoke.reject("Failed to open port: ${e.message}")
         }
     }
 
+    @Command
+    fun addUsbFilter(invoke: Invoke) {
+        try {
+            val args = invoke.parseArgs(UsbFilterArgs::class.java)
+            serialPortManager.addUsbFilter(args.vid, args.pid, args.driver)
+            invoke.resolve()
+        } catch (e: Exception) {
+            invoke.reject("Failed to add USB filter: ${e.message}")
+        }
+    }
+
+    @Command
+    fun getPortState(invoke: Invoke) {
+        try {
+            val args = invoke.parseArgs(CloseArgs::class.java)
+            val result = JSObject()
+            result.put("open", serialPortManager.isOpen(args.path))
+            result.put("listening", serialPortManager.isListening(args.path))
+            invoke.resolve(result)
+        } catch (e: Exception) {
+            invoke.reject("Failed to get port state: ${e.message}")
+        }
+    }
+
+    @Command
+    fun hasPermission(invoke: Invoke) {
+        try {
+            val args = invoke.parseArgs(CloseArgs::class.java)
+            val result = JSObject()
+            result.put("granted", serialPortManager.hasPermission(args.path))
+            invoke.resolve(result)
+        } catch (e: Exception) {
+            invoke.reject("Failed to check USB permission: ${e.message}")
+        }
+    }
+
+    @Command
+    fun requestPermission(invoke: Invoke) {
+        try {
+            val args = invoke.parseArgs(CloseArgs::class.java)
+            serialPortManager.requestPermission(args.path) { granted ->
+                val result = JSObject()
+                result.put("granted", granted)
+                invoke.resolve(result)
+            }
+        } catch (e: Exception) {
+            invoke.reject("Failed to request USB permission: ${e.message}")
+        }
+    }
+
     @Command
     fun write(invoke: Invoke) {
         try {
             val args = invoke.parseArgs(WriteArgs::class.java)
-            val bytesWritten = serialPortManager.writeToPort(args.path, args.value.toByteArray())
+            // Text arrives as is; only writeBinary sends base64
+            val data = args.value.toByteArray(Charsets.UTF_8)
+            serialPortManager.writeToPort(args.path, data)
             val result = JSObject()
-            result.put("bytesWritten", bytesWritten)
+            result.put("bytesWritten", data.size)
             invoke.resolve(result)
         } catch (e: Exception) {
             invoke.reject("Failed to write data: ${e.message}")
@@ -152,9 +232,11 @@ class SerialPlugin(private val activity: Activity) : Plugin(activity) {
     fun writeBinary(invoke: Invoke) {
         try {
             val args = invoke.parseArgs(WriteArgs::class.java)
-            val bytesWritten = serialPortManager.writeToPort(args.path, args.value.toByteArray())
+            // Binary data arrives base64-encoded rather than as a JSON number array
+            val data = Base64.decode(args.value, Base64.NO_WRAP)
+            serialPortManager.writeToPort(args.path, data)
             val result = JSObject()
-            result.put("bytesWritten", bytesWritten)
+            result.put("bytesWritten", data.size)
             invoke.resolve(result)
         } catch (e: Exception) {
             invoke.reject("Failed to write binary data: ${e.message}")
@@ -174,15 +256,31 @@ class SerialPlugin(private val activity: Activity) : Plugin(activity) {
         }
     }
 
+    @Command
+    fun readBinary(invoke: Invoke) {
+        try {
+            val args = invoke.parseArgs(ReadArgs::class.java)
+            val data = serialPortManager.readFromPort(args.path, args.timeout, args.size)
+            val result = JSObject()
+            result.put("data", Base64.encodeToString(data, Base64.NO_WRAP))
+            invoke.resolve(result)
+        } catch (e: Exception) {
+            invoke.reject("Failed to read binary data: ${e.message}")
+        }
+    }
+
     @Command
     fun startListening(invoke: Invoke) {
         try {
             val args = invoke.parseArgs(CloseArgs::class.java)
+            var seq = 0L
             val listener = { data: ByteArray ->
                 val eventData = JSObject()
                 eventData.put("path", args.path)
                 eventData.put("data", String(data))
                 eventData.put("size", data.size)
+                eventData.put("seq", seq++)
+                eventData.put("timestamp_ms", System.currentTimeMillis())
 
                 trigger("serialData", eventData)
             }
@@ -207,6 +305,18 @@ class SerialPlugin(private val activity: Activity) : Plugin(activity) {
         }
     }
 
+    @Command
+    fun cancelRead(invoke: Invoke) {
+        try {
+            val args = invoke.parseArgs(CloseArgs::class.java)
+            listeners.remove(args.path)
+            serialPortManager.stopListening(args.path)
+            invoke.resolve()
+        } catch (e: Exception) {
+            invoke.reject("Failed to cancel read: ${e.message}")
+        }
+    }
+
     @Command
     fun setBaudRate(invoke: Invoke) {
         try {
@@ -408,8 +518,10 @@ class SerialPlugin(private val activity: Activity) : Plugin(activity) {
     @Command
     fun clearBuffer(invoke: Invoke) {
         try {
-            val args = invoke.parseArgs(PortConfigArgs::class.java)
-            val success = serialPortManager.clearBuffer(args.path, args.dataBits ?: "input")
+            val args = invoke.parseArgs(ClearBufferArgs::class.java)
+            // Same names as the Rust `ClearBuffer` enum: "Input", "Output" or "All"
+            val bufferType = ClearBuffer.valueOf(args.bufferType.uppercase())
+            val success = serialPortManager.clearBuffer(args.path, bufferType)
             if (success) {
                 invoke.resolve()
             } else {
@@ -7,10 +7,18 @@ import android.content.Intent
 import android.content.IntentFilter
 import android.hardware.usb.UsbDevice
 import android.hardware.usb.UsbManager
+import com.hoho.android.usbserial.driver.CdcAcmSerialDriver
+import com.hoho.android.usbserial.driver.Ch34xSerialDriver
+import com.hoho.android.usbserial.driver.Cp21xxSerialDriver
+import com.hoho.android.usbserial.driver.FtdiSerialDriver
+import com.hoho.android.usbserial.driver.ProbeTable
+import com.hoho.android.usbserial.driver.ProlificSerialDriver
+import com.hoho.android.usbserial.driver.UsbSerialDriver
 import com.hoho.android.usbserial.driver.UsbSerialPort
 import com.hoho.android.usbserial.driver.UsbSerialProber
 import com.hoho.android.usbserial.util.SerialInputOutputManager
 import app.tauri.serialplugin.models.*
+import java.util.concurrent.ConcurrentHashMap
 import java.util.concurrent.Executors
 import java.io.IOException
 import android.util.Log
@@ -22,6 +30,9 @@ class SerialPortManager(private val context: Context) {
     private val executor = Executors.newCachedThreadPool()
     
     private val ACTION_USB_PERMISSION = "app.tauri.serialplugin.USB_PERMISSION"
+
+    // Pending requestPermission callbacks, keyed by device name
+    private val permissionCallbacks = ConcurrentHashMap<String, MutableList<(Boolean) -> Unit>>()
     
     private val usbReceiver = object : BroadcastReceiver() {
         override fun onReceive(context: Context, intent: Intent) {
@@ -29,10 +40,9 @@ class SerialPortManager(private val context: Context) {
                 synchronized(this) {
                     val device: UsbDevice? = intent.getParcelableExtra(UsbManager.EXTRA_DEVICE, UsbDevice::class.java)
                     //val device: UsbDevice? = intent.getParcelableExtra(UsbManager.EXTRA_DEVICE)
-                    if (intent.getBooleanExtra(UsbManager.EXTRA_PERMISSION_GRANTED, false)) {
-                        device?.let {
-                            // Permission granted, proceed with connection
-                        }
+                    val granted = intent.getBooleanExtra(UsbManager.EXTRA_PERMISSION_GRANTED, false)
+                    device?.let {
+                        permissionCallbacks.remove(it.deviceName)?.forEach { callback -> callback(granted) }
                     }
                 }
             }
@@ -44,9 +54,34 @@ class SerialPortManager(private val context: Context) {
         context.registerReceiver(usbReceiver, filter)
     }
 
+    // VID/PID pairs added at runtime, for adapters the default prober doesn't know
+    private val customProbeTable = ProbeTable()
+
+    private fun findAllDrivers(): List<UsbSerialDriver> {
+        val drivers = UsbSerialProber.getDefaultProber().findAllDrivers(usbManager).toMutableList()
+        UsbSerialProber(customProbeTable).findAllDrivers(usbManager).forEach { driver ->
+            if (drivers.none { it.device.deviceName == driver.device.deviceName }) {
+                drivers.add(driver)
+            }
+        }
+        return drivers
+    }
+
+    fun addUsbFilter(vid: Int, pid: Int, driver: String?) {
+        val driverClass = when (driver?.lowercase()) {
+            null, "cdc" -> CdcAcmSerialDriver::class.java
+            "ch34x" -> Ch34xSerialDriver::class.java
+            "cp21xx" -> Cp21xxSerialDriver::class.java
+            "ftdi" -> FtdiSerialDriver::class.java
+            "prolific" -> ProlificSerialDriver::class.java
+            else -> throw IllegalArgumentException("Unknown driver: $driver")
+        }
+        customProbeTable.addProduct(vid, pid, driverClass)
+    }
+
     fun getAvailablePorts(): Map<String, Map<String, String>> {
         val result = mutableMapOf<String, Map<String, String>>()
-        val availableDrivers = UsbSerialProber.getDefaultProber().findAllDrivers(usbManager)
+        val availableDrivers = findAllDrivers()
         
         availableDrivers.forEach { driver ->
             val device = driver.device
@@ -62,9 +97,45 @@ class SerialPortManager(private val context: Context) {
         return result
     }
 
+    fun isOpen(path: String): Boolean = portMap.containsKey(path)
+
+    fun isListening(path: String): Boolean = ioManagerMap.containsKey(path)
+
+    fun hasPermission(path: String): Boolean {
+        val device = usbManager.deviceList[path] ?: throw IOException("Device not found")
+        return usbManager.hasPermission(device)
+    }
+
+    // Shows the system USB permission dialog and reports the user's answer to `callback`
+    fun requestPermission(path: String, callback: (Boolean) -> Unit) {
+        val device = usbManager.deviceList[path] ?: throw IOException("Device not found")
+        if (usbManager.hasPermission(device)) {
+            callback(true)
+            return
+        }
+
+        val callbacks = permissionCallbacks.getOrPut(path) { mutableListOf() }
+        synchronized(callbacks) {
+            callbacks.add(callback)
+            // Only the first caller opens the dialog, later ones wait for the same answer
+            if (callbacks.size > 1) {
+                return
+            }
+        }
+
+        // Mutable so the system can attach the device and the result to the intent
+        val permissionIntent = PendingIntent.getBroadcast(
+            context,
+            0,
+            Intent(ACTION_USB_PERMISSION).setPackage(context.packageName),
+            PendingIntent.FLAG_MUTABLE
+        )
+        usbManager.requestPermission(device, permissionIntent)
+    }
+
     fun openPort(config: SerialPortConfig): Boolean {
         try {
-            val availableDrivers = UsbSerialProber.getDefaultProber().findAllDrivers(usbManager)
+            val availableDrivers = findAllDrivers()
             val driver = availableDrivers.find { it.device.deviceName == config.path }
                 ?: throw IOException("Device not found")
             
@@ -102,6 +173,8 @@ class SerialPortManager(private val context: Context) {
                 }
                 FlowControl.NONE -> {}
             }
+            config.initialDtr?.let { port.setDTR(it) }
+            config.initialRts?.let { port.setRTS(it) }
             
             portMap[config.path] = port
             return true
@@ -325,16 +398,15 @@ class SerialPortManager(private val context: Context) {
         }
     }
 
-    fun clearBuffer(path: String, bufferType: String): Boolean {
+    fun clearBuffer(path: String, bufferType: ClearBuffer): Boolean {
         return try {
-            Log.d("clearBuffer", path)
-            Log.d("clearBuffer", bufferType)
-            //when (bufferType) {
-            //    "input" -> portMap[path]?.clearInputBuffer()
-            //    "output" -> portMap[path]?.clearOutputBuffer()
-            //    else -> throw IOException("Invalid buffer type")
-            //}
-            false
+            val port = portMap[path] ?: return false
+            when (bufferType) {
+                ClearBuffer.INPUT -> port.purgeHwBuffers(false, true)
+                ClearBuffer.OUTPUT -> port.purgeHwBuffers(true, false)
+                ClearBuffer.ALL -> port.purgeHwBuffers(true, true)
+            }
+            true
         } catch (e: Exception) {
             false
         }
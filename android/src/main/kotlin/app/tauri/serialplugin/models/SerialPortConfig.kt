@@ -18,7 +18,9 @@ enum class FlowControl {
 enum class Parity(val value: Int) {
     NONE(UsbSerialPort.PARITY_NONE),
     ODD(UsbSerialPort.PARITY_ODD),
-    EVEN(UsbSerialPort.PARITY_EVEN)
+    EVEN(UsbSerialPort.PARITY_EVEN),
+    MARK(UsbSerialPort.PARITY_MARK),
+    SPACE(UsbSerialPort.PARITY_SPACE)
 }
 
 enum class StopBits(val value: Int) {
@@ -39,5 +41,7 @@ data class SerialPortConfig(
     val flowControl: FlowControl = FlowControl.NONE,
     val parity: Parity = Parity.NONE,
     val stopBits: StopBits = StopBits.ONE,
-    val timeout: Int = 1000
+    val timeout: Int = 1000,
+    val initialDtr: Boolean? = null,
+    val initialRts: Boolean? = null
 )
\ No newline at end of file
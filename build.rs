@@ -5,33 +5,98 @@
 const COMMANDS: &[&str] = &[
     "available_ports",
     "available_ports_direct",
+    "available_ports_merged",
+    "available_ports_filtered",
+    "start_port_scan",
+    "stop_port_scan",
     "cancel_read",
+    "cancel_operation",
     "close",
     "close_all",
+    "reconnect",
+    "reconfigure",
     "force_close",
     "open",
+    "open_with_config",
+    "open_when_available",
+    "cancel_open_when_available",
+    "add_usb_filter",
+    "has_permission",
+    "request_permission",
     "read",
+    "read_binary",
+    "read_hex",
+    "read_both",
+    "read_exact_or_partial",
+    "read_available",
+    "peek",
+    "transaction",
     "start_listening",
     "stop_listening",
+    "pause_listening",
+    "resume_listening",
     "write",
     "write_binary",
+    "write_binary_paced",
+    "write_slip",
+    "write_hex",
+    "write_sequence",
+    "set_rs485",
     "set_baud_rate",
     "set_data_bits",
     "set_flow_control",
+    "set_flow_control_chars",
+    "get_flow_control_chars",
     "set_parity",
     "set_stop_bits",
     "set_timeout",
+    "set_read_timeout",
+    "set_write_timeout",
+    "set_wait_for_cts",
+    "set_inter_byte_delay",
+    "set_write_events",
+    "set_write_chunk_size",
     "write_request_to_send",
     "write_data_terminal_ready",
+    "set_control_lines",
+    "reset_via_dtr",
+    "enter_esp_bootloader",
+    "reset_esp",
+    "trigger_bootloader_1200",
+    "start_heartbeat",
+    "stop_heartbeat",
+    "idle_auto_close",
     "read_clear_to_send",
     "read_data_set_ready",
     "read_ring_indicator",
     "read_carrier_detect",
+    "get_modem_status",
+    "watch_modem_signals",
+    "stop_watching_modem_signals",
+    "get_line_errors",
+    "set_latency_timer",
     "bytes_to_read",
     "bytes_to_write",
+    "drain",
     "clear_buffer",
     "set_break",
     "clear_break",
+    "send_break",
+    "is_open",
+    "managed_ports",
+    "managed_ports_detailed",
+    "get_port_state",
+    "export_config",
+    "get_port_capabilities",
+    "get_stats",
+    "reset_stats",
+    "start_capture",
+    "stop_capture",
+    "replay_capture",
+    "set_log_level",
+    "get_log_level",
+    "checksum",
+    "set_port_log_level",
     "write_rts",
     "write_dtr",
     "read_cts",
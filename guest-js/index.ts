@@ -8,6 +8,12 @@ export interface PortInfo {
   pid: "Unknown"|string;
   product: "Unknown"|string;
   serial_number: "Unknown"|string;
+  /** Stable `/dev/serial/by-id/...` path (Linux, USB devices only) */
+  by_id?: string;
+  /** Address of the remote device of a Bluetooth port, e.g. "98:D3:31:FB:12:34" */
+  bluetooth_address?: string;
+  /** Name of the remote device of a Bluetooth port */
+  bluetooth_name?: string;
   type: "PCI"|string;
   vid: "Unknown"|string;
 }
@@ -19,7 +25,162 @@ export interface InvokeResult {
 
 export interface ReadDataResult {
   size: number;
+  /** Byte array, or a base64 string when listening with `base64: true` */
+  data: number[] | string;
+  /** Counter of the listener's events of this name, starting at 0; a gap means an event was dropped */
+  seq: number;
+  /** When the event was emitted, in milliseconds since the Unix epoch */
+  timestamp_ms: number;
+}
+
+/** Payload of the `plugin-serialplugin-read-text-*` event, emitted when listening with `decode: true` */
+export interface ReadTextResult {
+  /** Number of bytes decoded */
+  size: number;
+  /** The received bytes decoded as UTF-8; a character split between reads arrives whole with the later event */
+  data: string;
+  /** Counter of the listener's events of this name, starting at 0; a gap means an event was dropped */
+  seq: number;
+  /** When the event was emitted, in milliseconds since the Unix epoch */
+  timestamp_ms: number;
+}
+
+/**
+ * @description Decodes a base64 read event payload into bytes
+ * @param {string} data The base64 encoded data
+ * @returns {Uint8Array} The decoded bytes
+ */
+export function decodeBase64(data: string): Uint8Array {
+  const binary = atob(data);
+  const bytes = new Uint8Array(binary.length);
+  for (let i = 0; i < binary.length; i++) {
+    bytes[i] = binary.charCodeAt(i);
+  }
+  return bytes;
+}
+
+/**
+ * @description Encodes a port path the way the plugin does in event names, which are
+ * `plugin-serialplugin-{kind}-${eventPath(path)}`. ASCII letters, digits and `-` are
+ * kept and every other UTF-8 byte becomes `_` plus two uppercase hex digits, so
+ * `/dev/tty.usb-1` becomes `_2Fdev_2Ftty_2Eusb-1`.
+ * @param {string} path The port path
+ * @returns {string} The path as it appears in event names
+ */
+export function eventPath(path: string): string {
+  let encoded = "";
+  for (const byte of new TextEncoder().encode(path)) {
+    const char = String.fromCharCode(byte);
+    encoded += /[A-Za-z0-9-]/.test(char)
+      ? char
+      : "_" + byte.toString(16).toUpperCase().padStart(2, "0");
+  }
+  return encoded;
+}
+
+/** What a command rejects with when it ran out of time, e.g. a read that got no data */
+export interface TimeoutError {
+  kind: "timeout";
+  message: string;
+  /** How long the command waited, in milliseconds */
+  elapsed_ms: number;
+}
+
+/**
+ * @description Tells a timeout apart from other errors a command rejects with, which are strings
+ * @param {unknown} error The rejection reason
+ * @returns {boolean} Whether the command timed out
+ */
+export function isTimeoutError(error: unknown): error is TimeoutError {
+  return typeof error === 'object' && error !== null && (error as TimeoutError).kind === 'timeout';
+}
+
+/** What `readExactOrPartial` rejects with when only some of the bytes arrived in time */
+export interface PartialReadError {
+  kind: "partial_read";
+  message: string;
+  /** The bytes that did arrive */
   data: number[];
+  /** How many bytes were asked for */
+  expected: number;
+}
+
+/**
+ * @description Tells a partial read apart from other errors a command rejects with
+ * @param {unknown} error The rejection reason
+ * @returns {boolean} Whether only some of the bytes arrived
+ */
+export function isPartialReadError(error: unknown): error is PartialReadError {
+  return typeof error === 'object' && error !== null && (error as PartialReadError).kind === 'partial_read';
+}
+
+export interface ListenerError {
+  path: string;
+  kind: "Read" | "Emit" | "Heartbeat" | "Overflow" | "Parse";
+  message: string;
+}
+
+export interface JsonData {
+  /** The parsed line */
+  value: unknown;
+  seq: number;
+  /** When the value was emitted, in milliseconds since the Unix epoch */
+  timestamp_ms: number;
+}
+
+export interface WriteProgress {
+  path: string;
+  bytes_written: number;
+  total: number;
+}
+
+export interface ModemStatus {
+  cts: boolean;
+  dsr: boolean;
+  ri: boolean;
+  cd: boolean;
+}
+
+/** Line errors the driver counted since the port was opened (Linux only) */
+export interface LineErrors {
+  framing: number;
+  parity: number;
+  overrun: number;
+  buffer_overrun: number;
+  breaks: number;
+}
+
+export interface PortChange {
+  path: string;
+  info: PortInfo;
+}
+
+export interface FlowControlChars {
+  xon: number;
+  xoff: number;
+}
+
+export interface PortFilter {
+  /** "USB", "Bluetooth", "PCI" or "Unknown" */
+  portType?: string;
+  vid?: number;
+  pid?: number;
+  /** Case-insensitive substring of the manufacturer name */
+  manufacturer?: string;
+}
+
+export interface PortStats {
+  bytes_read: number;
+  bytes_written: number;
+  read_errors: number;
+  /** Milliseconds since the Unix epoch of the last read or write */
+  last_activity: number | null;
+}
+
+export interface UsbMatch {
+  vid: number;
+  pid: number;
+  serialNumber?: string;
 }
 
 export interface SerialportOptions {
@@ -32,6 +193,35 @@ export interface SerialportOptions {
   stopBits?: StopBits;
   timeout?: number;
   size?: number;
+  /** How many times a busy port is retried on open (desktop only) */
+  openRetries?: number;
+  /** Delay before the first retry in milliseconds, doubled on each further retry */
+  retryDelayMs?: number;
+  /** Reject opening a path that is already open (default). Set to false to close and reopen it instead */
+  failIfOpen?: boolean;
+  /** Lock the device so other processes can't open it (default; Unix only, Windows always locks) */
+  exclusive?: boolean;
+  /** Drive DTR to this level as part of opening, before any data; left as the driver sets it by default */
+  initialDtr?: boolean;
+  /** Drive RTS to this level as part of opening, before any data; left as the driver sets it by default */
+  initialRts?: boolean;
+  /** Deliver read events as base64 strings instead of byte arrays (desktop only) */
+  base64?: boolean;
+  /** Serve `read` from a copy of the listener's data while listening (desktop only) */
+  bufferReads?: boolean;
+  /** Emit the bytes received so far as one frame after this many milliseconds without data (desktop only) */
+  idleTimeout?: number;
+  /** Split incoming data into protocol frames delivered to `onFrame` (desktop only) */
+  framing?: Framing;
+  /** How read events are emitted; by default the port is read every `timeout` ms (desktop only) */
+  emitStrategy?: EmitStrategy;
+  /** Most bytes the listener accumulates in a frame, batch or paused buffer before emitting or dropping them (desktop only) */
+  maxBufferSize?: number;
+  /**
+   * Emit read events as UTF-8 text on `plugin-serialplugin-read-text-*` (true) or as bytes on
+   * `plugin-serialplugin-read-bytes-*` (false) instead of `plugin-serialplugin-read-*`; `listen` follows the choice. Desktop only: on mobile starting a listener with it fails. A listener already running with a different value makes `startListening` fail.
+   */
+  decode?: boolean;
   is_test?: boolean;
   [key: string]: any;
 }
@@ -45,12 +235,34 @@ export interface Options {
   stopBits: StopBits;
   size?: number;
   timeout: number;
+  openRetries?: number;
+  retryDelayMs?: number;
+  failIfOpen?: boolean;
+  exclusive?: boolean;
+  initialDtr?: boolean;
+  initialRts?: boolean;
+  base64?: boolean;
+  bufferReads?: boolean;
+  idleTimeout?: number;
+  framing?: Framing;
+  emitStrategy?: EmitStrategy;
+  maxBufferSize?: number;
+  decode?: boolean;
   [key: string]: any;
 }
 
 export interface ReadOptions {
-  timeout?: number;
+  /** Milliseconds to wait, the port's timeout when left out; `null` goes by `setReadTimeout`, or without one waits until data arrives or `cancelOperation` is called */
+  timeout?: number | null;
   size?: number;
+  /** Keep reading until at least this many bytes arrived or `timeout` ran out (`readBinary` only) */
+  minSize?: number;
+}
+
+export interface Rs485Config {
+  rtsActiveHigh: boolean;
+  delayBeforeTx?: number;
+  delayAfterTx?: number;
 }
 
 export enum DataBits {
@@ -69,7 +281,11 @@ export enum FlowControl {
 export enum Parity {
   None = "None",
   Odd = "Odd",
-  Even = "Even"
+  Even = "Even",
+  /** Parity bit always 1 (Android only; rejected on desktop) */
+  Mark = "Mark",
+  /** Parity bit always 0 (Android only; rejected on desktop) */
+  Space = "Space"
 }
 
 export enum StopBits {
@@ -77,12 +293,127 @@ export enum StopBits {
   Two = "Two"
 }
 
+export interface BytesAndText {
+  bytes: Uint8Array;
+  /** The bytes decoded as UTF-8, invalid sequences replaced by U+FFFD */
+  text: string;
+}
+
+export interface PortConfig {
+  baud_rate: number;
+  data_bits: DataBits;
+  flow_control: FlowControl;
+  parity: Parity;
+  stop_bits: StopBits;
+  /** Port timeout in milliseconds */
+  timeout: number;
+}
+
+export interface PortState {
+  open: boolean;
+  listening: boolean;
+  /** The listener was paused with `pauseListening` */
+  paused: boolean;
+  /** Line settings while open; not reported on mobile */
+  config: PortConfig | null;
+}
+
+export interface PortCapabilities {
+  /** Standard rates the port is expected to accept, in ascending order; only up to 115200 when the device isn't recognized */
+  baud_rates: number[];
+  /** Highest rate of the recognized USB-serial chip */
+  max_baud_rate: number | null;
+  /** Rates between the standard ones are applied exactly instead of rounded; false for a chip that isn't recognized */
+  custom_baud: boolean;
+  /** A break condition can be sent */
+  break_signal: boolean;
+  /** DTR/RTS can be driven and CTS/DSR/RI/CD read */
+  modem_lines: boolean;
+  /** RTS can be toggled around writes for `setRs485` */
+  rs485: boolean;
+  /** The driver counts errors for `getLineErrors` */
+  line_errors: boolean;
+  /** The recognized USB-serial chip family */
+  chip: string | null;
+}
+
+export interface ManagedPortInfo {
+  /** Line settings as last applied */
+  settings: PortConfig;
+  /** The device was opened with the exclusive lock */
+  exclusive: boolean;
+  listening: boolean;
+  /** The listener was paused with `pauseListening` */
+  paused: boolean;
+  /** `USB`, `Bluetooth`, `PCI` or `Unknown`, as in `available_ports` */
+  type: string;
+}
+
 export enum ClearBuffer {
   Input = "Input",
   Output = "Output",
   All = "All"
 }
 
+export enum Framing {
+  /** NMEA 0183 sentences; sentences with a bad checksum are dropped */
+  Nmea = "Nmea",
+  /** SLIP (RFC 1055) frames, delivered un-escaped */
+  Slip = "Slip",
+  /** Newline-delimited JSON, delivered parsed to `onJson` instead of `onFrame`; lines that don't parse raise `Parse` errors */
+  Json = "Json"
+}
+
+/** Checksums computed by `SerialPort.checksum`; the CRCs are named as in the reveng CRC catalogue */
+export enum ChecksumAlgorithm {
+  /** CRC-8/SMBUS */
+  Crc8 = "Crc8",
+  /** CRC-8/MAXIM-DOW of 1-Wire devices */
+  Crc8Maxim = "Crc8Maxim",
+  /** CRC-16/CCITT-FALSE */
+  Crc16Ccitt = "Crc16Ccitt",
+  Crc16Xmodem = "Crc16Xmodem",
+  Crc16Kermit = "Crc16Kermit",
+  Crc16Modbus = "Crc16Modbus",
+  /** CRC-32/ISO-HDLC, as in Ethernet, zip and PNG */
+  Crc32 = "Crc32",
+  /** CRC-32C (Castagnoli) */
+  Crc32c = "Crc32c",
+  /** XOR of all bytes */
+  Xor8 = "Xor8",
+  /** Two's complement of the byte sum */
+  Sum8 = "Sum8"
+}
+
+/**
+ * How the listener emits read events:
+ * - `"Immediate"` emits every chunk as soon as it is read
+ * - `{ Batched: { windowMs } }` emits what arrived once `windowMs` passed since the batch's first byte, or when the listener stops
+ * - `{ Coalesced: { debounceMs, maxRate } }` merges what arrived into one event once no data came for `debounceMs`,
+ *   at most `maxRate` (default 60) times per second; what is left is emitted when the listener stops
+ */
+export type EmitStrategy =
+  | "Immediate"
+  | { Batched: { windowMs: number } }
+  | { Coalesced: { debounceMs: number; maxRate?: number } };
+
+export enum CaptureFormat {
+  /** One text line per chunk: `<unix ms> <TX|RX> <hex bytes>` */
+  Hexdump = "Hexdump",
+  /** Binary records: unix ms (u64 BE), 'T' or 'R', length (u32 BE), then the bytes */
+  Raw = "Raw"
+}
+
+export enum LogLevel {
+  None = "None",
+  Error = "Error",
+  Warn = "Warn",
+  Info = "Info",
+  Debug = "Debug",
+  /** Also logs the bytes sent and received */
+  Trace = "Trace"
+}
+
 let tester_ports: {[key: string]: SerialPort} = {}
 let tester_listeners: {[key: string]: (...args: any[]) => void} = {}
 
@@ -112,6 +443,19 @@ class SerialPort {
       stopBits: options.stopBits || StopBits.One,
       size: options.size || 1024,
       timeout: options.timeout || 200,
+      openRetries: options.openRetries,
+      retryDelayMs: options.retryDelayMs,
+      failIfOpen: options.failIfOpen,
+      exclusive: options.exclusive,
+      initialDtr: options.initialDtr,
+      initialRts: options.initialRts,
+      base64: options.base64,
+      bufferReads: options.bufferReads,
+      idleTimeout: options.idleTimeout,
+      framing: options.framing,
+      emitStrategy: options.emitStrategy,
+      maxBufferSize: options.maxBufferSize,
+      decode: options.decode,
     };
     this.size = options.size || 1024;
     this.is_test = options.is_test || false;
@@ -163,6 +507,123 @@ class SerialPort {
     }
   }
 
+  /**
+   * @description Lists all serial ports keyed by device path, merging `available_ports` with the details found by `available_ports_direct`
+   * @returns {Promise<{ [key: string]: PortInfo }>} A promise that resolves to a map of port names to port information
+   */
+  static async available_ports_merged(): Promise<{ [key: string]: PortInfo }> {
+    try {
+      const result = await invoke<{ [key: string]: PortInfo }>('plugin:serialplugin|available_ports_merged');
+      for (const path in tester_ports) {
+        result[path] = {
+          manufacturer: "tester",
+          pid: "tester",
+          product: "tester",
+          serial_number: "tester",
+          type: "USB",
+          vid: "tester",
+        } as PortInfo
+      }
+      return Promise.resolve(result)
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Lists the serial ports matching a filter
+   * @param {PortFilter} filter Port type, VID, PID or manufacturer to match; unset fields match anything
+   * @returns {Promise<{ [key: string]: PortInfo }>} A promise that resolves to a map of port names to port information
+   */
+  static async available_ports_filtered(filter: PortFilter): Promise<{ [key: string]: PortInfo }> {
+    try {
+      return await invoke<{ [key: string]: PortInfo }>('plugin:serialplugin|available_ports_filtered', { filter });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Starts scanning for ports in the background; changes are delivered to `onPortAdded`/`onPortRemoved`
+   * @param {number} [interval=1000] Time between scans, in milliseconds; must be at least 1
+   * @returns {Promise<void>} A promise that resolves when the scan has started
+   */
+  static async startPortScan(interval?: number): Promise<void> {
+    return await invoke<void>('plugin:serialplugin|start_port_scan', { interval });
+  }
+
+  /**
+   * @description Stops the background port scan
+   * @returns {Promise<void>} A promise that resolves when the scan has stopped
+   */
+  static async stopPortScan(): Promise<void> {
+    return await invoke<void>('plugin:serialplugin|stop_port_scan');
+  }
+
+  /**
+   * @description Calls back whenever the port scan finds a new port
+   * @param {Function} fn Callback receiving the added port
+   * @returns {Promise<UnlistenFn>} A promise that resolves to a function removing the callback
+   */
+  static async onPortAdded(fn: (change: PortChange) => void): Promise<UnlistenFn> {
+    return await listen<PortChange>('plugin-serialplugin-port-added', ({ payload }) => fn(payload));
+  }
+
+  /**
+   * @description Calls back whenever the port scan notices a port has gone
+   * @param {Function} fn Callback receiving the removed port
+   * @returns {Promise<UnlistenFn>} A promise that resolves to a function removing the callback
+   */
+  static async onPortRemoved(fn: (change: PortChange) => void): Promise<UnlistenFn> {
+    return await listen<PortChange>('plugin-serialplugin-port-removed', ({ payload }) => fn(payload));
+  }
+
+  /**
+   * @description Sets how much diagnostic output the plugin prints
+   * @param {LogLevel} level The most verbose level that is still printed
+   * @returns {Promise<void>} A promise that resolves when the level is set
+   */
+  static async setLogLevel(level: LogLevel): Promise<void> {
+    return await invoke<void>('plugin:serialplugin|set_log_level', { level });
+  }
+
+  /**
+   * @description Computes a checksum or CRC of the given bytes, e.g. to append to a frame or verify one
+   * @param {ChecksumAlgorithm} algorithm The checksum to compute
+   * @param {Uint8Array | number[]} data The bytes to checksum
+   * @returns {Promise<number>} A promise that resolves to the checksum
+   */
+  static async checksum(algorithm: ChecksumAlgorithm, data: Uint8Array | number[]): Promise<number> {
+    return await invoke<number>('plugin:serialplugin|checksum', {
+      algorithm,
+      data: Array.from(data)
+    });
+  }
+
+  /**
+   * @description Gets the current log level of the plugin
+   * @returns {Promise<LogLevel>} A promise that resolves to the log level
+   */
+  static async getLogLevel(): Promise<LogLevel> {
+    return await invoke<LogLevel>('plugin:serialplugin|get_log_level');
+  }
+
+  /**
+   * @description Overrides the log level for messages about this port, e.g. to debug one device among several; the override ends when the port is closed
+   * @param {LogLevel} [level] The level to use for this port, or nothing to go back to the global level
+   * @returns {Promise<void>} A promise that resolves when the level is set
+   */
+  async setPortLogLevel(level?: LogLevel): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|set_port_log_level', {
+        path: this.options.path,
+        level,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
   /**
    * @description Forcefully closes a specific serial port
    * @param {string} path The path of the serial port to close
@@ -218,6 +679,20 @@ class SerialPort {
     }
   }
 
+  /**
+   * @description Interrupts an in-flight `read`, `drain` or paced `writeBinary`, which then rejects with a "Cancelled" error; on mobile only `drain` can be interrupted
+   * @returns {Promise<void>} A promise that resolves once the cancellation is requested
+   */
+  async cancelOperation(): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|cancel_operation', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
   /**
    * @description Changes the serial port configuration
    * @param {object} options Configuration options
@@ -272,13 +747,51 @@ class SerialPort {
     }
   }
 
+  /**
+   * @description Reopens the device with the last-known settings, e.g. after it was unplugged and plugged back in.
+   * The listener is stopped and has to be started again with `startListening`.
+   * @returns {Promise<void>} A promise that resolves when the port is open again
+   */
+  async reconnect(): Promise<void> {
+    try {
+      await invoke<void>('plugin:serialplugin|reconnect', {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Closes and reopens the device with new line settings, for adapters that only take a new baud rate when opened with it (desktop only).
+   * A running listener carries on with the new settings and needn't be restarted.
+   * @param {PortConfig} config The line settings to reopen the port with; they replace those in the options
+   * @returns {Promise<void>} A promise that resolves when the port is open again
+   */
+  async reconfigure(config: PortConfig): Promise<void> {
+    try {
+      await invoke<void>('plugin:serialplugin|reconfigure', {
+        path: this.options.path,
+        config,
+      });
+      this.options.baudRate = config.baud_rate;
+      this.options.dataBits = config.data_bits;
+      this.options.flowControl = config.flow_control;
+      this.options.parity = config.parity;
+      this.options.stopBits = config.stop_bits;
+      this.options.timeout = config.timeout;
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
   /**
    * @description Sets up a listener for port disconnection events
    * @param {Function} fn Callback function to handle disconnection
    * @returns {Promise<void>} A promise that resolves when the listener is set up
    */
   async disconnected(fn: (...args: any[]) => void): Promise<void> {
-    let sub_path = this.options.path?.toString().replaceAll(".", "-").replaceAll("/", "-")
+    let sub_path = eventPath(this.options.path?.toString() ?? "")
     let checkEvent = `plugin-serialplugin-disconnected-${sub_path}`;
     console.log('listen event: ' + checkEvent)
     let unListen: any = await listen<ReadDataResult>(
@@ -296,16 +809,65 @@ class SerialPort {
   }
 
   /**
-   * @description Monitors serial port data
+   * @description Calls back when the port was closed by the watchdog set up with `idleAutoClose`
+   * @param {Function} fn Callback receiving a message saying why the port was closed
+   * @returns {Promise<UnlistenFn>} A promise that resolves to a function removing the callback
+   */
+  async onAutoClosed(fn: (message: string) => void): Promise<UnlistenFn> {
+    let sub_path = eventPath(this.options.path?.toString() ?? "")
+    let autoClosedEvent = `plugin-serialplugin-auto-closed-${sub_path}`;
+    return await listen<string>(autoClosedEvent, ({ payload }) => {
+      this.isOpen = false;
+      fn(payload);
+    });
+  }
+
+  /**
+   * @description Calls back when the listener fails to read from the port or to deliver data
+   * @param {Function} fn Callback receiving the error
+   * @returns {Promise<UnlistenFn>} A promise that resolves to a function removing the callback
+   */
+  async onError(fn: (error: ListenerError) => void): Promise<UnlistenFn> {
+    let sub_path = eventPath(this.options.path?.toString() ?? "")
+    let errorEvent = `plugin-serialplugin-error-${sub_path}`;
+    return await listen<ListenerError>(errorEvent, ({ payload }) => fn(payload));
+  }
+
+  /**
+   * @description Calls back when a write finishes, once enabled with `setWriteEvents`
+   * @param {Function} fn Callback receiving the number of bytes written
+   * @returns {Promise<UnlistenFn>} A promise that resolves to a function removing the callback
+   */
+  async onWriteComplete(fn: (progress: WriteProgress) => void): Promise<UnlistenFn> {
+    let sub_path = eventPath(this.options.path?.toString() ?? "")
+    let writeCompleteEvent = `plugin-serialplugin-write-complete-${sub_path}`;
+    return await listen<WriteProgress>(writeCompleteEvent, ({ payload }) => fn(payload));
+  }
+
+  /**
+   * @description Calls back after each chunk of a chunked write, see `setWriteChunkSize`
+   * @param {Function} fn Callback receiving the bytes written so far and the total
+   * @returns {Promise<UnlistenFn>} A promise that resolves to a function removing the callback
+   */
+  async onWriteProgress(fn: (progress: WriteProgress) => void): Promise<UnlistenFn> {
+    let sub_path = eventPath(this.options.path?.toString() ?? "")
+    let writeProgressEvent = `plugin-serialplugin-write-progress-${sub_path}`;
+    return await listen<WriteProgress>(writeProgressEvent, ({ payload }) => fn(payload));
+  }
+
+  /**
+   * @description Monitors serial port data, on the read event matching the `decode` option
    * @param {Function} fn Callback function to handle received data
-   * @param {boolean} [isDecode=true] Whether to decode the received data
+   * @param {boolean} [isDecode=true] Pass the data as a string rather than a Uint8Array
    * @returns {Promise<void>} A promise that resolves when monitoring starts
    */
   async listen(fn: (...args: any[]) => void, isDecode = true): Promise<void> {
     try {
       await this.cancelListen();
-      let sub_path = this.options.path?.toString().replaceAll(".", "-").replaceAll("/", "-")
-      let readEvent = `plugin-serialplugin-read-${sub_path}`;
+      let sub_path = eventPath(this.options.path?.toString() ?? "")
+      let readEvent = this.options.decode === undefined
+          ? `plugin-serialplugin-read-${sub_path}`
+          : `plugin-serialplugin-read-${this.options.decode ? 'text' : 'bytes'}-${sub_path}`;
       console.log('listen event: ' + readEvent)
 
       if (this.is_test) {
@@ -317,16 +879,26 @@ class SerialPort {
         return Promise.resolve();
       }
 
+      if (this.options.decode) {
+        this.unListen = await listen<ReadTextResult>(readEvent, ({ payload }) => {
+          fn(isDecode ? payload.data : new TextEncoder().encode(payload.data));
+        });
+        return;
+      }
+
       this.unListen = await listen<ReadDataResult>(
           readEvent,
           ({ payload }) => {
             try {
+              const bytes = typeof payload.data === 'string'
+                  ? decodeBase64(payload.data)
+                  : new Uint8Array(payload.data);
               if (isDecode) {
                 const decoder = new TextDecoder(this.encoding);
-                const data = decoder.decode(new Uint8Array(payload.data));
+                const data = decoder.decode(bytes);
                 fn(data);
               } else {
-                fn(new Uint8Array(payload.data));
+                fn(bytes);
               }
             } catch (error) {
               console.error(error);
@@ -340,7 +912,70 @@ class SerialPort {
   }
 
   /**
-   * @description Opens the serial port with current settings
+   * @description Calls back with the text of each read event of a listener started with `decode: true`
+   * @param {Function} fn Callback receiving the event payload
+   * @returns {Promise<UnlistenFn>} A promise that resolves to a function removing the callback
+   */
+  async onReadText(fn: (data: ReadTextResult) => void): Promise<UnlistenFn> {
+    let sub_path = eventPath(this.options.path?.toString() ?? "")
+    let readTextEvent = `plugin-serialplugin-read-text-${sub_path}`;
+    return await listen<ReadTextResult>(readTextEvent, ({ payload }) => fn(payload));
+  }
+
+  /**
+   * @description Calls back with the bytes of each read event of a listener started with `decode: false`
+   * @param {Function} fn Callback receiving the bytes read
+   * @returns {Promise<UnlistenFn>} A promise that resolves to a function removing the callback
+   */
+  async onReadBytes(fn: (data: Uint8Array) => void): Promise<UnlistenFn> {
+    let sub_path = eventPath(this.options.path?.toString() ?? "")
+    let readBytesEvent = `plugin-serialplugin-read-bytes-${sub_path}`;
+    return await listen<ReadDataResult>(readBytesEvent, ({ payload }) => {
+      fn(typeof payload.data === 'string' ? decodeBase64(payload.data) : new Uint8Array(payload.data));
+    });
+  }
+
+  /**
+   * @description Calls back with each frame delimited by an idle gap, see `idleTimeout`
+   * @param {Function} fn Callback receiving the frame bytes
+   * @returns {Promise<UnlistenFn>} A promise that resolves to a function removing the callback
+   */
+  async onIdleFrame(fn: (frame: Uint8Array) => void): Promise<UnlistenFn> {
+    let sub_path = eventPath(this.options.path?.toString() ?? "")
+    let idleEvent = `plugin-serialplugin-idle-${sub_path}`;
+    return await listen<ReadDataResult>(idleEvent, ({ payload }) => {
+      fn(typeof payload.data === 'string' ? decodeBase64(payload.data) : new Uint8Array(payload.data));
+    });
+  }
+
+  /**
+   * @description Calls back with each frame found by the `framing` option
+   * @param {Function} fn Callback receiving the frame bytes, without delimiters
+   * @returns {Promise<UnlistenFn>} A promise that resolves to a function removing the callback
+   */
+  async onFrame(fn: (frame: Uint8Array) => void): Promise<UnlistenFn> {
+    let sub_path = eventPath(this.options.path?.toString() ?? "")
+    let frameEvent = `plugin-serialplugin-frame-${sub_path}`;
+    return await listen<ReadDataResult>(frameEvent, ({ payload }) => {
+      fn(typeof payload.data === 'string' ? decodeBase64(payload.data) : new Uint8Array(payload.data));
+    });
+  }
+
+  /**
+   * @description Calls back with each line parsed by the `Framing.Json` framing
+   * @param {Function} fn Callback receiving the parsed value
+   * @returns {Promise<UnlistenFn>} A promise that resolves to a function removing the callback
+   */
+  async onJson(fn: (value: unknown) => void): Promise<UnlistenFn> {
+    let sub_path = eventPath(this.options.path?.toString() ?? "")
+    let jsonEvent = `plugin-serialplugin-json-${sub_path}`;
+    return await listen<JsonData>(jsonEvent, ({ payload }) => fn(payload.value));
+  }
+
+  /**
+   * @description Opens the serial port with current settings.
+   * `options.path` is replaced by the device node it resolves to, e.g. `/dev/ttyUSB0` for a `/dev/serial/by-id` link,
+   * which the port is managed and its events named under.
    * @returns {Promise<void>} A promise that resolves when the port is opened
    */
   async open(): Promise<void> {
@@ -354,18 +989,25 @@ class SerialPort {
       if (this.isOpen) {
         return;
       }
-      let res = undefined;
       if (this.is_test) {
         tester_ports[this.options.path] = this
       } else {
-        res = await invoke<void>('plugin:serialplugin|open', {
+        this.options.path = await invoke<string>('plugin:serialplugin|open', {
           path: this.options.path,
-          baudRate: this.options.baudRate,
-          dataBits: this.options.dataBits,
-          flowControl: this.options.flowControl,
-          parity: this.options.parity,
-          stopBits: this.options.stopBits,
-          timeout: this.options.timeout,
+          config: {
+            baudRate: this.options.baudRate,
+            dataBits: this.options.dataBits,
+            flowControl: this.options.flowControl,
+            parity: this.options.parity,
+            stopBits: this.options.stopBits,
+            timeout: this.options.timeout,
+            openRetries: this.options.openRetries,
+            retryDelayMs: this.options.retryDelayMs,
+            failIfOpen: this.options.failIfOpen,
+            exclusive: this.options.exclusive,
+            initialDtr: this.options.initialDtr,
+            initialRts: this.options.initialRts,
+          },
         });
       }
 
@@ -374,42 +1016,223 @@ class SerialPort {
       this.disconnected(() => {
         this.isOpen = false;
       }).catch(err => console.error(err))
-      return Promise.resolve(res);
+      return;
     } catch (error) {
       return Promise.reject(error);
     }
   }
 
-
   /**
-   * Starts listening for data on the serial port
-   * The port will continuously monitor for incoming data and emit events
-   * @returns {Promise<void>} A promise that resolves when listening starts
-   * @throws {Error} If starting listener fails or port is not open
-   * @example
-   * const port = new SerialPort({ path: '/dev/ttyUSB0' });
-   * await port.startListening();
-   * // Listen for data events
-   * port.listen((data) => {
-   *   console.log('listen', data)
-   *   receivedData += data;
-   * });
+   * @description Opens the serial port with saved settings, such as a preset from `exportConfig`
+   * @param {PortConfig} config The line settings to open the port with; they replace those in the options
+   * @returns {Promise<void>} A promise that resolves when the port is opened
    */
-  async startListening(): Promise<void> {
+  async openWithConfig(config: PortConfig): Promise<void> {
     try {
-      await invoke<string>('plugin:serialplugin|start_listening', {
-        path: this.options.path,
-        size: this.options.size,
-        timeout: this.options.timeout,
-      });
+      if (!this.options.path) {
+        return Promise.reject(`path Can not be empty!`);
+      }
+      if (this.isOpen) {
+        return;
+      }
+      this.options.baudRate = config.baud_rate;
+      this.options.dataBits = config.data_bits;
+      this.options.flowControl = config.flow_control;
+      this.options.parity = config.parity;
+      this.options.stopBits = config.stop_bits;
+      this.options.timeout = config.timeout;
+      if (this.is_test) {
+        tester_ports[this.options.path] = this
+      } else {
+        this.options.path = await invoke<string>('plugin:serialplugin|open_with_config', {
+          path: this.options.path,
+          config,
+        });
+      }
+
+      this.isOpen = true;
+
+      this.disconnected(() => {
+        this.isOpen = false;
+      }).catch(err => console.error(err))
     } catch (error) {
       return Promise.reject(error);
     }
   }
 
   /**
-   * Stops listening for data on the serial port
-   * Cleans up event listeners and monitoring threads
+   * @description Waits for the port to appear, then opens it
+   * @param {number} timeout Maximum time to wait, in milliseconds
+   * @param {UsbMatch} [usb] Wait for this USB device instead of `options.path`; the matched path is stored in `options.path`
+   * @returns {Promise<void>} A promise that resolves when the port is open
+   */
+  async openWhenAvailable(timeout: number, usb?: UsbMatch): Promise<void> {
+    try {
+      if (!usb && !this.options.path) {
+        return Promise.reject(`path Can not be empty!`);
+      }
+      if (!this.options.baudRate) {
+        return Promise.reject(`baudRate Can not be empty!`);
+      }
+      if (this.isOpen) {
+        return;
+      }
+      this.options.path = await invoke<string>('plugin:serialplugin|open_when_available', {
+        target: usb || this.options.path,
+        config: {
+          baudRate: this.options.baudRate,
+          dataBits: this.options.dataBits,
+          flowControl: this.options.flowControl,
+          parity: this.options.parity,
+          stopBits: this.options.stopBits,
+          timeout: this.options.timeout,
+          exclusive: this.options.exclusive,
+          initialDtr: this.options.initialDtr,
+          initialRts: this.options.initialRts,
+        },
+        timeout,
+      });
+
+      this.isOpen = true;
+
+      this.disconnected(() => {
+        this.isOpen = false;
+      }).catch(err => console.error(err))
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Cancels every `openWhenAvailable` call that is still waiting
+   * @returns {Promise<void>} A promise that resolves when the waits are cancelled
+   */
+  static async cancelOpenWhenAvailable(): Promise<void> {
+    return await invoke<void>('plugin:serialplugin|cancel_open_when_available');
+  }
+
+  /**
+   * @description Registers a USB adapter the Android driver prober doesn't know (no-op on desktop)
+   * @param {number} vid USB vendor id
+   * @param {number} pid USB product id
+   * @param {string} [driver] Protocol of the adapter: 'cdc' (default), 'ch34x', 'cp21xx', 'ftdi' or 'prolific'
+   * @returns {Promise<void>} A promise that resolves when the filter is registered
+   */
+  static async addUsbFilter(vid: number, pid: number, driver?: string): Promise<void> {
+    return await invoke<void>('plugin:serialplugin|add_usb_filter', { vid, pid, driver });
+  }
+
+  /**
+   * @description Checks whether the plugin has a port open
+   * @param {string} path The port path
+   * @returns {Promise<boolean>} A promise that resolves to whether the port is open
+   */
+  static async isPortOpen(path: string): Promise<boolean> {
+    return await invoke<boolean>('plugin:serialplugin|is_open', { path });
+  }
+
+  /**
+   * @description Lists the ports the plugin has open (desktop only)
+   * @returns {Promise<string[]>} A promise that resolves to the open port paths
+   */
+  static async managedPorts(): Promise<string[]> {
+    return await invoke<string[]>('plugin:serialplugin|managed_ports');
+  }
+
+  /**
+   * @description Lists the ports the plugin has open with their settings and listener status (desktop only)
+   * @returns {Promise<{ [key: string]: ManagedPortInfo }>} A promise that resolves to the open ports by path
+   */
+  static async managedPortsDetailed(): Promise<{ [key: string]: ManagedPortInfo }> {
+    return await invoke<{ [key: string]: ManagedPortInfo }>('plugin:serialplugin|managed_ports_detailed');
+  }
+
+  /**
+   * @description Checks whether the app may open a USB device (Android; always true on desktop)
+   * @param {string} path The port path
+   * @returns {Promise<boolean>} A promise that resolves to whether access is granted
+   */
+  static async hasPermission(path: string): Promise<boolean> {
+    return await invoke<boolean>('plugin:serialplugin|has_permission', { path });
+  }
+
+  /**
+   * @description Asks the user for access to a USB device (Android; always true on desktop)
+   * @param {string} path The port path
+   * @returns {Promise<boolean>} A promise that resolves to whether the user granted access
+   */
+  static async requestPermission(path: string): Promise<boolean> {
+    return await invoke<boolean>('plugin:serialplugin|request_permission', { path });
+  }
+
+  /**
+   * Starts listening for data on the serial port
+   * The port will continuously monitor for incoming data and emit events
+   * On desktop calls are counted: if a listener is already running it is shared,
+   * keeping its options, until every call has been matched by `stopListening`
+   * @returns {Promise<void>} A promise that resolves when listening starts
+   * @throws {Error} If starting listener fails or port is not open
+   * @example
+   * const port = new SerialPort({ path: '/dev/ttyUSB0' });
+   * await port.startListening();
+   * // Listen for data events
+   * port.listen((data) => {
+   *   console.log('listen', data)
+   *   receivedData += data;
+   * });
+   */
+  async startListening(): Promise<void> {
+    try {
+      await invoke<string>('plugin:serialplugin|start_listening', {
+        path: this.options.path,
+        size: this.options.size,
+        timeout: this.options.timeout,
+        base64: this.options.base64,
+        bufferReads: this.options.bufferReads,
+        idleTimeout: this.options.idleTimeout,
+        framing: this.options.framing,
+        emitStrategy: this.options.emitStrategy,
+        maxBufferSize: this.options.maxBufferSize,
+        decode: this.options.decode,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Pauses the listener without stopping its thread
+   * @param {boolean} [stopReading=true] Leave incoming bytes for `read` instead of holding them back until `resumeListening`
+   * @returns {Promise<void>} A promise that resolves when the listener is paused
+   */
+  async pauseListening(stopReading?: boolean): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|pause_listening', {
+        path: this.options.path,
+        stopReading
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Resumes a listener paused with `pauseListening`
+   * @returns {Promise<void>} A promise that resolves when the listener is resumed
+   */
+  async resumeListening(): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|resume_listening', {
+        path: this.options.path
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * Stops listening for data on the serial port
+   * Cleans up event listeners and monitoring threads once no other `startListening` call still uses them
    * @returns {Promise<void>} A promise that resolves when listening stops
    * @throws {Error} If stopping listener fails or port is not open
    * @example
@@ -425,10 +1248,50 @@ class SerialPort {
     }
   }
 
+  /**
+   * @description Returns up to `size` bytes of pending input without consuming them.
+   * Peeked bytes are returned again by the next `read` before any new data.
+   * @param {number} size Maximum number of bytes to inspect
+   * @returns {Promise<Uint8Array>} A promise that resolves to the buffered bytes
+   */
+  async peek(size: number): Promise<Uint8Array> {
+    try {
+      const data = await invoke<number[]>('plugin:serialplugin|peek', {
+        path: this.options.path,
+        size
+      });
+      return new Uint8Array(data);
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Discards stale input, writes a request and reads the response in one step
+   * @param {Uint8Array | number[]} request The request bytes
+   * @param {number} readSize Number of response bytes to wait for
+   * @param {number} responseTimeout Maximum time to wait for the response, in milliseconds
+   * @returns {Promise<Uint8Array>} A promise that resolves to the response, shorter than `readSize` on timeout
+   */
+  async transaction(request: Uint8Array | number[], readSize: number, responseTimeout: number): Promise<Uint8Array> {
+    try {
+      const response = await invoke<number[]>('plugin:serialplugin|transaction', {
+        path: this.options.path,
+        request: Array.from(request),
+        readSize,
+        responseTimeout
+      });
+      return new Uint8Array(response);
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
   /**
    * @description Reads data from the serial port
    * @param {ReadOptions} [options] Read options
    * @returns {Promise<void>} A promise that resolves when data is read
+   * @throws {TimeoutError} If no data arrived in time, see `isTimeoutError`
    */
   async read(options?: ReadOptions): Promise<string> {
     try {
@@ -442,8 +1305,368 @@ class SerialPort {
 
       return await invoke<string>('plugin:serialplugin|read', {
         path: this.options.path,
-        timeout: options?.timeout || this.options.timeout,
-        size: options?.size || this.size,
+        timeout: options?.timeout === undefined ? this.options.timeout : options.timeout,
+        size: options?.size || this.size,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Reads raw bytes from the serial port
+   * @param {ReadOptions} [options] Read options
+   * @returns {Promise<Uint8Array>} A promise that resolves to the bytes read
+   */
+  async readBinary(options?: ReadOptions): Promise<Uint8Array> {
+    try {
+      if (this.is_test) {
+        return Promise.resolve(new Uint8Array());
+      }
+
+      const data = await invoke<number[]>('plugin:serialplugin|read_binary', {
+        path: this.options.path,
+        timeout: options?.timeout === undefined ? this.options.timeout : options.timeout,
+        size: options?.size || this.size,
+        minSize: options?.minSize,
+      });
+      return new Uint8Array(data);
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Reads from the serial port and returns the bytes as hex
+   * @param {ReadOptions} [options] Read options
+   * @returns {Promise<string>} A promise that resolves to uppercase hex separated by spaces, e.g. "01 03 0A"
+   */
+  async readHex(options?: ReadOptions): Promise<string> {
+    try {
+      return await invoke<string>('plugin:serialplugin|read_hex', {
+        path: this.options.path,
+        timeout: options?.timeout === undefined ? this.options.timeout : options.timeout,
+        size: options?.size || this.size,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Reads exactly `size` bytes, e.g. the body of a length-prefixed message
+   * @param {number} size How many bytes to read
+   * @param {number} [timeout] How long to wait for all of them, in milliseconds
+   * @returns {Promise<Uint8Array>} A promise that resolves to the bytes read
+   * @throws {PartialReadError} If only some bytes arrived in time, carrying them; see `isPartialReadError`
+   * @throws {TimeoutError} If no bytes arrived in time
+   */
+  async readExactOrPartial(size: number, timeout?: number): Promise<Uint8Array> {
+    try {
+      const data = await invoke<number[]>('plugin:serialplugin|read_exact_or_partial', {
+        path: this.options.path,
+        size,
+        timeout: timeout ?? this.options.timeout,
+      });
+      return new Uint8Array(data);
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Returns whatever is buffered right now without waiting, or an empty array when nothing is; for poll loops
+   * @param {number} [maxSize] Most bytes to return, `options.size` by default
+   * @returns {Promise<Uint8Array>} A promise that resolves to the bytes read, possibly none
+   */
+  async readAvailable(maxSize?: number): Promise<Uint8Array> {
+    try {
+      if (this.is_test) {
+        return Promise.resolve(new Uint8Array());
+      }
+
+      const data = await invoke<number[]>('plugin:serialplugin|read_available', {
+        path: this.options.path,
+        maxSize: maxSize || this.size,
+      });
+      return new Uint8Array(data);
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Reads once and returns the data both as bytes and as text, e.g. for a hex and a text view
+   * @param {ReadOptions} [options] Read options
+   * @returns {Promise<BytesAndText>} A promise that resolves to the bytes read and their text
+   */
+  async readBoth(options?: ReadOptions): Promise<BytesAndText> {
+    try {
+      const result = await invoke<{ bytes: number[]; text: string }>('plugin:serialplugin|read_both', {
+        path: this.options.path,
+        timeout: options?.timeout === undefined ? this.options.timeout : options.timeout,
+        size: options?.size || this.size,
+      });
+      return { bytes: new Uint8Array(result.bytes), text: result.text };
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+
+  /**
+   * @description Enables RS485 half-duplex mode, toggling RTS around every write.
+   * On Linux drivers with RS485 support the driver does the toggling itself
+   * @param {Rs485Config | null} config RS485 settings, or null to disable
+   * @returns {Promise<void>} A promise that resolves when the mode is applied
+   */
+  async setRs485(config: Rs485Config | null): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|set_rs485', {
+        path: this.options.path,
+        config
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Sets the baud rate of the serial port; non-standard rates are passed to the driver as is
+   * @param {number} value The new baud rate
+   * @returns {Promise<number>} A promise that resolves to the rate the driver actually set, which differs if it was rounded
+   */
+  async setBaudRate(value: number): Promise<number> {
+    try {
+      return await invoke<number>('plugin:serialplugin|set_baud_rate', {
+        path: this.options.path,
+        baudRate: value
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Sets the data bits configuration
+   * @param {DataBits} value The new data bits setting
+   * @returns {Promise<void>} A promise that resolves when data bits are set
+   */
+  async setDataBits(value: DataBits): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|set_data_bits', {
+        path: this.options.path,
+        dataBits: value
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Sets the flow control mode
+   * @param {FlowControl} value The new flow control setting
+   * @returns {Promise<void>} A promise that resolves when flow control is set
+   */
+  async setFlowControl(value: FlowControl): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|set_flow_control', {
+        path: this.options.path,
+        flowControl: value
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Chooses the XON/XOFF bytes the OS driver uses for software flow control. Loopback and network ports only support the standard bytes (0x11/0x13)
+   * @param {number} xon The XON byte
+   * @param {number} xoff The XOFF byte
+   * @returns {Promise<void>} A promise that resolves when the bytes are applied
+   */
+  async setFlowControlChars(xon: number, xoff: number): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|set_flow_control_chars', {
+        path: this.options.path,
+        xon,
+        xoff
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Gets the XON/XOFF bytes chosen for software flow control
+   * @returns {Promise<FlowControlChars>} A promise that resolves to the XON/XOFF bytes
+   */
+  async getFlowControlChars(): Promise<FlowControlChars> {
+    try {
+      return await invoke<FlowControlChars>('plugin:serialplugin|get_flow_control_chars', {
+        path: this.options.path
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Sets the parity checking mode
+   * @param {Parity} value The new parity setting
+   * @returns {Promise<void>} A promise that resolves when parity is set
+   */
+  async setParity(value: Parity): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|set_parity', {
+        path: this.options.path,
+        parity: value
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Sets the number of stop bits
+   * @param {StopBits} value The new stop bits setting
+   * @returns {Promise<void>} A promise that resolves when stop bits are set
+   */
+  async setStopBits(value: StopBits): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|set_stop_bits', {
+        path: this.options.path,
+        stopBits: value
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Sets the timeout duration
+   * @param {number} value The new timeout in milliseconds
+   * @returns {Promise<void>} A promise that resolves when timeout is set
+   */
+  async setTimeout(value: number): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|set_timeout', {
+        path: this.options.path,
+        timeout: value
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Sets the timeout used by reads, overriding the port timeout
+   * @param {number} value Timeout in milliseconds
+   * @returns {Promise<void>} A promise that resolves when the timeout is set
+   */
+  async setReadTimeout(value: number): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|set_read_timeout', {
+        path: this.options.path,
+        timeout: value
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Sets the timeout used by writes, overriding the port timeout
+   * @param {number} value Timeout in milliseconds
+   * @returns {Promise<void>} A promise that resolves when the timeout is set
+   */
+  async setWriteTimeout(value: number): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|set_write_timeout', {
+        path: this.options.path,
+        timeout: value
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Holds writes until the device asserts CTS, doing the RTS/CTS handshake in software
+   * @param {boolean} enabled Whether writes wait for CTS
+   * @param {number} [timeout] Milliseconds a write waits for CTS before failing (default 1000)
+   * @returns {Promise<void>} A promise that resolves when the setting is applied
+   */
+  async setWaitForCts(enabled: boolean, timeout?: number): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|set_wait_for_cts', {
+        path: this.options.path,
+        enabled,
+        timeout
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Sends written data one byte at a time with a pause between bytes, for devices with tiny receive buffers
+   * @param {number} delayMs Pause between bytes in milliseconds, 0 to write whole buffers again
+   * @returns {Promise<void>} A promise that resolves when the delay is set
+   */
+  async setInterByteDelay(delayMs: number): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|set_inter_byte_delay', {
+        path: this.options.path,
+        delayMs
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Emits an event after each write, see `onWriteComplete`
+   * @param {boolean} enabled Whether write events are emitted
+   * @returns {Promise<void>} A promise that resolves when the setting is applied
+   */
+  async setWriteEvents(enabled: boolean): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|set_write_events', {
+        path: this.options.path,
+        enabled
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Splits writes into flushed chunks and reports progress after each, see `onWriteProgress`
+   * @param {number} chunkSize Chunk size in bytes, 0 to write whole buffers again
+   * @returns {Promise<void>} A promise that resolves when the chunk size is set
+   */
+  async setWriteChunkSize(chunkSize: number): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|set_write_chunk_size', {
+        path: this.options.path,
+        chunkSize
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Sets the RTS (Request To Send) control signal
+   * @param {boolean} value The signal level to set
+   * @returns {Promise<void>} A promise that resolves when RTS is set
+   */
+  async setRequestToSend(value: boolean): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|write_request_to_send', {
+        path: this.options.path,
+        level: value
       });
     } catch (error) {
       return Promise.reject(error);
@@ -451,15 +1674,15 @@ class SerialPort {
   }
 
   /**
-   * @description Sets the baud rate of the serial port
-   * @param {number} value The new baud rate
-   * @returns {Promise<void>} A promise that resolves when baud rate is set
+   * @description Sets the DTR (Data Terminal Ready) control signal
+   * @param {boolean} value The signal level to set
+   * @returns {Promise<void>} A promise that resolves when DTR is set
    */
-  async setBaudRate(value: number): Promise<void> {
+  async setDataTerminalReady(value: boolean): Promise<void> {
     try {
-      return await invoke<void>('plugin:serialplugin|set_baud_rate', {
+      return await invoke<void>('plugin:serialplugin|write_data_terminal_ready', {
         path: this.options.path,
-        baudRate: value
+        level: value
       });
     } catch (error) {
       return Promise.reject(error);
@@ -467,15 +1690,17 @@ class SerialPort {
   }
 
   /**
-   * @description Sets the data bits configuration
-   * @param {DataBits} value The new data bits setting
-   * @returns {Promise<void>} A promise that resolves when data bits are set
+   * @description Sets DTR and then RTS in one call, with nothing else reaching the port in between
+   * @param {boolean} [dtr] The DTR level, or nothing to leave it unchanged
+   * @param {boolean} [rts] The RTS level, or nothing to leave it unchanged
+   * @returns {Promise<void>} A promise that resolves when both lines are set
    */
-  async setDataBits(value: DataBits): Promise<void> {
+  async setControlLines(dtr?: boolean, rts?: boolean): Promise<void> {
     try {
-      return await invoke<void>('plugin:serialplugin|set_data_bits', {
+      return await invoke<void>('plugin:serialplugin|set_control_lines', {
         path: this.options.path,
-        dataBits: value
+        dtr,
+        rts
       });
     } catch (error) {
       return Promise.reject(error);
@@ -483,15 +1708,15 @@ class SerialPort {
   }
 
   /**
-   * @description Sets the flow control mode
-   * @param {FlowControl} value The new flow control setting
-   * @returns {Promise<void>} A promise that resolves when flow control is set
+   * @description Resets an Arduino-style board by pulsing DTR low, then high again
+   * @param {number} [pulseMs=100] How long DTR is held low, in milliseconds
+   * @returns {Promise<void>} A promise that resolves when the pulse is complete
    */
-  async setFlowControl(value: FlowControl): Promise<void> {
+  async resetViaDtr(pulseMs?: number): Promise<void> {
     try {
-      return await invoke<void>('plugin:serialplugin|set_flow_control', {
+      return await invoke<void>('plugin:serialplugin|reset_via_dtr', {
         path: this.options.path,
-        flowControl: value
+        pulseMs
       });
     } catch (error) {
       return Promise.reject(error);
@@ -499,15 +1724,13 @@ class SerialPort {
   }
 
   /**
-   * @description Sets the parity checking mode
-   * @param {Parity} value The new parity setting
-   * @returns {Promise<void>} A promise that resolves when parity is set
+   * @description Resets an ESP32/ESP8266 into its serial bootloader (esptool's classic DTR/RTS reset)
+   * @returns {Promise<void>} A promise that resolves once the chip is in download mode
    */
-  async setParity(value: Parity): Promise<void> {
+  async enterEspBootloader(): Promise<void> {
     try {
-      return await invoke<void>('plugin:serialplugin|set_parity', {
-        path: this.options.path,
-        parity: value
+      return await invoke<void>('plugin:serialplugin|enter_esp_bootloader', {
+        path: this.options.path
       });
     } catch (error) {
       return Promise.reject(error);
@@ -515,15 +1738,13 @@ class SerialPort {
   }
 
   /**
-   * @description Sets the number of stop bits
-   * @param {StopBits} value The new stop bits setting
-   * @returns {Promise<void>} A promise that resolves when stop bits are set
+   * @description Resets an ESP32/ESP8266 into its normal firmware by pulsing EN through RTS
+   * @returns {Promise<void>} A promise that resolves when the reset is complete
    */
-  async setStopBits(value: StopBits): Promise<void> {
+  async resetEsp(): Promise<void> {
     try {
-      return await invoke<void>('plugin:serialplugin|set_stop_bits', {
-        path: this.options.path,
-        stopBits: value
+      return await invoke<void>('plugin:serialplugin|reset_esp', {
+        path: this.options.path
       });
     } catch (error) {
       return Promise.reject(error);
@@ -531,15 +1752,26 @@ class SerialPort {
   }
 
   /**
-   * @description Sets the timeout duration
-   * @param {number} value The new timeout in milliseconds
-   * @returns {Promise<void>} A promise that resolves when timeout is set
+   * @description Performs the "1200bps touch" to enter the bootloader of native-USB boards
+   * @param {string} path The path of the serial port, which must not be open
+   * @returns {Promise<void>} A promise that resolves when the touch is complete
    */
-  async setTimeout(value: number): Promise<void> {
+  static async triggerBootloader1200(path: string): Promise<void> {
+    return await invoke<void>('plugin:serialplugin|trigger_bootloader_1200', { path });
+  }
+
+  /**
+   * @description Writes the given bytes at a fixed interval until stopped or the port closes
+   * @param {Uint8Array | number[]} value The heartbeat bytes
+   * @param {number} intervalMs Time between heartbeats, in milliseconds
+   * @returns {Promise<void>} A promise that resolves when the heartbeat has started
+   */
+  async startHeartbeat(value: Uint8Array | number[], intervalMs: number): Promise<void> {
     try {
-      return await invoke<void>('plugin:serialplugin|set_timeout', {
+      return await invoke<void>('plugin:serialplugin|start_heartbeat', {
         path: this.options.path,
-        timeout: value
+        value: Array.from(value),
+        intervalMs
       });
     } catch (error) {
       return Promise.reject(error);
@@ -547,15 +1779,15 @@ class SerialPort {
   }
 
   /**
-   * @description Sets the RTS (Request To Send) control signal
-   * @param {boolean} value The signal level to set
-   * @returns {Promise<void>} A promise that resolves when RTS is set
+   * @description Closes the port once it has gone the given time without a read or write
+   * @param {number} [timeoutMs] Idle time in milliseconds; leave out to turn the watchdog off
+   * @returns {Promise<void>} A promise that resolves when the watchdog is set up
    */
-  async setRequestToSend(value: boolean): Promise<void> {
+  async idleAutoClose(timeoutMs?: number): Promise<void> {
     try {
-      return await invoke<void>('plugin:serialplugin|write_request_to_send', {
+      return await invoke<void>('plugin:serialplugin|idle_auto_close', {
         path: this.options.path,
-        level: value
+        timeout: timeoutMs
       });
     } catch (error) {
       return Promise.reject(error);
@@ -563,15 +1795,13 @@ class SerialPort {
   }
 
   /**
-   * @description Sets the DTR (Data Terminal Ready) control signal
-   * @param {boolean} value The signal level to set
-   * @returns {Promise<void>} A promise that resolves when DTR is set
+   * @description Stops the heartbeat started by `startHeartbeat`
+   * @returns {Promise<void>} A promise that resolves when the heartbeat has stopped
    */
-  async setDataTerminalReady(value: boolean): Promise<void> {
+  async stopHeartbeat(): Promise<void> {
     try {
-      return await invoke<void>('plugin:serialplugin|write_data_terminal_ready', {
-        path: this.options.path,
-        level: value
+      return await invoke<void>('plugin:serialplugin|stop_heartbeat', {
+        path: this.options.path
       });
     } catch (error) {
       return Promise.reject(error);
@@ -634,6 +1864,98 @@ class SerialPort {
     }
   }
 
+  /**
+   * @description Reads the CTS, DSR, RI and CD lines in one call
+   * @returns {Promise<ModemStatus>} A promise that resolves to the state of all four lines
+   */
+  async getModemStatus(): Promise<ModemStatus> {
+    try {
+      return await invoke<ModemStatus>('plugin:serialplugin|get_modem_status', {
+        path: this.options.path
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Watches the CTS/DSR/RI/CD lines and calls back whenever one of them changes
+   * @param {Function} fn Callback receiving the new modem status
+   * @param {number} [pollInterval=100] How often the lines are sampled, in milliseconds
+   * @returns {Promise<UnlistenFn>} A promise that resolves to a function removing the callback
+   */
+  async watchModemSignals(fn: (status: ModemStatus) => void, pollInterval?: number): Promise<UnlistenFn> {
+    try {
+      let sub_path = eventPath(this.options.path?.toString() ?? "")
+      let signalsEvent = `plugin-serialplugin-signals-${sub_path}`;
+      const unListen = await listen<ModemStatus>(signalsEvent, ({ payload }) => fn(payload));
+      await invoke<void>('plugin:serialplugin|watch_modem_signals', {
+        path: this.options.path,
+        pollInterval
+      });
+      return unListen;
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Stops watching the modem status lines
+   * @returns {Promise<void>} A promise that resolves when watching stops
+   */
+  async stopWatchingModemSignals(): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|stop_watching_modem_signals', {
+        path: this.options.path
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Gets the parity, framing and overrun error counts of the port (desktop Linux only)
+   * @returns {Promise<LineErrors>} A promise that resolves to the counts since the port was opened
+   */
+  async getLineErrors(): Promise<LineErrors> {
+    try {
+      return await invoke<LineErrors>('plugin:serialplugin|get_line_errors', {
+        path: this.options.path
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Sets the latency timer of an FTDI adapter, which holds received bytes back for 16 ms by default
+   * (Linux and Windows; on Windows it needs administrator rights and applies once the port is reopened, e.g. with `reconnect`;
+   * other adapters and platforms fail with an "Unsupported" error)
+   * @param {number} ms The latency in milliseconds, 1 to 255
+   * @returns {Promise<void>} A promise that resolves when the timer is set
+   */
+  async setLatencyTimer(ms: number): Promise<void> {
+    try {
+      await invoke<void>('plugin:serialplugin|set_latency_timer', {
+        path: this.options.path,
+        ms
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Calls back when the line error counts go up while listening (desktop Linux only)
+   * @param {Function} fn Callback receiving the new counts
+   * @returns {Promise<UnlistenFn>} A promise that resolves to a function removing the callback
+   */
+  async onLineError(fn: (errors: LineErrors) => void): Promise<UnlistenFn> {
+    let sub_path = eventPath(this.options.path?.toString() ?? "")
+    let lineErrorEvent = `plugin-serialplugin-line-error-${sub_path}`;
+    return await listen<LineErrors>(lineErrorEvent, ({ payload }) => fn(payload));
+  }
+
   /**
    * @description Gets the number of bytes available to read
    * @returns {Promise<number>} A promise that resolves to the number of bytes
@@ -662,14 +1984,28 @@ class SerialPort {
     }
   }
 
+  /**
+   * @description Waits until everything written has been transmitted, without discarding anything
+   * @returns {Promise<void>} A promise that resolves once the output queue is empty
+   */
+  async drain(): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|drain', {
+        path: this.options.path
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
   /**
    * @description Clears the specified buffer
    * @param {ClearBuffer} buffer The buffer to clear
-   * @returns {Promise<void>} A promise that resolves when the buffer is cleared
+   * @returns {Promise<number>} A promise that resolves to the number of bytes discarded
    */
-  async clearBuffer(buffer: ClearBuffer): Promise<void> {
+  async clearBuffer(buffer: ClearBuffer): Promise<number> {
     try {
-      return await invoke<void>('plugin:serialplugin|clear_buffer', {
+      return await invoke<number>('plugin:serialplugin|clear_buffer', {
         path: this.options.path,
         bufferType: buffer
       });
@@ -706,6 +2042,142 @@ class SerialPort {
     }
   }
 
+  /**
+   * @description Transmits a break signal for a set time, timed by the plugin rather than the webview
+   * @param {number} duration Length of the break in milliseconds
+   * @returns {Promise<void>} A promise that resolves once the break has ended
+   */
+  async sendBreak(duration: number): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|send_break', {
+        path: this.options.path,
+        duration
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Gets whether the port is open, whether it is being listened to and its current settings
+   * @returns {Promise<PortState>} A promise that resolves to the port state
+   */
+  async getPortState(): Promise<PortState> {
+    try {
+      return await invoke<PortState>('plugin:serialplugin|get_port_state', {
+        path: this.options.path
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Gets the baud rates and features the port is expected to support, as a best guess from its path and USB vendor id.
+   * The port doesn't have to be open; not supported on mobile.
+   * @param {string} path The path of the serial port
+   * @returns {Promise<PortCapabilities>} A promise that resolves to the port capabilities
+   */
+  static async getPortCapabilities(path: string): Promise<PortCapabilities> {
+    return await invoke<PortCapabilities>('plugin:serialplugin|get_port_capabilities', {
+      path,
+    });
+  }
+
+  /**
+   * @description Gets the line settings of the open port as last applied, to save as a preset for `openWithConfig` (desktop only)
+   * @returns {Promise<PortConfig>} A promise that resolves to the port's settings
+   */
+  async exportConfig(): Promise<PortConfig> {
+    try {
+      return await invoke<PortConfig>('plugin:serialplugin|export_config', {
+        path: this.options.path
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Gets the traffic counters of the port since it was opened or last reset
+   * @returns {Promise<PortStats>} A promise that resolves to the port statistics
+   */
+  async getStats(): Promise<PortStats> {
+    try {
+      return await invoke<PortStats>('plugin:serialplugin|get_stats', {
+        path: this.options.path
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Resets the traffic counters of the port
+   * @returns {Promise<void>} A promise that resolves when the counters are reset
+   */
+  async resetStats(): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|reset_stats', {
+        path: this.options.path
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Records all traffic on the port to a file, replacing any running capture (desktop only)
+   * @param {string} filePath File to write, created or truncated; relative to the app log
+   * directory, and only allowed there or in the `captureDirs` of the plugin config
+   * @param {CaptureFormat} [format=CaptureFormat.Hexdump] File format
+   * @returns {Promise<void>} A promise that resolves when the capture has started
+   */
+  async startCapture(filePath: string, format?: CaptureFormat): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|start_capture', {
+        path: this.options.path,
+        filePath,
+        format: format || CaptureFormat.Hexdump
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Stops capturing traffic and closes the capture file
+   * @returns {Promise<void>} A promise that resolves when the capture has stopped
+   */
+  async stopCapture(): Promise<void> {
+    try {
+      return await invoke<void>('plugin:serialplugin|stop_capture', {
+        path: this.options.path
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Writes the TX records of a capture file back out of the port
+   * @param {string} filePath Capture file written by `startCapture`, in either format,
+   * resolved like for `startCapture`
+   * @param {boolean} [respectTiming=false] Keep the original gaps between writes
+   * @returns {Promise<number>} A promise that resolves to the number of bytes written
+   */
+  async replayCapture(filePath: string, respectTiming?: boolean): Promise<number> {
+    try {
+      return await invoke<number>('plugin:serialplugin|replay_capture', {
+        path: this.options.path,
+        filePath,
+        respectTiming
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
   /**
    * @description Writes string data to the serial port
    * @param {string} value The data to write
@@ -733,9 +2205,10 @@ class SerialPort {
   /**
    * @description Writes binary data to the serial port
    * @param {Uint8Array | number[]} value The binary data to write
+   * @param {number} [maxPending] Pace the transfer so the OS output buffer stays below this many bytes; a paced write can be stopped with `cancelOperation`
    * @returns {Promise<number>} A promise that resolves to the number of bytes written
    */
-  async writeBinary(value: Uint8Array | number[]): Promise<number> {
+  async writeBinary(value: Uint8Array | number[], maxPending?: number): Promise<number> {
     try {
       if (!this.isOpen) {
         return Promise.reject(`serial port ${this.options.path} not opened!`);
@@ -744,6 +2217,13 @@ class SerialPort {
         if (this.is_test) {
           return Promise.resolve(2);
         }
+        if (maxPending !== undefined) {
+          return await invoke<number>('plugin:serialplugin|write_binary_paced', {
+            value: Array.from(value),
+            path: this.options.path,
+            maxPending,
+          });
+        }
         return await invoke<number>('plugin:serialplugin|write_binary', {
           value: Array.from(value),
           path: this.options.path,
@@ -757,6 +2237,63 @@ class SerialPort {
       return Promise.reject(error);
     }
   }
+
+  /**
+   * @description Writes several binary messages in order in a single call
+   * @param {Array<Uint8Array | number[]>} messages The messages to write
+   * @param {number} [gapMs] Pause between messages in milliseconds
+   * @returns {Promise<number>} A promise that resolves to the total number of bytes written
+   */
+  async writeSequence(messages: Array<Uint8Array | number[]>, gapMs?: number): Promise<number> {
+    try {
+      return await invoke<number>('plugin:serialplugin|write_sequence', {
+        path: this.options.path,
+        messages: messages.map((message) => Array.from(message)),
+        gapMs
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description SLIP-encodes a payload as one frame and writes it
+   * @param {Uint8Array | number[]} payload The frame contents, before escaping
+   * @returns {Promise<number>} A promise that resolves to the number of encoded bytes written
+   */
+  async writeSlip(payload: Uint8Array | number[]): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`serial port ${this.options.path} not opened!`);
+      }
+      return await invoke<number>('plugin:serialplugin|write_slip', {
+        payload: Array.from(payload),
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description Writes bytes given as a hex string
+   * @param {string} value Hex bytes separated by spaces or colons, e.g. "01 03 00 00 00 0A"
+   * @returns {Promise<number>} A promise that resolves to the number of bytes written
+   */
+  async writeHex(value: string): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`serial port ${this.options.path} not opened!`);
+      }
+      return await invoke<number>('plugin:serialplugin|write_hex', {
+        value,
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
 }
 
 export { SerialPort };
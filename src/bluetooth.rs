@@ -0,0 +1,321 @@
+//! Remote device details of Bluetooth serial ports
+//!
+//! `serialport` only says that a port is a Bluetooth one, which makes several
+//! paired devices look alike. The remote address and name come from the
+//! platform: on Linux the RFCOMM binding of `/dev/rfcommN` is read with
+//! `RFCOMMGETDEVINFO` and the name asked from BlueZ with `bluetoothctl`; on
+//! macOS the port is matched by name against the paired devices IOBluetooth
+//! reports. Windows SPP ports carry the address in their device instance id,
+//! which only the direct port listing sees.
+
+use std::collections::HashMap;
+
+/// Key of the remote device's address, e.g. `98:D3:31:FB:12:34`
+const ADDRESS: &str = "bluetooth_address";
+/// Key of the remote device's name
+const NAME: &str = "bluetooth_name";
+
+/// Add the remote address and name of the Bluetooth port at `path` to its
+/// info, leaving out whatever the platform can't tell
+#[cfg_attr(
+    not(any(target_os = "linux", target_os = "macos")),
+    allow(unused_variables)
+)]
+pub fn add_details(path: &str, port_info: &mut HashMap<String, String>) {
+    #[cfg(target_os = "linux")]
+    if let Some(address) = linux::remote_address(path) {
+        if let Some(name) = linux::device_name(&address) {
+            port_info.insert(NAME.to_string(), name);
+        }
+        port_info.insert(ADDRESS.to_string(), address);
+    }
+
+    #[cfg(target_os = "macos")]
+    if let Some((name, address)) = macos::paired_device(path) {
+        port_info.insert(NAME.to_string(), name);
+        port_info.insert(ADDRESS.to_string(), address);
+    }
+}
+
+/// Add the address found in a Windows device instance id such as
+/// `BTHENUM\{00001101-...}_LOCALMFG&0002\8&2A6A6B8&0&98D331FB1234_C00000000`
+///
+/// Incoming ports, which aren't bound to a device, have an all-zero address
+/// and get none.
+#[cfg(target_os = "windows")]
+pub fn add_instance_address(instance_id: &str, port_info: &mut HashMap<String, String>) {
+    let last = instance_id.rsplit('\\').next().unwrap_or_default();
+    let hex = last.split('_').next().unwrap_or_default();
+    if hex.len() < 12 {
+        return;
+    }
+    let hex = &hex[hex.len() - 12..];
+    if !hex.bytes().all(|b| b.is_ascii_hexdigit()) || hex.bytes().all(|b| b == b'0') {
+        return;
+    }
+    let address = (0..12)
+        .step_by(2)
+        .map(|i| hex[i..i + 2].to_ascii_uppercase())
+        .collect::<Vec<_>>()
+        .join(":");
+    port_info.insert(ADDRESS.to_string(), address);
+}
+
+/// Stdout of `command`, or `None` if it fails or doesn't finish within a
+/// second; `bluetoothctl` waits forever when BlueZ isn't running
+///
+/// The output is read while the command runs, so a long one can't stall it
+/// on a full pipe.
+#[cfg(target_os = "linux")]
+fn command_output(command: &mut std::process::Command) -> Option<String> {
+    use std::io::Read;
+    use std::process::Stdio;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut stdout = child.stdout.take()?;
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut output = String::new();
+        let _ = tx.send(stdout.read_to_string(&mut output).map(|_| output));
+    });
+
+    // The pipe reaches its end when the command exits
+    let output = rx.recv_timeout(Duration::from_secs(1));
+    if output.is_err() {
+        let _ = child.kill();
+    }
+    let _ = child.wait();
+    output.ok()?.ok()
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::sync::{Mutex, OnceLock};
+    use std::time::{Duration, Instant};
+
+    /// How long a name asked from BlueZ is reused, so listing the ports again
+    /// doesn't run `bluetoothctl` for every Bluetooth port each time
+    const NAME_TTL: Duration = Duration::from_secs(30);
+
+    /// Names by address, with when they were asked for
+    type NameCache = Mutex<HashMap<String, (Instant, Option<String>)>>;
+
+    /// `struct rfcomm_dev_info` from the kernel's `net/bluetooth/rfcomm.h`
+    #[repr(C)]
+    #[derive(Default)]
+    struct RfcommDevInfo {
+        id: i16,
+        flags: u32,
+        state: u16,
+        src: [u8; 6],
+        dst: [u8; 6],
+        channel: u8,
+    }
+
+    const BTPROTO_RFCOMM: libc::c_int = 3;
+    /// `_IOR('R', 211, int)`
+    const RFCOMMGETDEVINFO: libc::c_ulong = 0x8004_52D3;
+
+    /// Address of the device `/dev/rfcommN` is bound to
+    pub fn remote_address(path: &str) -> Option<String> {
+        let id = Path::new(path)
+            .file_name()?
+            .to_str()?
+            .strip_prefix("rfcomm")?
+            .parse()
+            .ok()?;
+
+        // SAFETY: plain socket call; the descriptor is closed below
+        let socket = unsafe { libc::socket(libc::AF_BLUETOOTH, libc::SOCK_RAW, BTPROTO_RFCOMM) };
+        if socket < 0 {
+            return None;
+        }
+        let mut info = RfcommDevInfo {
+            id,
+            ..Default::default()
+        };
+        // SAFETY: `info` matches the layout the kernel reads and writes for
+        // RFCOMMGETDEVINFO
+        let result = unsafe { libc::ioctl(socket, RFCOMMGETDEVINFO as _, &mut info) };
+        // SAFETY: `socket` was opened above and isn't used afterwards
+        unsafe { libc::close(socket) };
+        if result < 0 {
+            return None;
+        }
+
+        // `bdaddr_t` is stored least significant byte first
+        Some(
+            info.dst
+                .iter()
+                .rev()
+                .map(|byte| format!("{:02X}", byte))
+                .collect::<Vec<_>>()
+                .join(":"),
+        )
+    }
+
+    /// Name BlueZ knows the device at `address` by, preferring the alias the
+    /// user may have given it
+    ///
+    /// Answers are cached for `NAME_TTL`, failed lookups too.
+    pub fn device_name(address: &str) -> Option<String> {
+        static NAMES: OnceLock<NameCache> = OnceLock::new();
+        let names = NAMES.get_or_init(Default::default);
+        if let Some((looked_up, name)) =
+            names.lock().unwrap_or_else(|e| e.into_inner()).get(address)
+        {
+            if looked_up.elapsed() < NAME_TTL {
+                return name.clone();
+            }
+        }
+
+        let name = look_up_name(address);
+        names
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(address.to_string(), (Instant::now(), name.clone()));
+        name
+    }
+
+    fn look_up_name(address: &str) -> Option<String> {
+        let info = super::command_output(
+            std::process::Command::new("bluetoothctl")
+                .arg("info")
+                .arg(address),
+        )?;
+        let field = |key: &str| {
+            info.lines()
+                .find_map(|line| line.trim().strip_prefix(key))
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        field("Alias:").or_else(|| field("Name:"))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod macos {
+    use std::ffi::{c_char, c_void, CStr};
+
+    type Id = *mut c_void;
+    type Sel = *mut c_void;
+
+    #[link(name = "IOBluetooth", kind = "framework")]
+    extern "C" {}
+
+    #[link(name = "objc")]
+    extern "C" {
+        fn objc_getClass(name: *const c_char) -> Id;
+        fn sel_registerName(name: *const c_char) -> Sel;
+        fn objc_msgSend();
+        fn objc_autoreleasePoolPush() -> *mut c_void;
+        fn objc_autoreleasePoolPop(pool: *mut c_void);
+    }
+
+    /// Selector of the method `name`, given with a trailing NUL
+    unsafe fn selector(name: &[u8]) -> Sel {
+        sel_registerName(name.as_ptr().cast())
+    }
+
+    /// Call a method taking no arguments and returning an object
+    unsafe fn send(receiver: Id, method: &[u8]) -> Id {
+        let send: unsafe extern "C" fn(Id, Sel) -> Id =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        send(receiver, selector(method))
+    }
+
+    /// `[array count]`
+    unsafe fn count(array: Id) -> usize {
+        let send: unsafe extern "C" fn(Id, Sel) -> usize =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        send(array, selector(b"count\0"))
+    }
+
+    /// `[array objectAtIndex:index]`
+    unsafe fn object_at(array: Id, index: usize) -> Id {
+        let send: unsafe extern "C" fn(Id, Sel, usize) -> Id =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        send(array, selector(b"objectAtIndex:\0"), index)
+    }
+
+    /// Contents of an `NSString`, `None` for nil
+    unsafe fn string(object: Id) -> Option<String> {
+        if object.is_null() {
+            return None;
+        }
+        let send: unsafe extern "C" fn(Id, Sel) -> *const c_char =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let chars = send(object, selector(b"UTF8String\0"));
+        if chars.is_null() {
+            return None;
+        }
+        Some(CStr::from_ptr(chars).to_string_lossy().into_owned())
+    }
+
+    /// Name and address of every paired device, from
+    /// `[IOBluetoothDevice pairedDevices]`
+    fn paired_devices() -> Vec<(String, String)> {
+        let mut devices = Vec::new();
+        // SAFETY: the methods are called with the signatures IOBluetooth and
+        // Foundation declare them with, on objects checked for nil, and the
+        // autoreleased results are only used inside the pool
+        unsafe {
+            let pool = objc_autoreleasePoolPush();
+            let class = objc_getClass(b"IOBluetoothDevice\0".as_ptr().cast());
+            let list = if class.is_null() {
+                std::ptr::null_mut()
+            } else {
+                send(class, b"pairedDevices\0")
+            };
+            let total = if list.is_null() { 0 } else { count(list) };
+            for index in 0..total {
+                let device = object_at(list, index);
+                let name = string(send(device, b"name\0"));
+                let address = string(send(device, b"addressString\0"));
+                if let (Some(name), Some(address)) = (name, address) {
+                    devices.push((name, address));
+                }
+            }
+            objc_autoreleasePoolPop(pool);
+        }
+        devices
+    }
+
+    /// Lowercase letters and digits of `name`, for comparing a device name
+    /// with the port name macOS derived from it
+    fn normalize(name: &str) -> String {
+        name.chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect()
+    }
+
+    /// Name and address of the paired device the port at `path` belongs to
+    ///
+    /// macOS names SPP ports after the device, e.g. `/dev/cu.HC-05-DevB` for
+    /// "HC-05", so the longest device name the port name starts with wins.
+    pub fn paired_device(path: &str) -> Option<(String, String)> {
+        let port = path
+            .strip_prefix("/dev/cu.")
+            .or_else(|| path.strip_prefix("/dev/tty."))?;
+        let port = normalize(port);
+
+        let (name, address) = paired_devices()
+            .into_iter()
+            .map(|(name, address)| (normalize(&name), name, address))
+            .filter(|(key, _, _)| !key.is_empty() && port.starts_with(key.as_str()))
+            .max_by_key(|(key, _, _)| key.len())
+            .map(|(_, name, address)| (name, address))?;
+        // IOBluetooth writes addresses as `98-d3-31-fb-12-34`
+        Some((name, address.replace('-', ":").to_ascii_uppercase()))
+    }
+}
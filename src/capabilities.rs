@@ -0,0 +1,154 @@
+//! Best-effort capabilities of a port, worked out without touching it
+//!
+//! Drivers don't say which baud rates a device accepts; a rate they can't
+//! produce is usually rounded or ignored without an error. The limits here
+//! come from the path scheme and, for USB adapters, from a table of common
+//! USB-serial chips keyed by vendor id. Unknown devices get a conservative
+//! list. `set_baud_rate` returns the rate actually applied, which confirms a
+//! choice on the device itself.
+
+use crate::state::PortCapabilities;
+use serialport::SerialPortType;
+
+/// Rates offered to pick from, in ascending order
+const STANDARD_BAUD_RATES: &[u32] = &[
+    300, 600, 1200, 2400, 4800, 9600, 14400, 19200, 38400, 57600, 115200, 230400, 460800, 921600,
+    1000000, 1500000, 2000000, 3000000,
+];
+
+/// Highest rate listed for a device whose chip isn't known
+const UNKNOWN_MAX_BAUD_RATE: u32 = 115200;
+
+/// A USB-serial chip family, recognized by its vendor id
+struct Chip {
+    vid: u16,
+    name: &'static str,
+    /// `None` for native USB devices, where the rate is only a setting
+    /// passed to the firmware and any value works
+    max_baud_rate: Option<u32>,
+    /// Rates between the standard ones are produced exactly
+    custom_baud: bool,
+}
+
+const CHIPS: &[Chip] = &[
+    Chip {
+        vid: 0x0403,
+        name: "FTDI",
+        max_baud_rate: Some(3000000),
+        custom_baud: true,
+    },
+    // Older CP2102/CP2103 only know a fixed table of rates and round to it
+    Chip {
+        vid: 0x10c4,
+        name: "CP210x",
+        max_baud_rate: Some(921600),
+        custom_baud: false,
+    },
+    Chip {
+        vid: 0x1a86,
+        name: "CH34x",
+        max_baud_rate: Some(2000000),
+        custom_baud: true,
+    },
+    // PL2303HX and later go faster, the older revisions stop here
+    Chip {
+        vid: 0x067b,
+        name: "PL2303",
+        max_baud_rate: Some(1228800),
+        custom_baud: true,
+    },
+    Chip {
+        vid: 0x2341,
+        name: "Arduino USB CDC",
+        max_baud_rate: None,
+        custom_baud: true,
+    },
+    Chip {
+        vid: 0x303a,
+        name: "Espressif USB CDC",
+        max_baud_rate: None,
+        custom_baud: true,
+    },
+    Chip {
+        vid: 0x2e8a,
+        name: "Raspberry Pi USB CDC",
+        max_baud_rate: None,
+        custom_baud: true,
+    },
+    Chip {
+        vid: 0x0483,
+        name: "STMicroelectronics USB CDC",
+        max_baud_rate: None,
+        custom_baud: true,
+    },
+];
+
+/// Standard rates up to `max`
+fn rates_up_to(max: Option<u32>) -> Vec<u32> {
+    STANDARD_BAUD_RATES
+        .iter()
+        .copied()
+        .filter(|&rate| max.map_or(true, |max| rate <= max))
+        .collect()
+}
+
+/// Capabilities of the port at `path`, of type `port_type` when the system lists it
+#[cfg_attr(
+    not(any(feature = "loopback", feature = "tcp")),
+    allow(unused_variables)
+)]
+pub fn for_port(path: &str, port_type: Option<&SerialPortType>) -> PortCapabilities {
+    #[cfg(feature = "loopback")]
+    if path.starts_with(crate::loopback::SCHEME) {
+        return PortCapabilities {
+            baud_rates: rates_up_to(None),
+            max_baud_rate: None,
+            custom_baud: true,
+            break_signal: true,
+            modem_lines: true,
+            rs485: true,
+            line_errors: false,
+            chip: None,
+        };
+    }
+
+    #[cfg(feature = "tcp")]
+    {
+        // A raw socket has no line at all, RFC 2217 forwards everything but
+        // the error counters to the remote port
+        let telnet = path.starts_with(crate::tcp::RFC2217_SCHEME);
+        if telnet || path.starts_with(crate::tcp::TCP_SCHEME) {
+            return PortCapabilities {
+                baud_rates: rates_up_to(None),
+                max_baud_rate: None,
+                custom_baud: true,
+                break_signal: telnet,
+                modem_lines: telnet,
+                rs485: telnet,
+                line_errors: false,
+                chip: None,
+            };
+        }
+    }
+
+    let chip = match port_type {
+        Some(SerialPortType::UsbPort(info)) => CHIPS.iter().find(|chip| chip.vid == info.vid),
+        _ => None,
+    };
+    let baud_rates = match chip {
+        Some(chip) => rates_up_to(chip.max_baud_rate),
+        None => rates_up_to(Some(UNKNOWN_MAX_BAUD_RATE)),
+    };
+
+    PortCapabilities {
+        baud_rates,
+        max_baud_rate: chip.and_then(|chip| chip.max_baud_rate),
+        // Nothing is known about an unrecognized chip, so it is not promised
+        custom_baud: chip.is_some_and(|chip| chip.custom_baud),
+        break_signal: true,
+        modem_lines: true,
+        rs485: true,
+        line_errors: crate::line_errors::SUPPORTED,
+        chip: chip.map(|chip| chip.name.to_string()),
+    }
+}
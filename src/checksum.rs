@@ -0,0 +1,193 @@
+//! Checksums and CRCs that serial protocols commonly append to their frames
+//!
+//! The CRCs follow the parameters of the catalogue at
+//! <https://reveng.sourceforge.io/crc-catalogue/>, so each one's check value
+//! (the CRC of the ASCII bytes `123456789`) can be looked up there.
+
+use crate::state::ChecksumAlgorithm;
+
+/// Parameters of a CRC, as listed in the catalogue
+struct Crc {
+    width: u32,
+    /// Polynomial in normal (MSB-first) form
+    poly: u32,
+    init: u32,
+    /// Input and output are both reflected; the catalogue has no common CRC
+    /// reflecting only one of them
+    reflected: bool,
+    xor_out: u32,
+}
+
+/// CRC-8/SMBUS
+const CRC8: Crc = Crc {
+    width: 8,
+    poly: 0x07,
+    init: 0,
+    reflected: false,
+    xor_out: 0,
+};
+
+/// CRC-8/MAXIM-DOW, used by Dallas/Maxim 1-Wire devices
+const CRC8_MAXIM: Crc = Crc {
+    width: 8,
+    poly: 0x31,
+    init: 0,
+    reflected: true,
+    xor_out: 0,
+};
+
+/// CRC-16/IBM-3740, better known as CRC-16/CCITT-FALSE
+const CRC16_CCITT: Crc = Crc {
+    width: 16,
+    poly: 0x1021,
+    init: 0xFFFF,
+    reflected: false,
+    xor_out: 0,
+};
+
+const CRC16_XMODEM: Crc = Crc {
+    width: 16,
+    poly: 0x1021,
+    init: 0,
+    reflected: false,
+    xor_out: 0,
+};
+
+/// CRC-16/KERMIT, the reflected CCITT CRC
+const CRC16_KERMIT: Crc = Crc {
+    width: 16,
+    poly: 0x1021,
+    init: 0,
+    reflected: true,
+    xor_out: 0,
+};
+
+const CRC16_MODBUS: Crc = Crc {
+    width: 16,
+    poly: 0x8005,
+    init: 0xFFFF,
+    reflected: true,
+    xor_out: 0,
+};
+
+/// CRC-32/ISO-HDLC, the CRC of Ethernet, zip and PNG
+const CRC32: Crc = Crc {
+    width: 32,
+    poly: 0x04C1_1DB7,
+    init: 0xFFFF_FFFF,
+    reflected: true,
+    xor_out: 0xFFFF_FFFF,
+};
+
+/// CRC-32/ISCSI, the Castagnoli CRC
+const CRC32C: Crc = Crc {
+    width: 32,
+    poly: 0x1EDC_6F41,
+    init: 0xFFFF_FFFF,
+    reflected: true,
+    xor_out: 0xFFFF_FFFF,
+};
+
+/// Compute `crc` over `data` bit by bit; frames are short enough that a
+/// lookup table isn't worth it
+fn compute(crc: &Crc, data: &[u8]) -> u32 {
+    let mask = u32::MAX >> (32 - crc.width);
+    // Every `init` above reads the same reflected, so it needs no reflecting
+    let mut value = crc.init;
+    if crc.reflected {
+        let poly = crc.poly.reverse_bits() >> (32 - crc.width);
+        for &byte in data {
+            value ^= u32::from(byte);
+            for _ in 0..8 {
+                value = if value & 1 != 0 {
+                    (value >> 1) ^ poly
+                } else {
+                    value >> 1
+                };
+            }
+        }
+    } else {
+        let top_bit = 1 << (crc.width - 1);
+        for &byte in data {
+            value ^= u32::from(byte) << (crc.width - 8);
+            for _ in 0..8 {
+                value = if value & top_bit != 0 {
+                    (value << 1) ^ crc.poly
+                } else {
+                    value << 1
+                };
+            }
+            value &= mask;
+        }
+    }
+    (value ^ crc.xor_out) & mask
+}
+
+/// CRC-8/SMBUS: polynomial `0x07`, no reflection
+pub fn crc8(data: &[u8]) -> u8 {
+    compute(&CRC8, data) as u8
+}
+
+/// CRC-8/MAXIM-DOW of Dallas/Maxim 1-Wire devices
+pub fn crc8_maxim(data: &[u8]) -> u8 {
+    compute(&CRC8_MAXIM, data) as u8
+}
+
+/// CRC-16/CCITT-FALSE: polynomial `0x1021` starting from `0xFFFF`
+pub fn crc16_ccitt(data: &[u8]) -> u16 {
+    compute(&CRC16_CCITT, data) as u16
+}
+
+/// CRC-16/XMODEM: polynomial `0x1021` starting from 0
+pub fn crc16_xmodem(data: &[u8]) -> u16 {
+    compute(&CRC16_XMODEM, data) as u16
+}
+
+/// CRC-16/KERMIT: the reflected form of CRC-16/CCITT
+pub fn crc16_kermit(data: &[u8]) -> u16 {
+    compute(&CRC16_KERMIT, data) as u16
+}
+
+/// CRC-16/MODBUS, appended to RTU frames low byte first
+pub fn crc16_modbus(data: &[u8]) -> u16 {
+    compute(&CRC16_MODBUS, data) as u16
+}
+
+/// CRC-32 as used by Ethernet, zip and PNG
+pub fn crc32(data: &[u8]) -> u32 {
+    compute(&CRC32, data)
+}
+
+/// CRC-32C (Castagnoli)
+pub fn crc32c(data: &[u8]) -> u32 {
+    compute(&CRC32C, data)
+}
+
+/// XOR of all bytes, as in NMEA sentences and many simple protocols
+pub fn xor8(data: &[u8]) -> u8 {
+    data.iter().fold(0, |checksum, byte| checksum ^ byte)
+}
+
+/// Two's complement of the byte sum, so that the data plus the checksum sums
+/// to zero, as in Intel HEX records
+pub fn sum8(data: &[u8]) -> u8 {
+    data.iter()
+        .fold(0u8, |sum, &byte| sum.wrapping_add(byte))
+        .wrapping_neg()
+}
+
+/// Checksum of `data` with `algorithm`, widened to `u32`
+pub fn checksum(algorithm: ChecksumAlgorithm, data: &[u8]) -> u32 {
+    match algorithm {
+        ChecksumAlgorithm::Crc8 => crc8(data).into(),
+        ChecksumAlgorithm::Crc8Maxim => crc8_maxim(data).into(),
+        ChecksumAlgorithm::Crc16Ccitt => crc16_ccitt(data).into(),
+        ChecksumAlgorithm::Crc16Xmodem => crc16_xmodem(data).into(),
+        ChecksumAlgorithm::Crc16Kermit => crc16_kermit(data).into(),
+        ChecksumAlgorithm::Crc16Modbus => crc16_modbus(data).into(),
+        ChecksumAlgorithm::Crc32 => crc32(data),
+        ChecksumAlgorithm::Crc32c => crc32c(data),
+        ChecksumAlgorithm::Xor8 => xor8(data).into(),
+        ChecksumAlgorithm::Sum8 => sum8(data).into(),
+    }
+}
@@ -1,14 +1,21 @@
 #[cfg(desktop)]
 use crate::desktop_api::SerialPort;
 use crate::error::Error;
+use crate::logger::LogLevel;
 #[cfg(mobile)]
 use crate::mobile_api::SerialPort;
-use crate::state::{ClearBuffer, DataBits, FlowControl, Parity, StopBits};
+use crate::state::{
+    BytesAndText, CaptureFormat, ChecksumAlgorithm, ClearBuffer, DataBits, EmitStrategy,
+    FlowControl, FlowControlChars, Framing, LineErrors, ManagedPortInfo, ModemStatus, OpenConfig,
+    Parity, PortCapabilities, PortConfig, PortFilter, PortState, PortStatsSnapshot, PortTarget,
+    Rs485Config, StopBits,
+};
 use std::collections::HashMap;
 use std::time::Duration;
 use tauri::{AppHandle, Runtime, State};
 
-#[tauri::command]
+// Runs on the async runtime so the device lookups don't block the main thread
+#[tauri::command(async)]
 pub fn available_ports<R: Runtime>(
     _app: AppHandle<R>,
     serial: State<'_, SerialPort<R>>,
@@ -16,7 +23,8 @@ pub fn available_ports<R: Runtime>(
     serial.available_ports()
 }
 
-#[tauri::command]
+// Runs on the async runtime so the device lookups don't block the main thread
+#[tauri::command(async)]
 pub fn available_ports_direct<R: Runtime>(
     _app: AppHandle<R>,
     serial: State<'_, SerialPort<R>>,
@@ -24,6 +32,42 @@ pub fn available_ports_direct<R: Runtime>(
     serial.available_ports_direct()
 }
 
+// Runs on the async runtime so the device lookups don't block the main thread
+#[tauri::command(async)]
+pub fn available_ports_merged<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+) -> Result<HashMap<String, HashMap<String, String>>, Error> {
+    serial.available_ports_merged()
+}
+
+// Runs on the async runtime so the device lookups don't block the main thread
+#[tauri::command(async)]
+pub fn available_ports_filtered<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    filter: PortFilter,
+) -> Result<HashMap<String, HashMap<String, String>>, Error> {
+    serial.available_ports_filtered(filter)
+}
+
+#[tauri::command]
+pub fn start_port_scan<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    interval: Option<u64>,
+) -> Result<(), Error> {
+    serial.start_port_scan(interval.unwrap_or(1000))
+}
+
+#[tauri::command]
+pub fn stop_port_scan<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+) -> Result<(), Error> {
+    serial.stop_port_scan()
+}
+
 #[tauri::command]
 pub fn cancel_read<R: Runtime>(
     _app: AppHandle<R>,
@@ -33,6 +77,15 @@ pub fn cancel_read<R: Runtime>(
     serial.cancel_read(path)
 }
 
+#[tauri::command]
+pub fn cancel_operation<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<(), Error> {
+    serial.cancel_operation(path)
+}
+
 #[tauri::command]
 pub fn close<R: Runtime>(
     _app: AppHandle<R>,
@@ -50,6 +103,25 @@ pub fn close_all<R: Runtime>(
     serial.close_all()
 }
 
+#[tauri::command]
+pub fn reconnect<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<(), Error> {
+    serial.reconnect(path)
+}
+
+#[tauri::command]
+pub fn reconfigure<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    config: PortConfig,
+) -> Result<(), Error> {
+    serial.reconfigure(path, config)
+}
+
 #[tauri::command]
 pub fn force_close<R: Runtime>(
     _app: AppHandle<R>,
@@ -64,22 +136,69 @@ pub fn open<R: Runtime>(
     _app: AppHandle<R>,
     serial: State<'_, SerialPort<R>>,
     path: String,
-    baud_rate: u32,
-    data_bits: Option<DataBits>,
-    flow_control: Option<FlowControl>,
-    parity: Option<Parity>,
-    stop_bits: Option<StopBits>,
-    timeout: Option<u64>,
+    config: OpenConfig,
+) -> Result<String, Error> {
+    serial.open(path, config)
+}
+
+#[tauri::command]
+pub fn open_with_config<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    config: PortConfig,
+) -> Result<String, Error> {
+    serial.open_with_config(path, config)
+}
+
+// Runs on the async runtime so the wait doesn't block the main thread
+#[tauri::command(async)]
+pub fn open_when_available<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    target: PortTarget,
+    config: OpenConfig,
+    timeout: u64,
+) -> Result<String, Error> {
+    serial.open_when_available(target, config, timeout)
+}
+
+#[tauri::command]
+pub fn cancel_open_when_available<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
 ) -> Result<(), Error> {
-    serial.open(
-        path,
-        baud_rate,
-        data_bits,
-        flow_control,
-        parity,
-        stop_bits,
-        timeout,
-    )
+    serial.cancel_open_when_available()
+}
+
+#[tauri::command]
+pub fn add_usb_filter<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    vid: u16,
+    pid: u16,
+    driver: Option<String>,
+) -> Result<(), Error> {
+    serial.add_usb_filter(vid, pid, driver)
+}
+
+#[tauri::command]
+pub fn has_permission<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<bool, Error> {
+    serial.has_permission(path)
+}
+
+// Runs on the async runtime so the permission dialog doesn't block the main thread
+#[tauri::command(async)]
+pub fn request_permission<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<bool, Error> {
+    serial.request_permission(path)
 }
 
 #[tauri::command]
@@ -102,7 +221,64 @@ pub fn write_binary<R: Runtime>(
     serial.write_binary(path, value)
 }
 
+// Runs on the async runtime so a long paced transfer doesn't block the main thread
+#[tauri::command(async)]
+pub fn write_binary_paced<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    value: Vec<u8>,
+    max_pending: usize,
+) -> Result<usize, Error> {
+    serial.write_binary_paced(path, value, max_pending)
+}
+
 #[tauri::command]
+pub fn write_slip<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    payload: Vec<u8>,
+) -> Result<usize, Error> {
+    serial.write_slip(path, payload)
+}
+
+#[tauri::command]
+pub fn write_hex<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    value: String,
+) -> Result<usize, Error> {
+    serial.write_hex(path, value)
+}
+
+// Runs on the async runtime so the gaps between messages don't block the main thread
+#[tauri::command(async)]
+pub fn write_sequence<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    messages: Vec<Vec<u8>>,
+    gap_ms: Option<u64>,
+) -> Result<usize, Error> {
+    serial.write_sequence(path, messages, gap_ms)
+}
+
+// Runs on the async runtime so a long replay doesn't block the main thread
+#[tauri::command(async)]
+pub fn replay_capture<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    file_path: String,
+    respect_timing: Option<bool>,
+) -> Result<usize, Error> {
+    serial.replay_capture(path, file_path, respect_timing.unwrap_or(false))
+}
+
+// Runs on the async runtime so waiting for data doesn't block the main thread
+#[tauri::command(async)]
 pub fn read<R: Runtime>(
     _app: AppHandle<R>,
     serial: State<'_, SerialPort<R>>,
@@ -113,6 +289,84 @@ pub fn read<R: Runtime>(
     serial.read(path, timeout, size)
 }
 
+// Runs on the async runtime so waiting for data doesn't block the main thread
+#[tauri::command(async)]
+pub fn read_binary<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    timeout: Option<u64>,
+    size: Option<usize>,
+    min_size: Option<usize>,
+) -> Result<Vec<u8>, Error> {
+    serial.read_binary(path, timeout, size, min_size)
+}
+
+#[tauri::command]
+pub fn read_hex<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    timeout: Option<u64>,
+    size: Option<usize>,
+) -> Result<String, Error> {
+    serial.read_hex(path, timeout, size)
+}
+
+#[tauri::command]
+pub fn read_exact_or_partial<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    size: usize,
+    timeout: Option<u64>,
+) -> Result<Vec<u8>, Error> {
+    serial.read_exact_or_partial(path, size, timeout)
+}
+
+#[tauri::command]
+pub fn read_available<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    max_size: usize,
+) -> Result<Vec<u8>, Error> {
+    serial.read_available(path, max_size)
+}
+
+#[tauri::command]
+pub fn read_both<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    timeout: Option<u64>,
+    size: Option<usize>,
+) -> Result<BytesAndText, Error> {
+    serial.read_both(path, timeout, size)
+}
+
+#[tauri::command]
+pub fn peek<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    size: usize,
+) -> Result<Vec<u8>, Error> {
+    serial.peek(path, size)
+}
+
+#[tauri::command]
+pub fn transaction<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    request: Vec<u8>,
+    read_size: usize,
+    response_timeout: u64,
+) -> Result<Vec<u8>, Error> {
+    serial.transaction(path, request, read_size, response_timeout)
+}
+
 #[tauri::command]
 pub fn start_listening<R: Runtime>(
     _app: AppHandle<R>,
@@ -120,8 +374,26 @@ pub fn start_listening<R: Runtime>(
     path: String,
     timeout: Option<u64>,
     size: Option<usize>,
+    base64: Option<bool>,
+    buffer_reads: Option<bool>,
+    idle_timeout: Option<u64>,
+    framing: Option<Framing>,
+    emit_strategy: Option<EmitStrategy>,
+    max_buffer_size: Option<usize>,
+    decode: Option<bool>,
 ) -> Result<(), Error> {
-    serial.start_listening(path, timeout, size)
+    serial.start_listening(
+        path,
+        timeout,
+        size,
+        base64,
+        buffer_reads,
+        idle_timeout,
+        framing,
+        emit_strategy,
+        max_buffer_size,
+        decode,
+    )
 }
 
 #[tauri::command]
@@ -133,13 +405,42 @@ pub fn stop_listening<R: Runtime>(
     serial.stop_listening(path)
 }
 
+#[tauri::command]
+pub fn pause_listening<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    stop_reading: Option<bool>,
+) -> Result<(), Error> {
+    serial.pause_listening(path, stop_reading.unwrap_or(true))
+}
+
+#[tauri::command]
+pub fn resume_listening<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<(), Error> {
+    serial.resume_listening(path)
+}
+
+#[tauri::command]
+pub fn set_rs485<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    config: Option<Rs485Config>,
+) -> Result<(), Error> {
+    serial.set_rs485(path, config)
+}
+
 #[tauri::command]
 pub fn set_baud_rate<R: Runtime>(
     _app: AppHandle<R>,
     serial: State<'_, SerialPort<R>>,
     path: String,
     baud_rate: u32,
-) -> Result<(), Error> {
+) -> Result<u32, Error> {
     serial.set_baud_rate(path, baud_rate)
 }
 
@@ -163,6 +464,26 @@ pub fn set_flow_control<R: Runtime>(
     serial.set_flow_control(path, flow_control)
 }
 
+#[tauri::command]
+pub fn set_flow_control_chars<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    xon: u8,
+    xoff: u8,
+) -> Result<(), Error> {
+    serial.set_flow_control_chars(path, xon, xoff)
+}
+
+#[tauri::command]
+pub fn get_flow_control_chars<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<FlowControlChars, Error> {
+    serial.get_flow_control_chars(path)
+}
+
 #[tauri::command]
 pub fn set_parity<R: Runtime>(
     _app: AppHandle<R>,
@@ -194,6 +515,67 @@ pub fn set_timeout<R: Runtime>(
     serial.set_timeout(path, timeout_duration)
 }
 
+#[tauri::command]
+pub fn set_read_timeout<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    timeout: u64,
+) -> Result<(), Error> {
+    serial.set_read_timeout(path, Duration::from_millis(timeout))
+}
+
+#[tauri::command]
+pub fn set_write_timeout<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    timeout: u64,
+) -> Result<(), Error> {
+    serial.set_write_timeout(path, Duration::from_millis(timeout))
+}
+
+#[tauri::command]
+pub fn set_wait_for_cts<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    enabled: bool,
+    timeout: Option<u64>,
+) -> Result<(), Error> {
+    serial.set_wait_for_cts(path, enabled, timeout)
+}
+
+#[tauri::command]
+pub fn set_inter_byte_delay<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    delay_ms: u64,
+) -> Result<(), Error> {
+    serial.set_inter_byte_delay(path, delay_ms)
+}
+
+#[tauri::command]
+pub fn set_write_events<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    enabled: bool,
+) -> Result<(), Error> {
+    serial.set_write_events(path, enabled)
+}
+
+#[tauri::command]
+pub fn set_write_chunk_size<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    chunk_size: usize,
+) -> Result<(), Error> {
+    serial.set_write_chunk_size(path, chunk_size)
+}
+
 #[tauri::command]
 pub fn write_request_to_send<R: Runtime>(
     _app: AppHandle<R>,
@@ -214,6 +596,87 @@ pub fn write_data_terminal_ready<R: Runtime>(
     serial.write_data_terminal_ready(path, level)
 }
 
+#[tauri::command]
+pub fn set_control_lines<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    dtr: Option<bool>,
+    rts: Option<bool>,
+) -> Result<(), Error> {
+    serial.set_control_lines(path, dtr, rts)
+}
+
+// Runs on the async runtime so the pulse doesn't block the main thread
+#[tauri::command(async)]
+pub fn reset_via_dtr<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    pulse_ms: Option<u64>,
+) -> Result<(), Error> {
+    serial.reset_via_dtr(path, pulse_ms.unwrap_or(100))
+}
+
+// Runs on the async runtime so the reset doesn't block the main thread
+#[tauri::command(async)]
+pub fn enter_esp_bootloader<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<(), Error> {
+    serial.enter_esp_bootloader(path)
+}
+
+// Runs on the async runtime so the reset doesn't block the main thread
+#[tauri::command(async)]
+pub fn reset_esp<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<(), Error> {
+    serial.reset_esp(path)
+}
+
+#[tauri::command]
+pub fn trigger_bootloader_1200<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<(), Error> {
+    serial.trigger_bootloader_1200(path)
+}
+
+#[tauri::command]
+pub fn start_heartbeat<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    value: Vec<u8>,
+    interval_ms: u64,
+) -> Result<(), Error> {
+    serial.start_heartbeat(path, value, interval_ms)
+}
+
+#[tauri::command]
+pub fn stop_heartbeat<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<(), Error> {
+    serial.stop_heartbeat(path)
+}
+
+#[tauri::command]
+pub fn idle_auto_close<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    timeout: Option<u64>,
+) -> Result<(), Error> {
+    serial.idle_auto_close(path, timeout)
+}
+
 #[tauri::command]
 pub fn read_clear_to_send<R: Runtime>(
     _app: AppHandle<R>,
@@ -250,6 +713,53 @@ pub fn read_carrier_detect<R: Runtime>(
     serial.read_carrier_detect(path)
 }
 
+#[tauri::command]
+pub fn get_modem_status<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<ModemStatus, Error> {
+    serial.get_modem_status(path)
+}
+
+#[tauri::command]
+pub fn watch_modem_signals<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    poll_interval: Option<u64>,
+) -> Result<(), Error> {
+    serial.watch_modem_signals(path, poll_interval.unwrap_or(100))
+}
+
+#[tauri::command]
+pub fn stop_watching_modem_signals<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<(), Error> {
+    serial.stop_watching_modem_signals(path)
+}
+
+#[tauri::command]
+pub fn get_line_errors<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<LineErrors, Error> {
+    serial.get_line_errors(path)
+}
+
+#[tauri::command]
+pub fn set_latency_timer<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    ms: u32,
+) -> Result<(), Error> {
+    serial.set_latency_timer(path, ms)
+}
+
 #[tauri::command]
 pub fn bytes_to_read<R: Runtime>(
     _app: AppHandle<R>,
@@ -268,13 +778,23 @@ pub fn bytes_to_write<R: Runtime>(
     serial.bytes_to_write(path)
 }
 
+// Runs on the async runtime so waiting for a slow line doesn't block the main thread
+#[tauri::command(async)]
+pub fn drain<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<(), Error> {
+    serial.drain(path)
+}
+
 #[tauri::command]
 pub fn clear_buffer<R: Runtime>(
     _app: AppHandle<R>,
     serial: State<'_, SerialPort<R>>,
     path: String,
     buffer_type: ClearBuffer,
-) -> Result<(), Error> {
+) -> Result<u32, Error> {
     serial.clear_buffer(path, buffer_type)
 }
 
@@ -295,3 +815,134 @@ pub fn clear_break<R: Runtime>(
 ) -> Result<(), Error> {
     serial.clear_break(path)
 }
+
+// Runs on the async runtime so the break doesn't block the main thread
+#[tauri::command(async)]
+pub fn send_break<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    duration: u64,
+) -> Result<(), Error> {
+    serial.send_break(path, duration)
+}
+
+#[tauri::command]
+pub fn is_open<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<bool, Error> {
+    serial.is_open(path)
+}
+
+#[tauri::command]
+pub fn managed_ports<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+) -> Result<Vec<String>, Error> {
+    serial.managed_ports()
+}
+
+#[tauri::command]
+pub fn managed_ports_detailed<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+) -> Result<HashMap<String, ManagedPortInfo>, Error> {
+    serial.managed_ports_detailed()
+}
+
+#[tauri::command]
+pub fn get_port_state<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<PortState, Error> {
+    serial.get_port_state(path)
+}
+
+#[tauri::command]
+pub fn export_config<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<PortConfig, Error> {
+    serial.export_config(path)
+}
+
+#[tauri::command]
+pub fn get_port_capabilities<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<PortCapabilities, Error> {
+    serial.get_port_capabilities(path)
+}
+
+#[tauri::command]
+pub fn get_stats<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<PortStatsSnapshot, Error> {
+    serial.get_stats(path)
+}
+
+#[tauri::command]
+pub fn reset_stats<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<(), Error> {
+    serial.reset_stats(path)
+}
+
+#[tauri::command]
+pub fn start_capture<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    file_path: String,
+    format: CaptureFormat,
+) -> Result<(), Error> {
+    serial.start_capture(path, file_path, format)
+}
+
+#[tauri::command]
+pub fn stop_capture<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+) -> Result<(), Error> {
+    serial.stop_capture(path)
+}
+
+#[tauri::command]
+pub fn checksum<R: Runtime>(
+    _app: AppHandle<R>,
+    algorithm: ChecksumAlgorithm,
+    data: Vec<u8>,
+) -> Result<u32, Error> {
+    Ok(crate::checksum::checksum(algorithm, &data))
+}
+
+#[tauri::command]
+pub fn set_log_level<R: Runtime>(_app: AppHandle<R>, level: LogLevel) -> Result<(), Error> {
+    crate::logger::set_log_level(level);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_log_level<R: Runtime>(_app: AppHandle<R>) -> Result<LogLevel, Error> {
+    Ok(crate::logger::get_log_level())
+}
+
+#[tauri::command]
+pub fn set_port_log_level<R: Runtime>(
+    _app: AppHandle<R>,
+    serial: State<'_, SerialPort<R>>,
+    path: String,
+    level: Option<LogLevel>,
+) -> Result<(), Error> {
+    serial.set_port_log_level(path, level)
+}
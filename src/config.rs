@@ -0,0 +1,171 @@
+//! Plugin configuration, read from the `plugins.serialplugin` block of
+//! `tauri.conf.json` or passed to `init_with_config`
+
+use crate::logger::LogLevel;
+use crate::state::PortFilter;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Defaults and restrictions applied by the plugin
+///
+/// ```json
+/// {
+///   "plugins": {
+///     "serialplugin": {
+///       "defaultTimeout": 500,
+///       "logLevel": "Warn",
+///       "allowedPorts": ["/dev/ttyUSB*", { "vid": 9025, "pid": 67 }],
+///       "maxOpenPorts": 4
+///     }
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Config {
+    /// Port timeout in milliseconds for `open` calls that don't pass one
+    pub default_timeout: Option<u64>,
+    /// Log level set when the plugin starts
+    pub log_level: Option<LogLevel>,
+    /// The only ports `open` accepts; every port is allowed when unset
+    pub allowed_ports: Option<Vec<AllowedPort>>,
+    /// How many ports may be open at once; `open` fails with
+    /// `Error::TooManyPorts` beyond it. Only enforced on desktop, where the
+    /// plugin keeps the open ports itself.
+    pub max_open_ports: Option<usize>,
+    /// Directories besides the app's log directory that `start_capture` and
+    /// `replay_capture` may use files in
+    pub capture_dirs: Option<Vec<String>>,
+}
+
+/// An entry of `Config::allowed_ports`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum AllowedPort {
+    /// A path, where `*` matches any run of characters and `?` any one,
+    /// except a path separator
+    Path(String),
+    /// Ports whose info passes the filter, usually a USB vendor/product id.
+    /// Only ports the system lists have info, so this never matches others.
+    Device(PortFilter),
+}
+
+impl Config {
+    /// Whether any allowlist entry needs the port info to be checked
+    pub fn needs_port_info(&self) -> bool {
+        match &self.allowed_ports {
+            Some(allowed) => allowed
+                .iter()
+                .any(|entry| matches!(entry, AllowedPort::Device(_))),
+            None => false,
+        }
+    }
+
+    /// Whether `open` may use the port at `path`, whose info is `port_info`
+    /// when the system lists it
+    ///
+    /// Paths with a `..` component are refused whenever there is an
+    /// allowlist, so they can't climb out of a directory a pattern names.
+    pub fn allows(&self, path: &str, port_info: Option<&HashMap<String, String>>) -> bool {
+        let allowed = match &self.allowed_ports {
+            Some(allowed) => allowed,
+            None => return true,
+        };
+        if has_parent_component(path) {
+            return false;
+        }
+
+        allowed.iter().any(|entry| match entry {
+            AllowedPort::Path(pattern) => glob_match(pattern.as_bytes(), path.as_bytes()),
+            AllowedPort::Device(filter) => match port_info {
+                Some(port_info) => filter.matches(port_info),
+                None => false,
+            },
+        })
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || c == '\\'
+}
+
+/// Whether `path` has a `..` component
+pub(crate) fn has_parent_component(path: &str) -> bool {
+    path.split(is_separator).any(|component| component == "..")
+}
+
+/// Match `text` against a pattern of literal bytes, `*` and `?`; neither
+/// wildcard matches a path separator
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    // Iterative matching with backtracking to the last `*`
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        let wildcard_fits = !is_separator(text[t] as char);
+        if p < pattern.len() && (pattern[p] == text[t] || (pattern[p] == b'?' && wildcard_fits)) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) =
+            star.filter(|&(_, star_t)| !is_separator(text[star_t] as char))
+        {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&byte| byte == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allowing(patterns: &[&str]) -> Config {
+        Config {
+            allowed_ports: Some(
+                patterns
+                    .iter()
+                    .map(|pattern| AllowedPort::Path(pattern.to_string()))
+                    .collect(),
+            ),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn glob_wildcards() {
+        assert!(glob_match(b"/dev/ttyUSB*", b"/dev/ttyUSB0"));
+        assert!(glob_match(b"/dev/ttyUSB*", b"/dev/ttyUSB"));
+        assert!(glob_match(b"/dev/tty?SB1", b"/dev/ttyUSB1"));
+        assert!(glob_match(b"\\\\.\\COM*", b"\\\\.\\COM12"));
+        assert!(glob_match(b"/dev/*/by-id/*", b"/dev/serial/by-id/usb-FTDI"));
+        assert!(!glob_match(b"/dev/ttyUSB*", b"/dev/ttyACM0"));
+        assert!(!glob_match(b"/dev/tty?", b"/dev/tty10"));
+    }
+
+    #[test]
+    fn glob_wildcards_stop_at_separators() {
+        assert!(!glob_match(
+            b"/dev/serial/by-id/*",
+            b"/dev/serial/by-id/x/y"
+        ));
+        assert!(!glob_match(b"/dev/*", b"/dev/pts/0"));
+        assert!(!glob_match(b"/dev/tty?", b"/dev/tty/"));
+        assert!(!glob_match(b"COM*", b"COM1\\x"));
+        assert!(glob_match(b"/dev/*/0", b"/dev/pts/0"));
+    }
+
+    #[test]
+    fn allowlist_refuses_parent_components() {
+        let config = allowing(&["/dev/serial/by-id/*", "/dev/serial/*/../*"]);
+        assert!(config.allows("/dev/serial/by-id/usb-FTDI", None));
+        assert!(!config.allows("/dev/serial/by-id/../../ttyS0", None));
+        assert!(!config.allows("/dev/serial/x/../ttyS0", None));
+        assert!(Config::default().allows("/dev/serial/by-id/../../ttyS0", None));
+    }
+}
@@ -1,26 +1,136 @@
+use crate::bluetooth;
+use crate::capabilities;
+use crate::config::{has_parent_component, Config};
 use crate::error::Error;
+use crate::flow_chars;
+use crate::framing::{self, FrameDecoder};
+use crate::latency;
+use crate::line_errors;
+use crate::logger::{LogLevel, PortLogLevel};
+use crate::managed_io::{ManagedReader, ManagedWriter};
+use crate::opener::{set_initial_lines, PortOpener, PortSettings};
+use crate::rs485;
 use crate::state::{
-    ClearBuffer, DataBits, FlowControl, Parity, ReadData, SerialportInfo, StopBits, BLUETOOTH, PCI,
-    UNKNOWN, USB,
+    event_path, validate_line_settings, BytesAndText, Capture, CaptureDirection, CaptureFormat,
+    ClearBuffer, DataBits, EmitStrategy, FlowControl, FlowControlChars, Framing, JsonData,
+    LineErrors, ListenQueue, ListenState, ListenerError, ListenerErrorKind, ManagedPortInfo,
+    ModemStatus, OpenConfig, Parity, PortCapabilities, PortChange, PortConfig, PortFilter,
+    PortState, PortStats, PortStatsSnapshot, PortTarget, RawPortHandle, ReadData, ReadDataBase64,
+    ReadText, Rs485Config, SerialportInfo, StopBits, WriteProgress, BLUETOOTH, PCI, UNKNOWN, USB,
 };
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use serialport::{
     DataBits as SerialDataBits, FlowControl as SerialFlowControl, Parity as SerialParity,
     StopBits as SerialStopBits,
 };
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc;
 use std::sync::mpsc::{Receiver, Sender, TryRecvError};
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, TryLockError};
 use std::thread;
-use std::time::Duration;
-use tauri::{AppHandle, Emitter, Runtime};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager, Runtime};
 
 /// Access to the serial port APIs for mobile platforms.
 pub struct SerialPort<R: Runtime> {
     #[allow(dead_code)]
     pub(crate) app: AppHandle<R>,
     pub(crate) serialports: Arc<Mutex<HashMap<String, SerialportInfo>>>,
+    /// Stop signal for the background port scan, if one is running
+    pub(crate) port_scan: Arc<Mutex<Option<Sender<usize>>>>,
+    /// Cancel signals for `open_when_available` calls still waiting, keyed by
+    /// a per-call id so concurrent waits for one target each have their own
+    pub(crate) pending_opens: Arc<Mutex<HashMap<u64, Sender<usize>>>>,
+    /// Per-path flags tripped by `cancel_operation`
+    pub(crate) cancel_flags: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    /// Backend that opens and lists ports, see `set_port_opener`
+    pub(crate) opener: Arc<RwLock<Arc<dyn PortOpener>>>,
+    pub(crate) config: Arc<Config>,
+}
+
+/// How often `drain` checks whether the driver's output queue has emptied
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// How long closing waits for a background thread before detaching it
+const JOIN_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// How often a blocking read checks whether it was cancelled
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long EN is held low when resetting an ESP chip, as esptool does
+const ESP_RESET_PULSE: Duration = Duration::from_millis(100);
+
+/// How long GPIO0 stays low after an ESP chip leaves reset, so it samples the
+/// strapping pin and boots into download mode
+const ESP_BOOT_DELAY: Duration = Duration::from_millis(50);
+
+/// How often a listener checks the driver's line error counters
+const LINE_ERROR_POLL: Duration = Duration::from_millis(1000);
+
+/// Longest wait between two attempts to open a busy port
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// Lock the map of open ports, taking it over from a thread that panicked
+/// while holding the lock
+///
+/// Each change to the map is a single insert or remove, so it is still
+/// consistent after such a panic; failing every later call instead would
+/// leave the app unable to use any port until it restarts.
+pub(crate) fn lock_ports(
+    serialports: &Mutex<HashMap<String, SerialportInfo>>,
+) -> MutexGuard<'_, HashMap<String, SerialportInfo>> {
+    serialports.lock().unwrap_or_else(|e| {
+        log_warn!("Serial port map lock was poisoned by a panic, recovering it");
+        e.into_inner()
+    })
+}
+
+/// The key a port is managed under: the device node `path` resolves to, so
+/// aliases such as `/dev/serial/by-id/...` links share one entry
+///
+/// Paths that don't resolve, like `loopback://` ones or a device that is
+/// gone, are used as given. On Windows `\\.\COM3` and `com3` both become
+/// `COM3`.
+pub(crate) fn port_key(path: &str) -> String {
+    #[cfg(unix)]
+    if path.starts_with('/') {
+        if let Some(resolved) = std::fs::canonicalize(path)
+            .ok()
+            .and_then(|resolved| resolved.to_str().map(str::to_string))
+        {
+            return resolved;
+        }
+    }
+    #[cfg(windows)]
+    {
+        let name = path.strip_prefix(r"\\.\").unwrap_or(path);
+        let is_com = name.len() > 3
+            && name[..3].eq_ignore_ascii_case("COM")
+            && name[3..].bytes().all(|b| b.is_ascii_digit());
+        if is_com {
+            return name.to_ascii_uppercase();
+        }
+    }
+    path.to_string()
+}
+
+// Manual impl: deriving would needlessly require `R: Clone`
+impl<R: Runtime> Clone for SerialPort<R> {
+    fn clone(&self) -> Self {
+        Self {
+            app: self.app.clone(),
+            serialports: self.serialports.clone(),
+            port_scan: self.port_scan.clone(),
+            pending_opens: self.pending_opens.clone(),
+            cancel_flags: self.cancel_flags.clone(),
+            opener: self.opener.clone(),
+            config: self.config.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -31,19 +141,165 @@ struct MobileResponse<T> {
 }
 
 impl<R: Runtime> SerialPort<R> {
+    /// Replace the backend used to open and list ports
+    ///
+    /// Ports that are already open keep working; the new opener is used from
+    /// the next `open` or port listing on.
+    pub fn set_port_opener(&self, opener: Arc<dyn PortOpener>) -> Result<(), Error> {
+        *self
+            .opener
+            .write()
+            .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))? = opener;
+        Ok(())
+    }
+
+    /// The backend currently used to open and list ports
+    fn opener(&self) -> Result<Arc<dyn PortOpener>, Error> {
+        self.opener
+            .read()
+            .map(|opener| opener.clone())
+            .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))
+    }
+
+    /// Ports reported by the opener, empty if they can't be listed
+    fn enumerate_ports(opener: &RwLock<Arc<dyn PortOpener>>) -> Vec<serialport::SerialPortInfo> {
+        match opener.read() {
+            Ok(opener) => opener.enumerate().unwrap_or_else(|_| vec![]),
+            Err(_) => vec![],
+        }
+    }
+
+    /// Fail unless the configured `allowed_ports` include `path`
+    ///
+    /// The path is checked both as given and with symlinks resolved, so
+    /// `/dev/ttyUSB*` also admits a `/dev/serial/by-id/` link to such a device.
+    /// A path with a `..` component is refused either way.
+    fn ensure_allowed(&self, path: &str) -> Result<(), Error> {
+        let key = port_key(path);
+        let port_info = if self.config.needs_port_info() {
+            let mut ports = Self::list_ports(&self.opener);
+            ports.remove(&key).or_else(|| ports.remove(path))
+        } else {
+            None
+        };
+
+        let allowed = self.config.allows(path, port_info.as_ref())
+            || (!has_parent_component(path) && self.config.allows(&key, port_info.as_ref()));
+        if allowed {
+            Ok(())
+        } else {
+            Err(Error::PermissionDenied(format!(
+                "Serial port {} is not in the allowed ports",
+                path
+            )))
+        }
+    }
+
     /// Get serial port list
     pub fn available_ports(&self) -> Result<HashMap<String, HashMap<String, String>>, Error> {
-        let mut list = serialport::available_ports().unwrap_or_else(|_| vec![]);
+        Ok(Self::list_ports(&self.opener))
+    }
+
+    /// List USB serial ports with their details
+    fn list_ports(
+        opener: &RwLock<Arc<dyn PortOpener>>,
+    ) -> HashMap<String, HashMap<String, String>> {
+        let mut list = Self::enumerate_ports(opener);
         list.retain(|port| matches!(port.port_type, serialport::SerialPortType::UsbPort(_)));
         list.sort_by(|a, b| a.port_name.cmp(&b.port_name));
 
         let mut result_list: HashMap<String, HashMap<String, String>> = HashMap::new();
 
         for p in list {
-            result_list.insert(p.port_name, self.get_port_info(p.port_type));
+            result_list.insert(p.port_name, Self::get_port_info(p.port_type));
         }
 
-        Ok(result_list)
+        #[cfg(target_os = "linux")]
+        Self::add_by_id_paths(&mut result_list);
+
+        result_list
+    }
+
+    /// Start scanning for ports in the background, emitting
+    /// `plugin-serialplugin-port-added` and `plugin-serialplugin-port-removed`
+    ///
+    /// A change is only reported once two consecutive scans agree, so a port
+    /// that disappears for a single scan (a quick replug) produces no events.
+    pub fn start_port_scan(&self, interval: u64) -> Result<(), Error> {
+        if interval == 0 {
+            return Err(Error::InvalidInput(
+                "interval must be at least 1 ms".to_string(),
+            ));
+        }
+        let mut port_scan = self
+            .port_scan
+            .lock()
+            .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))?;
+
+        if let Some(sender) = port_scan.take() {
+            let _ = sender.send(1);
+        }
+
+        let (tx, rx): (Sender<usize>, Receiver<usize>) = mpsc::channel();
+        *port_scan = Some(tx);
+
+        let app_clone = self.app.clone();
+        let opener = self.opener.clone();
+        thread::spawn(move || {
+            let mut known = Self::list_ports(&opener);
+            let mut previous = known.clone();
+            // A stop message or a dropped sender ends the scan
+            while let Err(mpsc::RecvTimeoutError::Timeout) =
+                rx.recv_timeout(Duration::from_millis(interval))
+            {
+                let current = Self::list_ports(&opener);
+
+                for (path, info) in &current {
+                    if !known.contains_key(path) && previous.contains_key(path) {
+                        known.insert(path.clone(), info.clone());
+                        let change = PortChange {
+                            path: path.clone(),
+                            info: info.clone(),
+                        };
+                        if let Err(e) = app_clone.emit("plugin-serialplugin-port-added", change) {
+                            log_error!("Failed to send port added event: {}", e);
+                        }
+                    }
+                }
+
+                let removed: Vec<String> = known
+                    .keys()
+                    .filter(|path| !current.contains_key(*path) && !previous.contains_key(*path))
+                    .cloned()
+                    .collect();
+                for path in removed {
+                    if let Some(info) = known.remove(&path) {
+                        let change = PortChange { path, info };
+                        if let Err(e) = app_clone.emit("plugin-serialplugin-port-removed", change) {
+                            log_error!("Failed to send port removed event: {}", e);
+                        }
+                    }
+                }
+
+                previous = current;
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Stop the background port scan
+    pub fn stop_port_scan(&self) -> Result<(), Error> {
+        let mut port_scan = self
+            .port_scan
+            .lock()
+            .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))?;
+
+        if let Some(sender) = port_scan.take() {
+            let _ = sender.send(1);
+        }
+
+        Ok(())
     }
 
     /// Get serial port list using platform-specific commands
@@ -51,57 +307,73 @@ impl<R: Runtime> SerialPort<R> {
         &self,
     ) -> Result<HashMap<String, HashMap<String, String>>, Error> {
         let mut result_list: HashMap<String, HashMap<String, String>> = HashMap::new();
+        #[allow(unused_mut)]
+        let mut errors: Vec<String> = Vec::new();
 
         #[cfg(target_os = "windows")]
         {
             use std::process::Command;
 
-            // Get USB ports
-            let usb_output = Command::new("wmic")
-                .arg("path")
-                .arg("Win32_PnPEntity")
-                .arg("where")
-                .arg("PNPDeviceID like '%USB%' and Name like '%(COM%'")
-                .arg("get")
-                .arg("Name,DeviceID")
-                .output()
-                .expect("Failed to execute command");
-
-            let usb_devices = String::from_utf8_lossy(&usb_output.stdout);
-            for line in usb_devices.lines().skip(1) {
-                let device_info = line.trim();
-                if !device_info.is_empty() {
-                    let parts: Vec<&str> = device_info.split_whitespace().collect();
-                    if parts.len() >= 2 {
-                        let port_name = parts[1].trim();
+            // Every COM port the kernel has registered, e.g.
+            // "    \Device\Serial0    REG_SZ    COM1"
+            let com_devices = Self::command_stdout(
+                Command::new("reg")
+                    .arg("query")
+                    .arg(r"HKLM\HARDWARE\DEVICEMAP\SERIALCOMM"),
+                &mut errors,
+            );
+
+            for line in com_devices.lines() {
+                if let Some((device, port_name)) = line.split_once("REG_SZ") {
+                    let port_name = port_name.trim();
+                    if !port_name.is_empty() {
                         let mut port_info = HashMap::new();
-                        port_info.insert("type".to_string(), "USB".to_string());
+                        port_info.insert("type".to_string(), "COM".to_string());
+                        port_info.insert("device".to_string(), device.trim().to_string());
                         result_list.insert(port_name.to_string(), port_info);
                     }
                 }
             }
 
-            // Get COM ports
-            let com_output = Command::new("wmic")
-                .arg("path")
-                .arg("Win32_SerialPort")
-                .arg("get")
-                .arg("DeviceID,Name")
-                .output()
-                .expect("Failed to execute command");
+            // Friendly names and bus type, e.g.
+            // "USB\VID_0403&PID_6001\A50285BI|USB Serial Port (COM3)"
+            let pnp_devices = Self::command_stdout(
+                Command::new("powershell")
+                    .arg("-NoProfile")
+                    .arg("-NonInteractive")
+                    .arg("-Command")
+                    .arg(
+                        "Get-PnpDevice -Class Ports -PresentOnly | \
+                         ForEach-Object { $_.InstanceId + '|' + $_.FriendlyName }",
+                    ),
+                &mut errors,
+            );
 
-            let com_devices = String::from_utf8_lossy(&com_output.stdout);
-            for line in com_devices.lines().skip(1) {
-                let device_info = line.trim();
-                if !device_info.is_empty() {
-                    let parts: Vec<&str> = device_info.split_whitespace().collect();
-                    if parts.len() >= 2 {
-                        let port_name = parts[0].trim();
-                        let mut port_info = HashMap::new();
-                        port_info.insert("type".to_string(), "COM".to_string());
-                        result_list.insert(port_name.to_string(), port_info);
-                    }
+            for line in pnp_devices.lines() {
+                let (instance_id, friendly_name) = match line.trim().split_once('|') {
+                    Some(parts) => parts,
+                    None => continue,
+                };
+                let port_name = match friendly_name.rfind("(COM").and_then(|start| {
+                    let rest = &friendly_name[start + 1..];
+                    rest.find(')').map(|end| &rest[..end])
+                }) {
+                    Some(port_name) => port_name,
+                    None => continue,
+                };
+
+                let port_info = result_list.entry(port_name.to_string()).or_default();
+                if instance_id.starts_with("USB") || instance_id.starts_with("FTDIBUS") {
+                    port_info.insert("type".to_string(), "USB".to_string());
+                } else if instance_id.starts_with("BTHENUM") {
+                    port_info.insert("type".to_string(), "Bluetooth".to_string());
+                    bluetooth::add_instance_address(instance_id, port_info);
+                } else {
+                    port_info
+                        .entry("type".to_string())
+                        .or_insert_with(|| "COM".to_string());
                 }
+                port_info.insert("name".to_string(), friendly_name.trim().to_string());
             }
         }
 
@@ -110,11 +382,8 @@ impl<R: Runtime> SerialPort<R> {
             use std::process::Command;
 
             // Get USB devices
-            let output = Command::new("lsusb")
-                .output()
-                .expect("Failed to execute lsusb command");
+            let usb_devices = Self::command_stdout(&mut Command::new("lsusb"), &mut errors);
 
-            let usb_devices = String::from_utf8_lossy(&output.stdout);
             for line in usb_devices.lines() {
                 if line.contains("Serial") || line.contains("USB") {
                     let mut port_info = HashMap::new();
@@ -124,12 +393,8 @@ impl<R: Runtime> SerialPort<R> {
             }
 
             // Get serial ports from /dev
-            let dev_output = Command::new("ls")
-                .arg("/dev")
-                .output()
-                .expect("Failed to execute ls command");
+            let dev_ports = Self::command_stdout(Command::new("ls").arg("/dev"), &mut errors);
 
-            let dev_ports = String::from_utf8_lossy(&dev_output.stdout);
             for line in dev_ports.lines() {
                 if line.starts_with("ttyUSB") || line.starts_with("ttyS") {
                     let mut port_info = HashMap::new();
@@ -145,9 +410,11 @@ impl<R: Runtime> SerialPort<R> {
                     result_list.insert(format!("/dev/{}", line), port_info);
                 }
                 if line.starts_with("rfcomm") {
+                    let path = format!("/dev/{}", line);
                     let mut port_info = HashMap::new();
                     port_info.insert("type".to_string(), "Bluetooth".to_string());
-                    result_list.insert(format!("/dev/{}", line), port_info);
+                    bluetooth::add_details(&path, &mut port_info);
+                    result_list.insert(path, port_info);
                 }
                 if line.starts_with("ttyACM") {
                     let mut port_info = HashMap::new();
@@ -155,6 +422,8 @@ impl<R: Runtime> SerialPort<R> {
                     result_list.insert(format!("/dev/{}", line), port_info);
                 }
             }
+
+            Self::add_by_id_paths(&mut result_list);
         }
 
         #[cfg(target_os = "macos")]
@@ -162,12 +431,11 @@ impl<R: Runtime> SerialPort<R> {
             use std::process::Command;
 
             // Get USB devices
-            let output = Command::new("system_profiler")
-                .arg("SPUSBDataType")
-                .output()
-                .expect("Failed to execute system_profiler");
+            let usb_devices = Self::command_stdout(
+                Command::new("system_profiler").arg("SPUSBDataType"),
+                &mut errors,
+            );
 
-            let usb_devices = String::from_utf8_lossy(&output.stdout);
             for line in usb_devices.lines() {
                 if line.contains("Serial") || line.contains("USB") {
                     let mut port_info = HashMap::new();
@@ -177,23 +445,84 @@ impl<R: Runtime> SerialPort<R> {
             }
 
             // Check devices in /dev
-            let dev_output = Command::new("ls")
-                .arg("/dev")
-                .output()
-                .expect("Failed to execute ls command");
+            let dev_ports = Self::command_stdout(Command::new("ls").arg("/dev"), &mut errors);
 
-            let dev_ports = String::from_utf8_lossy(&dev_output.stdout);
             for line in dev_ports.lines() {
                 if line.starts_with("cu.") || line.starts_with("tty.") {
+                    let path = format!("/dev/{}", line);
                     let mut port_info = HashMap::new();
                     if line.contains("Bluetooth") {
                         port_info.insert("type".to_string(), "Bluetooth".to_string());
+                        bluetooth::add_details(&path, &mut port_info);
                     } else if line.starts_with("cu.") {
                         port_info.insert("type".to_string(), "USB".to_string());
                     } else {
                         port_info.insert("type".to_string(), "COM".to_string());
                     }
-                    result_list.insert(format!("/dev/{}", line), port_info);
+                    result_list.insert(path, port_info);
+                }
+            }
+        }
+
+        // A partial list is more useful than an error, so only fail when nothing was found
+        if result_list.is_empty() && !errors.is_empty() {
+            return Err(Error::String(format!(
+                "Failed to list ports: {}",
+                errors.join(", ")
+            )));
+        }
+
+        Ok(result_list)
+    }
+
+    /// Get the ports passing `filter`, of any type unless the filter says otherwise
+    pub fn available_ports_filtered(
+        &self,
+        filter: PortFilter,
+    ) -> Result<HashMap<String, HashMap<String, String>>, Error> {
+        let mut result_list: HashMap<String, HashMap<String, String>> = HashMap::new();
+        for p in Self::enumerate_ports(&self.opener) {
+            let (path, port_info) = Self::listed_port_info(p);
+            if filter.matches(&port_info) {
+                result_list.insert(path, port_info);
+            }
+        }
+
+        Ok(result_list)
+    }
+
+    /// Get every port keyed by its device path, combining `available_ports` with
+    /// the extra details `available_ports_direct` finds
+    ///
+    /// Unlike `available_ports` this includes non-USB ports. Direct entries whose
+    /// key isn't an openable path (raw `lsusb`/`system_profiler` lines) are
+    /// skipped, and a failing platform command only loses its extra details.
+    pub fn available_ports_merged(
+        &self,
+    ) -> Result<HashMap<String, HashMap<String, String>>, Error> {
+        let mut result_list: HashMap<String, HashMap<String, String>> = HashMap::new();
+        for p in Self::enumerate_ports(&self.opener) {
+            let (path, port_info) = Self::listed_port_info(p);
+            result_list.insert(path, port_info);
+        }
+
+        #[cfg(target_os = "linux")]
+        Self::add_by_id_paths(&mut result_list);
+
+        if let Ok(direct) = self.available_ports_direct() {
+            for (path, direct_info) in direct {
+                if !Self::is_port_path(&path) {
+                    continue;
+                }
+                let port_info = result_list.entry(path).or_default();
+                for (key, value) in direct_info {
+                    // Details from the serialport crate win, direct only fills gaps
+                    match port_info.get(&key) {
+                        Some(existing) if existing != UNKNOWN => {}
+                        _ => {
+                            port_info.insert(key, value);
+                        }
+                    }
                 }
             }
         }
@@ -201,6 +530,15 @@ impl<R: Runtime> SerialPort<R> {
         Ok(result_list)
     }
 
+    /// Whether an `available_ports_direct` key names a device that can be opened
+    fn is_port_path(key: &str) -> bool {
+        if cfg!(target_os = "windows") {
+            key.starts_with("COM")
+        } else {
+            std::path::Path::new(key).exists()
+        }
+    }
+
     /// Cancel reading data from the serial port
     pub fn cancel_read(&self, path: String) -> Result<(), Error> {
         self.get_serialport(path.clone(), |serialport_info| {
@@ -210,191 +548,903 @@ impl<R: Runtime> SerialPort<R> {
                 })?;
             }
             serialport_info.sender = None;
+            serialport_info.listeners = 0;
             Ok(())
         })
     }
 
     /// Close the specified serial port
+    ///
+    /// An alias such as a `/dev/serial/by-id` link only finds the port while
+    /// it still resolves to the device; once the device is gone the link
+    /// dangles, so pass the path `open` returned.
     pub fn close(&self, path: String) -> Result<(), Error> {
-        match self.serialports.lock() {
-            Ok(mut serialports) => {
-                if let Some(port_info) = serialports.remove(&path) {
-                    // Signal the thread to stop
-                    if let Some(sender) = &port_info.sender {
-                        sender.send(1).map_err(|e| {
-                            Error::String(format!(
-                                "Failed to cancel serial port data reading: {}",
-                                e
-                            ))
-                        })?;
-                    }
+        let path = port_key(&path);
+        let mut serialports = lock_ports(&self.serialports);
+        if let Some(port_info) = serialports.remove(&path) {
+            Self::shut_down(port_info)
+        } else {
+            Err(Error::String(format!("Serial port {} is not open!", &path)))
+        }
+    }
 
-                    // Wait for the thread to finish
-                    if let Some(handle) = port_info.thread_handle {
-                        handle.join().map_err(|e| {
-                            Error::String(format!("Failed to join thread: {:?}", e))
-                        })?;
-                    }
+    /// Stop the background threads of a port taken out of the map and drop
+    /// its handle
+    ///
+    /// The listener, heartbeat and modem signal watcher are joined. The idle
+    /// watchdog is only told to stop, as it may be the caller or be waiting
+    /// for the map lock the caller holds.
+    fn shut_down(mut port_info: SerialportInfo) -> Result<(), Error> {
+        if let Some(sender) = port_info.idle_sender.take() {
+            let _ = sender.send(1);
+        }
+        port_info.idle_thread = None;
 
-                    Ok(())
-                } else {
-                    Err(Error::String(format!("Serial port {} is not open!", &path)))
-                }
+        // Either may already have exited on an I/O error
+        for sender in [
+            port_info.heartbeat_sender.take(),
+            port_info.signal_sender.take(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            let _ = sender.send(1);
+        }
+
+        // Signal the listener to stop
+        if let Some(sender) = &port_info.sender {
+            sender.send(1).map_err(|e| {
+                Error::String(format!("Failed to cancel serial port data reading: {}", e))
+            })?;
+        }
+
+        for handle in [
+            port_info.heartbeat_thread.take(),
+            port_info.signal_thread.take(),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if let Err(e) = Self::join_with_timeout(handle) {
+                log_error!(port = &port_info.log_level, "Error joining thread: {:?}", e);
+            }
+        }
+
+        // Wait for the listener to finish, but never hang on a wedged read
+        if let Some(handle) = port_info.thread_handle.take() {
+            Self::join_with_timeout(handle)
+                .map_err(|e| Error::String(format!("Failed to join thread: {:?}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Reopen the device of an open port with its last-known settings
+    ///
+    /// Meant for a device that was unplugged and plugged back in: the line
+    /// settings as last changed by the setters are reapplied, along with the
+    /// per-port options such as timeouts, RS-485 and capture. The listener,
+    /// heartbeat and modem signal watcher are stopped and have to be started
+    /// again. Reads are cancelled and writes still going out waited for, up
+    /// to a second, before the device is reopened. If the device can't be
+    /// opened the port is closed.
+    ///
+    /// As with `close`, an alias that no longer resolves doesn't find the
+    /// port, so pass the path `open` returned.
+    pub fn reconnect(&self, path: String) -> Result<(), Error> {
+        let path = port_key(&path);
+        let opener = self.opener()?;
+        let (io_locks, log_level) = self.stop_io(&path)?;
+        let _io = Self::lock_io(&path, &log_level, &io_locks);
+        let mut serialports = lock_ports(&self.serialports);
+        let port_info = match serialports.remove(&path) {
+            Some(port_info) => port_info,
+            None => return Err(Error::String(format!("Serial port {} is not open!", &path))),
+        };
+
+        let settings = Self::port_settings(&port_info)?;
+        let port_info = Self::reopen(opener.as_ref(), &path, port_info, &settings, false)
+            .map_err(|e| Error::String(format!("Failed to reopen serial port: {}", e)))?;
+        log_info!(
+            port = &port_info.log_level,
+            "Reconnected serial port {}",
+            path
+        );
+        serialports.insert(path, port_info);
+        Ok(())
+    }
+
+    /// Apply new line settings by closing and reopening the device, keeping
+    /// the listener
+    ///
+    /// Some USB adapters only take a new baud rate reliably when opened with
+    /// it, which the `set_*` setters can't do. A running listener waits while
+    /// the device is reopened with `config`, then reads from the new handle
+    /// with its buffers, framing and subscribers intact, so it needn't be
+    /// stopped and started around the change. Per-port options carry over as
+    /// with `reconnect`, as does the handling of I/O in progress; the
+    /// heartbeat and modem signal watcher are stopped. If the device can't be
+    /// reopened the port is closed.
+    pub fn reconfigure(&self, path: String, config: PortConfig) -> Result<(), Error> {
+        validate_line_settings(config.baud_rate, config.data_bits, config.stop_bits)?;
+        let parity = config.parity.try_into()?;
+        let path = port_key(&path);
+
+        let opener = self.opener()?;
+        let (io_locks, log_level) = self.stop_io(&path)?;
+        let _io = Self::lock_io(&path, &log_level, &io_locks);
+        let mut serialports = lock_ports(&self.serialports);
+        let port_info = match serialports.remove(&path) {
+            Some(port_info) => port_info,
+            None => return Err(Error::String(format!("Serial port {} is not open!", &path))),
+        };
+
+        let mut settings = Self::port_settings(&port_info)?;
+        settings.baud_rate = config.baud_rate;
+        settings.data_bits = config.data_bits.into();
+        settings.flow_control = config.flow_control.into();
+        settings.parity = parity;
+        settings.stop_bits = config.stop_bits.into();
+        settings.timeout = Duration::from_millis(config.timeout);
+
+        let port_info = Self::reopen(opener.as_ref(), &path, port_info, &settings, true)
+            .map_err(|e| Error::String(format!("Failed to reopen serial port: {}", e)))?;
+        log_info!(
+            port = &port_info.log_level,
+            "Reopened serial port {} at {} baud",
+            path,
+            config.baud_rate
+        );
+        serialports.insert(path, port_info);
+        Ok(())
+    }
+
+    /// Open the device of a port taken out of the map again with `settings`,
+    /// carrying the per-port options over
+    ///
+    /// With `keep_listener` a running listener is detached from the old handle
+    /// and handed a clone of the new one; otherwise it is stopped along with
+    /// the heartbeat and modem signal watcher. On failure the port stays
+    /// closed, and a kept listener exits as its stop channel is dropped.
+    fn reopen(
+        opener: &dyn PortOpener,
+        path: &str,
+        mut port_info: SerialportInfo,
+        settings: &PortSettings,
+        keep_listener: bool,
+    ) -> Result<SerialportInfo, Error> {
+        let listener_port = port_info.listener_port.clone().filter(|_| {
+            keep_listener
+                && port_info
+                    .thread_handle
+                    .as_ref()
+                    .is_some_and(|handle| !handle.is_finished())
+        });
+
+        // Every clone of the old handle has to be gone before the device is
+        // opened again, or its exclusive lock makes the open fail
+        let mut senders = vec![
+            port_info.heartbeat_sender.take(),
+            port_info.signal_sender.take(),
+        ];
+        let mut handles = vec![
+            port_info.heartbeat_thread.take(),
+            port_info.signal_thread.take(),
+        ];
+        match &listener_port {
+            // Waits for the listener's current read to return
+            Some(slot) => drop(
+                slot.lock()
+                    .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))?
+                    .take(),
+            ),
+            None => {
+                senders.push(port_info.sender.take());
+                handles.push(port_info.thread_handle.take());
+            }
+        }
+        for sender in senders.into_iter().flatten() {
+            let _ = sender.send(1);
+        }
+        for handle in handles.into_iter().flatten() {
+            if let Err(e) = Self::join_with_timeout(handle) {
+                log_error!(port = &port_info.log_level, "Error joining thread: {:?}", e);
+            }
+        }
+
+        let SerialportInfo {
+            serialport,
+            sender,
+            thread_handle,
+            listeners,
+            listen_decode,
+            listen_state,
+            listen_queue,
+            subscribers,
+            rs485,
+            rs485_driver,
+            read_timeout,
+            write_timeout,
+            wait_for_cts,
+            inter_byte_delay,
+            write_events,
+            write_chunk_size,
+            stats,
+            flow_control_chars,
+            capture,
+            exclusive,
+            initial_dtr,
+            initial_rts,
+            idle_sender,
+            idle_thread,
+            write_lock,
+            read_lock,
+            log_level,
+            ..
+        } = port_info;
+        drop(serialport);
+
+        let (mut port, raw_handle) = opener.open_with_handle(path, settings)?;
+        set_initial_lines(port.as_mut(), settings)?;
+
+        let mut port_info = SerialportInfo::new(port, Self::port_config(settings), exclusive);
+        if let Some(slot) = listener_port {
+            let listener_handle = port_info.serialport.try_clone()?;
+            *slot
+                .lock()
+                .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))? =
+                Some(listener_handle);
+            port_info.sender = sender;
+            port_info.thread_handle = thread_handle;
+            port_info.listeners = listeners;
+            port_info.listen_decode = listen_decode;
+            port_info.listen_state = listen_state;
+            port_info.listen_queue = listen_queue;
+            port_info.listener_port = Some(slot);
+            port_info.subscribers = subscribers;
+        }
+        port_info.raw_handle = raw_handle;
+        port_info.initial_dtr = initial_dtr;
+        port_info.initial_rts = initial_rts;
+        port_info.rs485 = rs485;
+        // A device that was replugged has lost the driver's RS485 mode
+        if rs485_driver {
+            port_info.rs485_driver = rs485::set(raw_handle, rs485.as_ref()).unwrap_or(false);
+        }
+        port_info.read_timeout = read_timeout;
+        port_info.write_timeout = write_timeout;
+        port_info.wait_for_cts = wait_for_cts;
+        port_info.inter_byte_delay = inter_byte_delay;
+        port_info.write_events = write_events;
+        port_info.write_chunk_size = write_chunk_size;
+        port_info.stats = stats;
+        // The new handle starts out with the standard bytes
+        if !flow_control_chars.is_standard() {
+            match flow_chars::set(raw_handle, flow_control_chars) {
+                Ok(()) => port_info.flow_control_chars = flow_control_chars,
+                Err(e) => log_warn!(
+                    port = &log_level,
+                    "Failed to set XON/XOFF bytes again on {}: {}",
+                    path,
+                    e
+                ),
             }
-            Err(error) => Err(Error::String(format!("Failed to acquire lock: {}", error))),
         }
+        port_info.capture = capture;
+        // The watchdog goes by the shared stats, so it carries on unchanged
+        port_info.idle_sender = idle_sender;
+        port_info.idle_thread = idle_thread;
+        // Reads and writes waiting for the port go on with the new handle
+        port_info.write_lock = write_lock;
+        port_info.read_lock = read_lock;
+        port_info.log_level = log_level;
+        Ok(port_info)
+    }
+
+    /// Ask the I/O running on clones of the handle of `path` to stop, returning
+    /// the locks it holds and the port's log level
+    fn stop_io(&self, path: &str) -> Result<(IoLocks, Arc<PortLogLevel>), Error> {
+        let stopping = self.get_serialport(path.to_string(), |port_info| {
+            Ok((
+                vec![port_info.write_lock.clone(), port_info.read_lock.clone()],
+                port_info.log_level.clone(),
+            ))
+        })?;
+        self.cancel_flag(path)?.store(true, Ordering::Relaxed);
+        Ok(stopping)
+    }
+
+    /// Take `io_locks` so no clone of the handle is in use while the device is
+    /// reopened, waiting at most `JOIN_TIMEOUT` for each
+    ///
+    /// An operation that doesn't finish in time is left running; with the
+    /// exclusive lock the reopen then fails as the device is still in use.
+    fn lock_io<'a>(
+        path: &str,
+        log_level: &PortLogLevel,
+        io_locks: &'a [Arc<Mutex<()>>],
+    ) -> Vec<MutexGuard<'a, ()>> {
+        let deadline = Instant::now() + JOIN_TIMEOUT;
+        io_locks
+            .iter()
+            .filter_map(|lock| loop {
+                match lock.try_lock() {
+                    Ok(guard) => break Some(guard),
+                    Err(TryLockError::Poisoned(e)) => break Some(e.into_inner()),
+                    Err(TryLockError::WouldBlock) if Instant::now() >= deadline => {
+                        log_warn!(port = log_level, "I/O on {} did not stop in time", path);
+                        break None;
+                    }
+                    Err(TryLockError::WouldBlock) => thread::sleep(Duration::from_millis(5)),
+                }
+            })
+            .collect()
     }
 
     /// Close all open serial ports
     pub fn close_all(&self) -> Result<(), Error> {
-        match self.serialports.lock() {
-            Ok(mut map) => {
-                let mut errors = Vec::new();
-
-                for (path, port_info) in map.drain() {
-                    if let Some(sender) = &port_info.sender {
-                        if let Err(e) = sender.send(1) {
-                            errors.push(format!("Failed to cancel port {}: {}", path, e));
-                            continue;
-                        }
-                    }
+        let mut map = lock_ports(&self.serialports);
+        let mut errors = Vec::new();
 
-                    if let Some(handle) = port_info.thread_handle {
-                        if let Err(e) = handle.join() {
-                            errors
-                                .push(format!("Failed to join thread for port {}: {:?}", path, e));
-                        }
-                    }
+        for (path, port_info) in map.drain() {
+            if let Some(sender) = &port_info.sender {
+                if let Err(e) = sender.send(1) {
+                    errors.push(format!("Failed to cancel port {}: {}", path, e));
+                    continue;
                 }
+            }
 
-                if errors.is_empty() {
-                    Ok(())
-                } else {
-                    Err(Error::String(format!(
-                        "Errors during close: {}",
-                        errors.join(", ")
-                    )))
+            if let Some(handle) = port_info.thread_handle {
+                if let Err(e) = Self::join_with_timeout(handle) {
+                    errors.push(format!("Failed to join thread for port {}: {:?}", path, e));
                 }
             }
-            Err(error) => Err(Error::String(format!("Failed to acquire lock: {}", error))),
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::String(format!(
+                "Errors during close: {}",
+                errors.join(", ")
+            )))
         }
     }
 
     /// Force close a serial port
+    ///
+    /// The port is always removed from the map; a listener that fails to stop
+    /// is logged and left behind rather than reported as an error.
     pub fn force_close(&self, path: String) -> Result<(), Error> {
-        match self.serialports.lock() {
-            Ok(mut map) => {
-                if let Some(serial) = map.remove(&path) {
-                    if let Some(sender) = &serial.sender {
-                        sender.send(1).map_err(|e| {
-                            Error::String(format!(
-                                "Failed to cancel serial port data reading: {}",
-                                e
-                            ))
-                        })?;
-                    }
+        let path = port_key(&path);
+        let mut map = lock_ports(&self.serialports);
+        if let Some(serial) = map.remove(&path) {
+            if let Some(sender) = &serial.sender {
+                // The listener may already have exited on a read error
+                let _ = sender.send(1);
+            }
 
-                    if let Some(handle) = serial.thread_handle {
-                        handle.join().map_err(|e| {
-                            Error::String(format!("Failed to join thread: {:?}", e))
-                        })?;
-                    }
+            if let Some(handle) = serial.thread_handle {
+                if let Err(e) = Self::join_with_timeout(handle) {
+                    log_error!("Error joining thread: {:?}", e);
                 }
-                Ok(())
             }
-            Err(error) => Err(Error::String(format!("Failed to acquire lock: {}", error))),
         }
+        Ok(())
+    }
+
+    /// Join a background thread that has been told to stop, giving up after
+    /// `JOIN_TIMEOUT`
+    ///
+    /// A thread stuck in a blocking call on a vanished device is detached instead,
+    /// so closing never hangs. It exits by itself once that call returns.
+    fn join_with_timeout(
+        handle: JoinHandle<()>,
+    ) -> Result<(), Box<dyn std::any::Any + Send + 'static>> {
+        let deadline = Instant::now() + JOIN_TIMEOUT;
+        while !handle.is_finished() {
+            if Instant::now() >= deadline {
+                log_warn!("Background thread did not stop in time, detaching it");
+                return Ok(());
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+        handle.join()
+    }
+
+    /// Open a port and manage it under `path`
+    ///
+    /// With `exclusive` (the default) the device is locked on Unix so other
+    /// processes opening it get `EBUSY`; `Some(false)` lets them share it.
+    /// `initial_dtr` and `initial_rts` drive the modem lines as part of
+    /// opening, before any data is read or written, whichever `PortOpener`
+    /// opened the port.
+    ///
+    /// The port is managed under the device node `path` resolves to, so an
+    /// alias such as a `/dev/serial/by-id` link can't open the same device a
+    /// second time. That path is returned; events are named after it, so
+    /// later calls should use it too.
+    ///
+    /// The device is opened, and busy retries waited out, with the map
+    /// unlocked, so other ports carry on meanwhile.
+    pub fn open(&self, path: String, config: OpenConfig) -> Result<String, Error> {
+        self.ensure_allowed(&path)?;
+        validate_line_settings(
+            config.baud_rate,
+            config.data_bits.unwrap_or(DataBits::Eight),
+            config.stop_bits.unwrap_or(StopBits::One),
+        )?;
+        let path = port_key(&path);
+
+        let replaced = {
+            let mut serialports = lock_ports(&self.serialports);
+            let replaced = if serialports.contains_key(&path) {
+                if config.fail_if_open.unwrap_or(true) {
+                    return Err(Self::already_open(&path));
+                }
+                // Opt-in legacy behaviour: tear down the existing handle and reopen
+                serialports.remove(&path)
+            } else {
+                None
+            };
+            // Checked up front so a full map doesn't cost an open of the device
+            self.check_open_ports(&path, &serialports)?;
+            replaced
+        };
+        if let Some(port_info) = replaced {
+            log_warn!(
+                port = &port_info.log_level,
+                "Serial port {} is already open, reopening it",
+                path
+            );
+            // Torn down as `close` does, so no worker of the old entry
+            // keeps a clone of the handle open
+            if let Err(e) = Self::shut_down(port_info) {
+                log_error!("Error closing serial port {}: {}", path, e);
+            }
+        }
+
+        let parity = match config.parity {
+            Some(parity) => SerialParity::try_from(parity)?,
+            None => SerialParity::None,
+        };
+
+        let data_bits = config
+            .data_bits
+            .map(Into::into)
+            .unwrap_or(SerialDataBits::Eight);
+        let flow_control = config
+            .flow_control
+            .map(Into::into)
+            .unwrap_or(SerialFlowControl::None);
+        let stop_bits = config
+            .stop_bits
+            .map(Into::into)
+            .unwrap_or(SerialStopBits::One);
+        let timeout = config.timeout.or(self.config.default_timeout);
+        let timeout = Duration::from_millis(timeout.unwrap_or(200));
+
+        let settings = PortSettings {
+            baud_rate: config.baud_rate,
+            data_bits,
+            flow_control,
+            parity,
+            stop_bits,
+            timeout,
+            exclusive: config.exclusive.unwrap_or(true),
+            initial_dtr: config.initial_dtr,
+            initial_rts: config.initial_rts,
+        };
+        let opener = self.opener()?;
+
+        // The OS can hold on to a port for a moment after it was closed,
+        // so busy errors are retried with a doubling delay
+        let mut retries_left = config.open_retries.unwrap_or(0);
+        let mut retry_delay =
+            Duration::from_millis(config.retry_delay_ms.unwrap_or(100)).min(MAX_RETRY_DELAY);
+        let (mut port, raw_handle) = loop {
+            match opener.open_with_handle(&path, &settings) {
+                Ok(opened) => break opened,
+                Err(e) if retries_left > 0 && Self::is_busy_error(&e) => {
+                    retries_left -= 1;
+                    thread::sleep(retry_delay);
+                    retry_delay = retry_delay.saturating_mul(2).min(MAX_RETRY_DELAY);
+                }
+                Err(e) => return Err(Error::String(format!("Failed to open serial port: {}", e))),
+            }
+        };
+        set_initial_lines(port.as_mut(), &settings)
+            .map_err(|e| Error::String(format!("Failed to set DTR/RTS after opening: {}", e)))?;
+
+        let mut port_info =
+            SerialportInfo::new(port, Self::port_config(&settings), settings.exclusive);
+        port_info.raw_handle = raw_handle;
+        port_info.initial_dtr = settings.initial_dtr;
+        port_info.initial_rts = settings.initial_rts;
+
+        // Another call may have opened the path or taken the last slot while
+        // the device was being opened; the new handle is dropped then
+        let mut serialports = lock_ports(&self.serialports);
+        if serialports.contains_key(&path) {
+            return Err(Self::already_open(&path));
+        }
+        self.check_open_ports(&path, &serialports)?;
+        serialports.insert(path.clone(), port_info);
+        Ok(path)
+    }
+
+    fn already_open(path: &str) -> Error {
+        Error::PortBusy(format!("Serial port {} is already open", path))
+    }
+
+    /// Fail with `Error::TooManyPorts` if another port would go over
+    /// `max_open_ports`
+    fn check_open_ports(
+        &self,
+        path: &str,
+        serialports: &HashMap<String, SerialportInfo>,
+    ) -> Result<(), Error> {
+        match self.config.max_open_ports {
+            Some(max) if serialports.len() >= max => Err(Error::TooManyPorts(format!(
+                "Cannot open {}, {} of {} ports are already open",
+                path,
+                serialports.len(),
+                max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Open a port with settings saved from `export_config`, returning the
+    /// path it is managed under as `open` does
+    pub fn open_with_config(&self, path: String, config: PortConfig) -> Result<String, Error> {
+        self.open(path, config.into())
+    }
+
+    /// Wait until the target port appears, then open it
+    ///
+    /// Returns the path that was opened, which is useful when waiting for a USB
+    /// device. Fails with `Error::Timeout` once `timeout` milliseconds have passed,
+    /// or with an error if the wait is cancelled by `cancel_open_when_available`.
+    pub fn open_when_available(
+        &self,
+        target: PortTarget,
+        config: OpenConfig,
+        timeout: u64,
+    ) -> Result<String, Error> {
+        // Checked up front, as open errors are otherwise retried until the timeout
+        validate_line_settings(
+            config.baud_rate,
+            config.data_bits.unwrap_or(DataBits::Eight),
+            config.stop_bits.unwrap_or(StopBits::One),
+        )?;
+
+        static NEXT_WAIT_ID: AtomicU64 = AtomicU64::new(0);
+        let id = NEXT_WAIT_ID.fetch_add(1, Ordering::Relaxed);
+        let (tx, rx): (Sender<usize>, Receiver<usize>) = mpsc::channel();
+        self.pending_opens
+            .lock()
+            .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))?
+            .insert(id, tx);
+
+        let result = self.wait_and_open(&target, config, timeout, rx);
+
+        if let Ok(mut pending_opens) = self.pending_opens.lock() {
+            pending_opens.remove(&id);
+        }
+
+        result
+    }
+
+    fn wait_and_open(
+        &self,
+        target: &PortTarget,
+        config: OpenConfig,
+        timeout: u64,
+        cancel: Receiver<usize>,
+    ) -> Result<String, Error> {
+        let deadline = Instant::now() + Duration::from_millis(timeout);
+        let mut last_error: Option<Error> = None;
+
+        loop {
+            if let Some(path) = Self::find_port(&self.opener, target) {
+                // The device node can show up before it is ready to be opened
+                match self.open(path.clone(), config.clone()) {
+                    Ok(opened) => return Ok(opened),
+                    Err(e) => last_error = Some(e),
+                }
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(Error::Timeout {
+                    message: match last_error {
+                        Some(e) => format!("Port {} could not be opened: {}", target, e),
+                        None => format!(
+                            "Port {} did not become available within {}ms",
+                            target, timeout
+                        ),
+                    },
+                    elapsed_ms: timeout,
+                });
+            }
+
+            let wait = (deadline - now).min(Duration::from_millis(100));
+            match cancel.recv_timeout(wait) {
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Ok(_) | Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    return Err(Error::String(format!(
+                        "Waiting for port {} was cancelled",
+                        target
+                    )));
+                }
+            }
+        }
+    }
+
+    /// Cancel every `open_when_available` call that is still waiting
+    pub fn cancel_open_when_available(&self) -> Result<(), Error> {
+        let mut pending_opens = self
+            .pending_opens
+            .lock()
+            .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))?;
+
+        for (_, sender) in pending_opens.drain() {
+            let _ = sender.send(1);
+        }
+
+        Ok(())
     }
 
-    pub fn open(
+    /// Desktop lists every port whatever its VID/PID, so there is nothing to register
+    pub fn add_usb_filter(
         &self,
-        path: String,
-        baud_rate: u32,
-        data_bits: Option<DataBits>,
-        flow_control: Option<FlowControl>,
-        parity: Option<Parity>,
-        stop_bits: Option<StopBits>,
-        timeout: Option<u64>,
+        _vid: u16,
+        _pid: u16,
+        _driver: Option<String>,
     ) -> Result<(), Error> {
-        match self.serialports.lock() {
-            Ok(mut serialports) => {
-                if serialports.contains_key(&path) {
-                    return Err(Error::String(format!("Serial port {} is open!", path)));
-                }
-
-                let port = serialport::new(path.clone(), baud_rate)
-                    .data_bits(data_bits.map(Into::into).unwrap_or(SerialDataBits::Eight))
-                    .flow_control(
-                        flow_control
-                            .map(Into::into)
-                            .unwrap_or(SerialFlowControl::None),
-                    )
-                    .parity(parity.map(Into::into).unwrap_or(SerialParity::None))
-                    .stop_bits(stop_bits.map(Into::into).unwrap_or(SerialStopBits::One))
-                    .timeout(Duration::from_millis(timeout.unwrap_or(200)))
-                    .open()
-                    .map_err(|e| Error::String(format!("Failed to open serial port: {}", e)))?;
-
-                let port_info = SerialportInfo {
-                    serialport: port,
-                    sender: None,
-                    thread_handle: None,
-                };
+        Ok(())
+    }
 
-                serialports.insert(path, port_info);
-                Ok(())
+    /// Desktop needs no per-device permission, so this is always true
+    pub fn has_permission(&self, _path: String) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    /// Desktop needs no per-device permission, so this is always true
+    pub fn request_permission(&self, _path: String) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    /// The tracked form of the settings a port was opened with
+    fn port_config(settings: &PortSettings) -> PortConfig {
+        PortConfig {
+            baud_rate: settings.baud_rate,
+            data_bits: settings.data_bits.into(),
+            flow_control: settings.flow_control.into(),
+            parity: settings.parity.into(),
+            stop_bits: settings.stop_bits.into(),
+            timeout: settings.timeout.as_millis() as u64,
+        }
+    }
+
+    /// Settings to reopen a port with so it matches how it was left
+    fn port_settings(port_info: &SerialportInfo) -> Result<PortSettings, Error> {
+        let config = &port_info.settings;
+        Ok(PortSettings {
+            baud_rate: config.baud_rate,
+            data_bits: config.data_bits.into(),
+            flow_control: config.flow_control.into(),
+            parity: config.parity.try_into()?,
+            stop_bits: config.stop_bits.into(),
+            timeout: Duration::from_millis(config.timeout),
+            exclusive: port_info.exclusive,
+            initial_dtr: port_info.initial_dtr,
+            initial_rts: port_info.initial_rts,
+        })
+    }
+
+    /// Whether an open failure looks like the port is still held by someone else
+    fn is_busy_error(e: &serialport::Error) -> bool {
+        match e.kind() {
+            serialport::ErrorKind::Io(std::io::ErrorKind::PermissionDenied)
+            | serialport::ErrorKind::Io(std::io::ErrorKind::WouldBlock) => true,
+            // EBUSY has no io::ErrorKind of its own
+            _ => e.description.to_lowercase().contains("busy"),
+        }
+    }
+
+    /// Resolve a target to the path of a port that currently exists
+    fn find_port(opener: &RwLock<Arc<dyn PortOpener>>, target: &PortTarget) -> Option<String> {
+        let ports = Self::enumerate_ports(opener);
+        match target {
+            PortTarget::Path(path) => {
+                if ports.iter().any(|p| &p.port_name == path) || std::path::Path::new(path).exists()
+                {
+                    Some(path.clone())
+                } else {
+                    None
+                }
             }
-            Err(error) => Err(Error::String(format!("Failed to acquire lock: {}", error))),
+            PortTarget::Usb {
+                vid,
+                pid,
+                serial_number,
+            } => ports
+                .into_iter()
+                .find(|p| match &p.port_type {
+                    serialport::SerialPortType::UsbPort(info) => {
+                        info.vid == *vid
+                            && info.pid == *pid
+                            && (serial_number.is_none() || info.serial_number == *serial_number)
+                    }
+                    _ => false,
+                })
+                .map(|p| p.port_name),
         }
     }
 
     /// Read data from the serial port
+    ///
+    /// `emit_strategy` picks how read events are emitted; see `EmitStrategy`.
+    /// `max_buffer_size` caps each buffer the listener accumulates data in:
+    /// idle frames and batches are emitted early when they reach it, while
+    /// incomplete frames and data held back by `pause_listening` are dropped
+    /// with an `Overflow` error event.
+    ///
+    /// Without `decode` read events go out as `plugin-serialplugin-read-*`
+    /// with a `ReadData` byte payload. `Some(true)` emits them as
+    /// `plugin-serialplugin-read-text-*` with a `ReadText` payload of the data
+    /// decoded as UTF-8, `Some(false)` as `plugin-serialplugin-read-bytes-*`
+    /// with `ReadData`, so a subscriber can't bind a text handler to bytes or
+    /// the other way round. Idle and frame events are not affected.
+    ///
+    /// Listeners are counted per port so independent callers don't stop each
+    /// other: while one is running, another call keeps it running with its
+    /// original options and only adds to the count, and the thread stops when
+    /// every call has been balanced by `stop_listening`. A call whose `decode`
+    /// differs from the running listener's fails with `Error::InvalidInput`,
+    /// as its caller would wait for events under another name.
     pub fn start_listening(
         &self,
         path: String,
         timeout: Option<u64>,
         size: Option<usize>,
+        base64: Option<bool>,
+        buffer_reads: Option<bool>,
+        idle_timeout: Option<u64>,
+        framing: Option<Framing>,
+        emit_strategy: Option<EmitStrategy>,
+        max_buffer_size: Option<usize>,
+        decode: Option<bool>,
     ) -> Result<(), Error> {
-        println!("Starting listening on port: {}", path);
+        // Event names are built from the key, as `open` returned it
+        let path = port_key(&path);
 
         self.get_serialport(path.clone(), |port_info| {
+            let log_level = port_info.log_level.clone();
+            log_info!(port = &log_level, "Starting listening on port: {}", path);
+            let running = port_info
+                .thread_handle
+                .as_ref()
+                .is_some_and(|handle| !handle.is_finished());
+            if port_info.sender.is_some() && running {
+                if decode != port_info.listen_decode {
+                    return Err(Error::InvalidInput(format!(
+                        "Serial port {} is already listening with decode {:?}, not {:?}",
+                        path, port_info.listen_decode, decode
+                    )));
+                }
+                port_info.listeners += 1;
+                log_debug!(
+                    port = &log_level,
+                    "Listener already running, now shared by {} callers",
+                    port_info.listeners
+                );
+                return Ok(());
+            }
+
             if port_info.sender.is_some() {
-                println!("Existing listener found, stopping it first");
+                log_debug!(
+                    port = &log_level,
+                    "Stopped listener found, cleaning it up first"
+                );
                 if let Some(sender) = &port_info.sender {
                     sender.send(1).map_err(|e| {
-                        eprintln!("Failed to stop existing listener: {}", e);
+                        log_error!(port = &log_level, "Failed to stop existing listener: {}", e);
                         Error::String(format!("Failed to stop existing listener: {}", e))
                     })?;
                 }
                 port_info.sender = None;
+                // Whoever started it is restarting it after the error that ended it
+                port_info.listeners = 0;
 
                 // Wait for thread to finish
                 if let Some(handle) = port_info.thread_handle.take() {
-                    println!("Waiting for existing thread to finish");
-                    if let Err(e) = handle.join() {
-                        eprintln!("Error joining thread: {:?}", e);
+                    log_debug!(port = &log_level, "Waiting for existing thread to finish");
+                    if let Err(e) = Self::join_with_timeout(handle) {
+                        log_error!(port = &log_level, "Error joining thread: {:?}", e);
                     }
                 }
             }
 
             // Start listening immediately after opening
-            let event_path = path.replace(".", "-").replace("/", "-");
-            let read_event = format!("plugin-serialplugin-read-{}", &event_path);
+            let event_path = event_path(&path);
+            let read_event = match decode {
+                Some(true) => format!("plugin-serialplugin-read-text-{}", &event_path),
+                Some(false) => format!("plugin-serialplugin-read-bytes-{}", &event_path),
+                None => format!("plugin-serialplugin-read-{}", &event_path),
+            };
             let disconnected_event = format!("plugin-serialplugin-disconnected-{}", &event_path);
+            let error_event = format!("plugin-serialplugin-error-{}", &event_path);
+            let idle_event = format!("plugin-serialplugin-idle-{}", &event_path);
+            // JSON lines go out parsed, under their own event name
+            let frame_event = if framing == Some(Framing::Json) {
+                format!("plugin-serialplugin-json-{}", &event_path)
+            } else {
+                format!("plugin-serialplugin-frame-{}", &event_path)
+            };
+            let line_error_event = format!("plugin-serialplugin-line-error-{}", &event_path);
 
-            println!("Setting up port monitoring for: {}", read_event);
+            log_debug!(
+                port = &log_level,
+                "Setting up port monitoring for: {}",
+                read_event
+            );
 
-            let mut serial = port_info
+            let serial = port_info
                 .serialport
                 .try_clone()
                 .map_err(|e| Error::String(format!("Failed to clone serial port: {}", e)))?;
+            // Shared so `reconfigure` can swap the handle under the running listener
+            let serial = Arc::new(Mutex::new(Some(serial)));
+            port_info.listener_port = Some(serial.clone());
 
             let (tx, rx): (Sender<usize>, Receiver<usize>) = mpsc::channel();
             port_info.sender = Some(tx);
+            port_info.listeners += 1;
+            port_info.listen_decode = decode;
 
-            let app_clone = self.app.clone();
+            port_info
+                .listen_state
+                .store(ListenState::Running.as_u8(), Ordering::Relaxed);
+            let listen_state = port_info.listen_state.clone();
+
+            // With `buffer_reads`, `read` is served from a copy of what the listener
+            // reads instead of competing with it for the device
+            port_info.listen_queue = if buffer_reads.unwrap_or(false) {
+                Some(Arc::new(ListenQueue::default()))
+            } else {
+                None
+            };
+            let listen_queue = port_info.listen_queue.clone();
+            let subscribers = port_info.subscribers.clone();
+            let stats = port_info.stats.clone();
+            let capture = port_info.capture.clone();
+
+            let app_clone = self.app.clone();
             let path_clone = path.clone();
+            let base64 = base64.unwrap_or(false);
             let thread_handle = thread::spawn(move || {
+                // Reused across iterations; each read only exposes the bytes it filled
+                let mut buffer = vec![0; size.unwrap_or(1024)];
+                // Data read while paused with `PausedReading`, delivered on resume
+                let mut held: Vec<u8> = Vec::new();
+                // Bytes since the last idle gap, emitted as one frame with `idle_timeout`
+                let mut frame: Vec<u8> = Vec::new();
+                let mut last_data = Instant::now();
+                let mut decoder = framing.map(|framing| frame_decoder(framing, max_buffer_size));
+                let mut read_emitter =
+                    ReadEmitter::new(emit_strategy, max_buffer_size, decode == Some(true));
+                // Each event name is numbered on its own, so a gap in one
+                // only ever means a lost event of that name
+                let mut read_seq: u64 = 0;
+                let mut frame_seq: u64 = 0;
+                let mut idle_seq: u64 = 0;
+                // Last line error counts, polled while the driver reports them
+                let mut line_errors: Option<LineErrors> = None;
+                let mut poll_line_errors = line_errors::SUPPORTED;
+                let mut last_line_check = Instant::now();
                 loop {
                     match rx.try_recv() {
                         Ok(_) => break,
@@ -403,34 +1453,229 @@ impl<R: Runtime> SerialPort<R> {
                                 &disconnected_event,
                                 format!("Serial port {} disconnected!", &path_clone),
                             ) {
-                                eprintln!("Failed to send disconnection event: {}", e);
+                                log_error!(
+                                    port = &log_level,
+                                    "Failed to send disconnection event: {}",
+                                    e
+                                );
                             }
                             break;
                         }
                         Err(TryRecvError::Empty) => {}
                     }
 
-                    let mut buffer = vec![0; size.unwrap_or(1024)];
-                    match serial.read(&mut buffer) {
+                    let state = ListenState::from_u8(listen_state.load(Ordering::Relaxed));
+                    if state == ListenState::Paused {
+                        thread::sleep(Duration::from_millis(timeout.unwrap_or(200)));
+                        continue;
+                    }
+
+                    if state == ListenState::Running && !held.is_empty() {
+                        if let Some(queue) = &listen_queue {
+                            Self::push_listen_queue(queue, &held);
+                        }
+                        Self::push_subscribers(&subscribers, &held);
+                        if let Err(e) =
+                            read_emitter.push(&app_clone, &read_event, &held, base64, &mut read_seq)
+                        {
+                            log_error!(port = &log_level, "Failed to send data: {}", e);
+                        }
+                        if let Some(decoder) = &mut decoder {
+                            Self::emit_frames(
+                                &app_clone,
+                                &frame_event,
+                                &path_clone,
+                                &log_level,
+                                decoder,
+                                &held,
+                                base64,
+                                &mut frame_seq,
+                            );
+                        }
+                        held.clear();
+                    }
+
+                    // A collecting batch cuts the read short, so its window or
+                    // debounce isn't stretched to the port timeout
+                    let until_due = read_emitter.until_due();
+                    let attached = match serial.lock() {
+                        Ok(mut slot) => slot
+                            .as_mut()
+                            .map(|port| Self::read_until(port.as_mut(), &mut buffer, until_due)),
+                        Err(_) => break,
+                    };
+                    let result = match attached {
+                        Some(result) => result,
+                        // Detached while `reconfigure` reopens the device
+                        None => {
+                            thread::sleep(Duration::from_millis(5));
+                            continue;
+                        }
+                    };
+                    match &result {
                         Ok(n) => {
-                            if let Err(e) = app_clone.emit(
+                            log_trace!(
+                                port = &log_level,
+                                "Read {} bytes from {}: {:02X?}",
+                                n,
+                                path_clone,
+                                &buffer[..*n]
+                            );
+                            stats.record_read(*n);
+                            Self::record_capture(&capture, CaptureDirection::Rx, &buffer[..*n]);
+                        }
+                        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
+                        Err(_) => stats.record_read_error(),
+                    }
+
+                    // Bytes dropped in this iteration for going over `max_buffer_size`
+                    let mut dropped = 0;
+                    match result {
+                        Ok(n) if state == ListenState::PausedReading => {
+                            held.extend_from_slice(&buffer[..n]);
+                            if let Some(max) = max_buffer_size {
+                                let excess = held.len().saturating_sub(max);
+                                held.drain(..excess);
+                                dropped += excess;
+                            }
+                        }
+                        Ok(n) => {
+                            if let Some(queue) = &listen_queue {
+                                Self::push_listen_queue(queue, &buffer[..n]);
+                            }
+                            Self::push_subscribers(&subscribers, &buffer[..n]);
+                            if idle_timeout.is_some() {
+                                frame.extend_from_slice(&buffer[..n]);
+                                last_data = Instant::now();
+                                if max_buffer_size.is_some_and(|max| frame.len() >= max) {
+                                    if let Err(e) = Self::emit_read_data(
+                                        &app_clone,
+                                        &idle_event,
+                                        &frame,
+                                        base64,
+                                        &mut idle_seq,
+                                    ) {
+                                        log_error!(
+                                            port = &log_level,
+                                            "Failed to send idle frame: {}",
+                                            e
+                                        );
+                                    }
+                                    frame.clear();
+                                }
+                            }
+                            if let Some(decoder) = &mut decoder {
+                                Self::emit_frames(
+                                    &app_clone,
+                                    &frame_event,
+                                    &path_clone,
+                                    &log_level,
+                                    decoder,
+                                    &buffer[..n],
+                                    base64,
+                                    &mut frame_seq,
+                                );
+                            }
+                            let emitted = read_emitter.push(
+                                &app_clone,
                                 &read_event,
-                                ReadData {
-                                    data: &buffer[..n],
-                                    size: n,
-                                },
-                            ) {
-                                eprintln!("Failed to send data: {}", e);
+                                &buffer[..n],
+                                base64,
+                                &mut read_seq,
+                            );
+                            if let Err(e) = emitted {
+                                log_error!(port = &log_level, "Failed to send data: {}", e);
+                                Self::emit_listener_error(
+                                    &app_clone,
+                                    &error_event,
+                                    &path_clone,
+                                    ListenerErrorKind::Emit,
+                                    format!("Failed to send data: {}", e),
+                                );
                             }
                         }
                         Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {}
                         Err(e) => {
-                            eprintln!("Failed to read data: {}", e);
+                            log_error!(port = &log_level, "Failed to read data: {}", e);
+                            Self::emit_listener_error(
+                                &app_clone,
+                                &error_event,
+                                &path_clone,
+                                ListenerErrorKind::Read,
+                                format!("Failed to read data: {}", e),
+                            );
                             break; // Exit on error
                         }
                     }
 
-                    thread::sleep(Duration::from_millis(timeout.unwrap_or(200)));
+                    if let Some(decoder) = &mut decoder {
+                        dropped += decoder.take_dropped();
+                    }
+                    if dropped > 0 {
+                        Self::emit_listener_error(
+                            &app_clone,
+                            &error_event,
+                            &path_clone,
+                            ListenerErrorKind::Overflow,
+                            format!("Dropped {} bytes over the buffer limit", dropped),
+                        );
+                    }
+
+                    if let Some(idle_timeout) = idle_timeout {
+                        if !frame.is_empty()
+                            && last_data.elapsed() >= Duration::from_millis(idle_timeout)
+                        {
+                            if let Err(e) = Self::emit_read_data(
+                                &app_clone,
+                                &idle_event,
+                                &frame,
+                                base64,
+                                &mut idle_seq,
+                            ) {
+                                log_error!(port = &log_level, "Failed to send idle frame: {}", e);
+                            }
+                            frame.clear();
+                        }
+                    }
+
+                    if poll_line_errors && last_line_check.elapsed() >= LINE_ERROR_POLL {
+                        last_line_check = Instant::now();
+                        match line_errors::read(&path_clone) {
+                            Ok(counts) => {
+                                if line_errors.is_some_and(|last| last != counts) {
+                                    if let Err(e) = app_clone.emit(&line_error_event, counts) {
+                                        log_error!(
+                                            port = &log_level,
+                                            "Failed to send line errors: {}",
+                                            e
+                                        );
+                                    }
+                                }
+                                line_errors = Some(counts);
+                            }
+                            Err(e) => {
+                                log_debug!(port = &log_level, "Not watching line errors: {}", e);
+                                poll_line_errors = false;
+                            }
+                        }
+                    }
+
+                    if let Err(e) =
+                        read_emitter.flush_due(&app_clone, &read_event, base64, &mut read_seq)
+                    {
+                        log_error!(port = &log_level, "Failed to send batch: {}", e);
+                    }
+
+                    // With a strategy the blocking read paces the loop instead
+                    if emit_strategy.is_none() {
+                        thread::sleep(Duration::from_millis(timeout.unwrap_or(200)));
+                    }
+                }
+
+                // A batch still waiting for its window, or a character cut off
+                // by the end of the data, would be lost otherwise
+                if let Err(e) = read_emitter.flush(&app_clone, &read_event, base64, &mut read_seq) {
+                    log_error!(port = &log_level, "Failed to send batch: {}", e);
                 }
             });
 
@@ -440,124 +1685,1533 @@ impl<R: Runtime> SerialPort<R> {
         })
     }
 
-    pub fn stop_listening(&self, path: String) -> Result<(), Error> {
-        println!("Stopping listening on port: {}", path);
+    /// Feed received data to `decoder` and emit every frame it completes
+    fn emit_frames(
+        app: &AppHandle<R>,
+        event: &str,
+        path: &str,
+        log_level: &PortLogLevel,
+        decoder: &mut FrameDecoder,
+        data: &[u8],
+        base64: bool,
+        seq: &mut u64,
+    ) {
+        for frame in decoder.push(data) {
+            if decoder.framing() == Framing::Json {
+                Self::emit_json_line(app, event, path, log_level, &frame, seq);
+            } else if let Err(e) = Self::emit_read_data(app, event, &frame, base64, seq) {
+                log_error!(port = log_level, "Failed to send frame: {}", e);
+            }
+        }
+    }
+
+    /// Parse a line received with `Framing::Json` and emit the value, or a
+    /// `Parse` error event if it isn't valid JSON
+    fn emit_json_line(
+        app: &AppHandle<R>,
+        event: &str,
+        path: &str,
+        log_level: &PortLogLevel,
+        line: &[u8],
+        seq: &mut u64,
+    ) {
+        let value = match serde_json::from_slice(line) {
+            Ok(value) => value,
+            Err(e) => {
+                log_debug!(
+                    port = log_level,
+                    "Invalid JSON line: {}",
+                    String::from_utf8_lossy(line)
+                );
+                Self::emit_listener_error(
+                    app,
+                    &format!("plugin-serialplugin-error-{}", event_path(path)),
+                    path,
+                    ListenerErrorKind::Parse,
+                    format!("Invalid JSON line: {}", e),
+                );
+                return;
+            }
+        };
+
+        let current = *seq;
+        *seq += 1;
+        let data = JsonData {
+            value,
+            seq: current,
+            timestamp_ms: crate::state::unix_millis(),
+        };
+        if let Err(e) = app.emit(event, data) {
+            log_error!(port = log_level, "Failed to send JSON line: {}", e);
+        }
+    }
+
+    /// Emit a chunk of received data as a `ReadData` or `ReadDataBase64` payload,
+    /// numbered with `seq`, which is advanced even if emitting fails
+    fn emit_read_data(
+        app: &AppHandle<R>,
+        event: &str,
+        data: &[u8],
+        base64: bool,
+        seq: &mut u64,
+    ) -> tauri::Result<()> {
+        let current = *seq;
+        *seq += 1;
+        let timestamp_ms = crate::state::unix_millis();
+        if base64 {
+            app.emit(
+                event,
+                ReadDataBase64 {
+                    data: base64::engine::general_purpose::STANDARD.encode(data),
+                    size: data.len(),
+                    seq: current,
+                    timestamp_ms,
+                },
+            )
+        } else {
+            app.emit(
+                event,
+                ReadData {
+                    data,
+                    size: data.len(),
+                    seq: current,
+                    timestamp_ms,
+                },
+            )
+        }
+    }
+
+    /// Subscribe to the data a port's listener receives
+    ///
+    /// Every chunk the listener reads is sent to each subscriber as well as
+    /// emitted as an event, so Rust code can consume the stream directly. Data
+    /// only flows while the port is listening; dropping the receiver unsubscribes.
+    pub fn subscribe(&self, path: String) -> Result<Receiver<Vec<u8>>, Error> {
+        self.get_serialport(path, |port_info| {
+            let (tx, rx) = mpsc::channel();
+            port_info
+                .subscribers
+                .lock()
+                .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))?
+                .push(tx);
+            Ok(rx)
+        })
+    }
+
+    /// Get a `std::io::Read` handle for a managed port
+    ///
+    /// The handle reads from a clone of the port and starts failing once the
+    /// port is closed through the plugin.
+    pub fn reader(&self, path: String) -> Result<ManagedReader, Error> {
+        let path = port_key(&path);
+        self.get_serialport(path.clone(), |port_info| {
+            let serialport = port_info
+                .serialport
+                .try_clone()
+                .map_err(|e| Error::String(format!("Failed to clone serial port: {}", e)))?;
+            Ok(ManagedReader {
+                path,
+                serialports: self.serialports.clone(),
+                serialport,
+            })
+        })
+    }
+
+    /// Get a `std::io::Write` handle for a managed port
+    ///
+    /// The handle writes to a clone of the port and starts failing once the
+    /// port is closed through the plugin.
+    pub fn writer(&self, path: String) -> Result<ManagedWriter, Error> {
+        let path = port_key(&path);
+        self.get_serialport(path.clone(), |port_info| {
+            let serialport = port_info
+                .serialport
+                .try_clone()
+                .map_err(|e| Error::String(format!("Failed to clone serial port: {}", e)))?;
+            Ok(ManagedWriter {
+                path,
+                serialports: self.serialports.clone(),
+                serialport,
+            })
+        })
+    }
+
+    /// Send data to every subscriber, forgetting those whose receiver was dropped
+    fn push_subscribers(subscribers: &Mutex<Vec<Sender<Vec<u8>>>>, data: &[u8]) {
+        if let Ok(mut subscribers) = subscribers.lock() {
+            subscribers.retain(|subscriber| subscriber.send(data.to_vec()).is_ok());
+        }
+    }
+
+    /// Append data to a listener's read queue, dropping the oldest bytes when full
+    fn push_listen_queue(queue: &ListenQueue, data: &[u8]) {
+        if let Ok(mut queued) = queue.data.lock() {
+            queued.extend(data);
+            let excess = queued.len().saturating_sub(ListenQueue::CAPACITY);
+            queued.drain(..excess);
+            queue.ready.notify_all();
+        }
+    }
+
+    /// Take up to `size` bytes (everything when `None`) from a listener's read
+    /// queue, waiting until `deadline`, or indefinitely, for data to arrive
+    fn take_listen_queue(
+        queue: &ListenQueue,
+        deadline: Option<Instant>,
+        size: Option<usize>,
+        min_size: usize,
+        cancel: &AtomicBool,
+    ) -> Result<Vec<u8>, Error> {
+        let mut queued = queue
+            .data
+            .lock()
+            .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))?;
+        while queued.len() < min_size.max(1) {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(Error::Cancelled("Read was cancelled".to_string()));
+            }
+            let wait = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(Instant::now()),
+                None => CANCEL_POLL_INTERVAL,
+            };
+            if wait.is_zero() {
+                break;
+            }
+            queued = queue
+                .ready
+                .wait_timeout(queued, wait.min(CANCEL_POLL_INTERVAL))
+                .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))?
+                .0;
+        }
 
+        let n = size.unwrap_or(queued.len()).min(queued.len());
+        Ok(queued.drain(..n).collect())
+    }
+
+    /// Pause the listener without stopping its thread
+    ///
+    /// With `stop_reading` the listener leaves incoming bytes in the OS buffer,
+    /// so a direct `read` can pick them up; otherwise it keeps reading and
+    /// delivers everything it held back once resumed.
+    pub fn pause_listening(&self, path: String, stop_reading: bool) -> Result<(), Error> {
         self.get_serialport(path.clone(), |port_info| {
+            if port_info.sender.is_none() {
+                return Err(Error::String(format!(
+                    "Serial port {} is not listening!",
+                    path
+                )));
+            }
+
+            let state = if stop_reading {
+                ListenState::Paused
+            } else {
+                ListenState::PausedReading
+            };
+            port_info
+                .listen_state
+                .store(state.as_u8(), Ordering::Relaxed);
+
+            Ok(())
+        })
+    }
+
+    /// Resume a listener paused with `pause_listening`
+    pub fn resume_listening(&self, path: String) -> Result<(), Error> {
+        self.get_serialport(path, |port_info| {
+            port_info
+                .listen_state
+                .store(ListenState::Running.as_u8(), Ordering::Relaxed);
+
+            Ok(())
+        })
+    }
+
+    /// Report a listener failure to the frontend
+    fn emit_listener_error(
+        app: &AppHandle<R>,
+        event: &str,
+        path: &str,
+        kind: ListenerErrorKind,
+        message: String,
+    ) {
+        let error = ListenerError {
+            path: path.to_string(),
+            kind,
+            message,
+        };
+        if let Err(e) = app.emit(event, error) {
+            log_error!("Failed to send error event: {}", e);
+        }
+    }
+
+    /// Read into `buffer`, waiting no longer than `limit` if it's shorter than
+    /// the port timeout
+    fn read_until(
+        port: &mut dyn serialport::SerialPort,
+        buffer: &mut [u8],
+        limit: Option<Duration>,
+    ) -> std::io::Result<usize> {
+        let timeout = port.timeout();
+        // A zero timeout wouldn't wait at all on some platforms
+        let limit = match limit {
+            Some(limit) if limit < timeout => limit.max(Duration::from_millis(1)),
+            _ => return port.read(buffer),
+        };
+        port.set_timeout(limit)?;
+        let result = port.read(buffer);
+        port.set_timeout(timeout)?;
+        result
+    }
+
+    /// Give back one `start_listening`, stopping the listener if it was the last
+    ///
+    /// Data a `Batched` or `Coalesced` listener still holds is emitted before
+    /// this returns.
+    pub fn stop_listening(&self, path: String) -> Result<(), Error> {
+        let stopped = self.get_serialport(path.clone(), |port_info| {
+            log_info!(
+                port = &port_info.log_level,
+                "Stopping listening on port: {}",
+                path
+            );
+            if port_info.listeners > 1 {
+                port_info.listeners -= 1;
+                log_debug!(
+                    port = &port_info.log_level,
+                    "Listener still used by {} callers",
+                    port_info.listeners
+                );
+                return Ok(None);
+            }
+            port_info.listeners = 0;
+
             if let Some(sender) = &port_info.sender {
                 sender.send(1).map_err(|e| {
                     Error::String(format!("Failed to cancel serial port data reading: {}", e))
                 })?;
             }
             port_info.sender = None;
-            port_info.thread_handle = None;
+            port_info.listen_queue = None;
+            // A pause ends with the listener
+            port_info
+                .listen_state
+                .store(ListenState::Running.as_u8(), Ordering::Relaxed);
 
-            Ok({})
+            Ok(port_info
+                .thread_handle
+                .take()
+                .map(|handle| (handle, port_info.log_level.clone())))
+        })?;
+
+        // Waited for, with the map unlocked, so the last coalesced or batched
+        // event is out before the caller stops listening for it
+        if let Some((handle, log_level)) = stopped {
+            if let Err(e) = Self::join_with_timeout(handle) {
+                log_error!(port = &log_level, "Error joining listener thread: {:?}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Write `data` to the port every `interval` milliseconds until stopped
+    ///
+    /// The heartbeat ends on its own when the port is closed. A failed write emits
+    /// a `plugin-serialplugin-error-*` event and stops the heartbeat.
+    pub fn start_heartbeat(&self, path: String, data: Vec<u8>, interval: u64) -> Result<(), Error> {
+        let path = port_key(&path);
+        self.get_serialport(path.clone(), |port_info| {
+            if let Some(sender) = port_info.heartbeat_sender.take() {
+                let _ = sender.send(1);
+            }
+            if let Some(handle) = port_info.heartbeat_thread.take() {
+                if let Err(e) = Self::join_with_timeout(handle) {
+                    log_error!("Error joining heartbeat thread: {:?}", e);
+                }
+            }
+
+            let error_event = format!("plugin-serialplugin-error-{}", event_path(&path));
+
+            let mut serial = port_info
+                .serialport
+                .try_clone()
+                .map_err(|e| Error::String(format!("Failed to clone serial port: {}", e)))?;
+
+            let (tx, rx): (Sender<usize>, Receiver<usize>) = mpsc::channel();
+            port_info.heartbeat_sender = Some(tx);
+
+            let stats = port_info.stats.clone();
+            let capture = port_info.capture.clone();
+            let log_level = port_info.log_level.clone();
+            let write_lock = port_info.write_lock.clone();
+            let app_clone = self.app.clone();
+            let path_clone = path.clone();
+            let interval = Duration::from_millis(interval);
+            let thread_handle = thread::spawn(move || {
+                // Scheduled against a fixed start so slow writes don't make it drift
+                let mut next_beat = Instant::now();
+                loop {
+                    // Taken like any other write, so a beat never lands inside one
+                    let written = {
+                        let _write = write_lock.lock().unwrap_or_else(|e| e.into_inner());
+                        serial.write_all(&data).and_then(|_| serial.flush())
+                    };
+                    if let Err(e) = written {
+                        log_error!(port = &log_level, "Failed to write heartbeat: {}", e);
+                        Self::emit_listener_error(
+                            &app_clone,
+                            &error_event,
+                            &path_clone,
+                            ListenerErrorKind::Heartbeat,
+                            format!("Failed to write heartbeat: {}", e),
+                        );
+                        break;
+                    }
+                    stats.record_write(data.len());
+                    Self::record_capture(&capture, CaptureDirection::Tx, &data);
+
+                    next_beat += interval;
+                    let wait = next_beat.saturating_duration_since(Instant::now());
+                    // A stop message or a dropped sender (port closed) ends the heartbeat
+                    if !matches!(rx.recv_timeout(wait), Err(mpsc::RecvTimeoutError::Timeout)) {
+                        break;
+                    }
+                }
+            });
+
+            port_info.heartbeat_thread = Some(thread_handle);
+
+            Ok(())
         })
     }
 
+    /// Stop the heartbeat started by `start_heartbeat`
+    pub fn stop_heartbeat(&self, path: String) -> Result<(), Error> {
+        self.get_serialport(path, |port_info| {
+            // The thread may already have exited on a write error, so a failed send is fine
+            if let Some(sender) = port_info.heartbeat_sender.take() {
+                let _ = sender.send(1);
+            }
+            port_info.heartbeat_thread = None;
+
+            Ok(())
+        })
+    }
+
+    /// Close the port once it has gone `timeout` milliseconds without a read or
+    /// write, or turn the watchdog off with `None`
+    ///
+    /// Activity is whatever `get_stats` counts, so listener reads and heartbeat
+    /// writes keep the port open too. When the watchdog fires it closes the
+    /// port and emits a `plugin-serialplugin-auto-closed-*` event.
+    pub fn idle_auto_close(&self, path: String, timeout: Option<u64>) -> Result<(), Error> {
+        let path = port_key(&path);
+        self.get_serialport(path.clone(), |port_info| {
+            // Not joined: the old watchdog may be waiting for the map lock we
+            // hold, and it gives up as soon as it sees the stop message
+            if let Some(sender) = port_info.idle_sender.take() {
+                let _ = sender.send(1);
+            }
+            port_info.idle_thread = None;
+
+            let timeout = match timeout {
+                Some(timeout) => Duration::from_millis(timeout),
+                None => return Ok(()),
+            };
+
+            let (tx, rx): (Sender<usize>, Receiver<usize>) = mpsc::channel();
+            port_info.idle_sender = Some(tx);
+
+            let auto_closed_event =
+                format!("plugin-serialplugin-auto-closed-{}", event_path(&path));
+            let stats = port_info.stats.clone();
+            let log_level = port_info.log_level.clone();
+            let serialports = self.serialports.clone();
+            let app_clone = self.app.clone();
+            let path_clone = path.clone();
+            let thread_handle = thread::spawn(move || {
+                let started = crate::state::unix_millis();
+                let idle_for = || {
+                    let last_activity = stats.last_activity.load(Ordering::Relaxed).max(started);
+                    Duration::from_millis(crate::state::unix_millis().saturating_sub(last_activity))
+                };
+                loop {
+                    let idle = idle_for();
+                    if idle < timeout {
+                        // A stop message or a dropped sender (port closed) ends the watchdog
+                        match rx.recv_timeout(timeout - idle) {
+                            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+                            _ => break,
+                        }
+                    }
+
+                    let mut serialports = lock_ports(&serialports);
+                    // Stopped, replaced or closed while waiting for the lock
+                    if !matches!(rx.try_recv(), Err(TryRecvError::Empty)) {
+                        break;
+                    }
+                    if idle_for() < timeout {
+                        continue;
+                    }
+                    if let Some(port_info) = serialports.remove(&path_clone) {
+                        if let Err(e) = Self::shut_down(port_info) {
+                            log_error!(port = &log_level, "Error closing idle port: {}", e);
+                        }
+                    }
+                    drop(serialports);
+
+                    log_info!(
+                        port = &log_level,
+                        "Closed serial port {} after {} ms without traffic",
+                        path_clone,
+                        timeout.as_millis()
+                    );
+                    if let Err(e) = app_clone.emit(
+                        &auto_closed_event,
+                        format!(
+                            "Serial port {} was closed after {} ms without traffic",
+                            &path_clone,
+                            timeout.as_millis()
+                        ),
+                    ) {
+                        log_error!(port = &log_level, "Failed to send auto-close event: {}", e);
+                    }
+                    break;
+                }
+            });
+            port_info.idle_thread = Some(thread_handle);
+
+            Ok(())
+        })
+    }
+
+    /// Watch the CTS/DSR/RI/CD lines and emit an event whenever any of them changes
+    ///
+    /// The lines are sampled every `poll_interval` milliseconds, which must be
+    /// at least 1.
+    pub fn watch_modem_signals(&self, path: String, poll_interval: u64) -> Result<(), Error> {
+        let path = port_key(&path);
+        if poll_interval == 0 {
+            return Err(Error::InvalidInput(
+                "poll_interval must be at least 1 ms".to_string(),
+            ));
+        }
+        self.get_serialport(path.clone(), |port_info| {
+            if let Some(sender) = port_info.signal_sender.take() {
+                let _ = sender.send(1);
+            }
+            if let Some(handle) = port_info.signal_thread.take() {
+                if let Err(e) = Self::join_with_timeout(handle) {
+                    log_error!("Error joining modem signal thread: {:?}", e);
+                }
+            }
+
+            let signals_event = format!("plugin-serialplugin-signals-{}", event_path(&path));
+
+            let mut serial = port_info
+                .serialport
+                .try_clone()
+                .map_err(|e| Error::String(format!("Failed to clone serial port: {}", e)))?;
+
+            let (tx, rx): (Sender<usize>, Receiver<usize>) = mpsc::channel();
+            port_info.signal_sender = Some(tx);
+
+            let app_clone = self.app.clone();
+            let thread_handle = thread::spawn(move || {
+                let mut last_status: Option<ModemStatus> = None;
+                loop {
+                    // A dropped sender means the port was closed
+                    match rx.try_recv() {
+                        Ok(_) | Err(TryRecvError::Disconnected) => break,
+                        Err(TryRecvError::Empty) => {}
+                    }
+
+                    match Self::read_modem_status(serial.as_mut()) {
+                        Ok(status) => {
+                            if last_status.is_some() && last_status != Some(status) {
+                                if let Err(e) = app_clone.emit(&signals_event, status) {
+                                    log_error!("Failed to send modem signals: {}", e);
+                                }
+                            }
+                            last_status = Some(status);
+                        }
+                        Err(e) => {
+                            log_error!("Failed to read modem signals: {}", e);
+                            break;
+                        }
+                    }
+
+                    thread::sleep(Duration::from_millis(poll_interval));
+                }
+            });
+
+            port_info.signal_thread = Some(thread_handle);
+
+            Ok(())
+        })
+    }
+
+    /// Stop watching the modem status lines
+    pub fn stop_watching_modem_signals(&self, path: String) -> Result<(), Error> {
+        self.get_serialport(path, |port_info| {
+            // The thread may already have exited on a read error, so a failed send is fine
+            if let Some(sender) = port_info.signal_sender.take() {
+                let _ = sender.send(1);
+            }
+            port_info.signal_thread = None;
+
+            Ok(())
+        })
+    }
+
+    /// Get the parity, framing and overrun error counts of a port
+    ///
+    /// Only Linux drivers keep these counters. While listening, increases are
+    /// also reported as `plugin-serialplugin-line-error-*` events.
+    pub fn get_line_errors(&self, path: String) -> Result<LineErrors, Error> {
+        // The lock keeps the port, and so its file descriptor, open meanwhile
+        self.get_serialport(path.clone(), |_| line_errors::read(&path))
+    }
+
+    /// Set the latency timer of an FTDI adapter, in milliseconds (1 to 255)
+    ///
+    /// Lowering it from the default 16 ms makes short replies arrive sooner.
+    /// On Linux it applies at once and the driver resets it when the adapter
+    /// is plugged in again. On Windows it is stored in the FTDI driver's
+    /// settings for the port, which takes administrator rights, and applies
+    /// from the next time the port is opened, e.g. by `reconnect`. Other
+    /// adapters and macOS fail with `Error::Unsupported`.
+    pub fn set_latency_timer(&self, path: String, ms: u32) -> Result<(), Error> {
+        let ms = match u8::try_from(ms) {
+            Ok(ms) if ms > 0 => ms,
+            _ => {
+                return Err(Error::InvalidInput(format!(
+                    "Latency timer must be 1 to 255 ms, got {}",
+                    ms
+                )))
+            }
+        };
+        self.get_serialport(path.clone(), |_| latency::set(&path, ms))
+    }
+
     /// Read data from the serial port
+    ///
+    /// Bytes previously returned by `peek` are handed out first; the device is
+    /// only read once that holdback buffer is empty. With `size: None` everything
+    /// currently available is returned rather than a single buffer's worth.
+    ///
+    /// `timeout` is in milliseconds. Without one the timeout set with
+    /// `set_read_timeout` applies, and if there is none either the read blocks
+    /// until at least one byte arrives or `cancel_operation` is called. Other
+    /// reads of the port wait meanwhile; writes and other ports don't. A read
+    /// that times out without data fails with `Error::Timeout`.
     pub fn read(
         &self,
         path: String,
         timeout: Option<u64>,
         size: Option<usize>,
     ) -> Result<String, Error> {
-        self.get_serialport(path.clone(), |serialport_info| {
-            let mut buffer = vec![0; size.unwrap_or(1024)];
-            serialport_info
-                .serialport
-                .set_timeout(Duration::from_millis(timeout.unwrap_or(200)))
+        self.read_binary(path, timeout, size, None)
+            .map(|data| String::from_utf8_lossy(&data).to_string())
+    }
+
+    /// Read raw bytes from the serial port, without the lossy UTF-8 conversion of `read`
+    ///
+    /// Normally the first bytes to arrive are returned, even if fewer than
+    /// `size`. With `min_size` reading goes on until at least that many bytes
+    /// are in, or the timeout for the whole call runs out and what arrived so
+    /// far is returned. If nothing arrived at all the read fails with
+    /// `Error::Timeout`, which callers can tell from a failed read.
+    pub fn read_binary(
+        &self,
+        path: String,
+        timeout: Option<u64>,
+        size: Option<usize>,
+        min_size: Option<usize>,
+    ) -> Result<Vec<u8>, Error> {
+        let cancel = self.cancel_flag(&path)?;
+        cancel.store(false, Ordering::Relaxed);
+
+        // Without `min_size` any data ends the read, as one read is all it takes
+        let min_size = min_size.unwrap_or(0).min(size.unwrap_or(usize::MAX));
+
+        let started = Instant::now();
+        let data = self.with_port_io(
+            &path,
+            |port_info| vec![port_info.read_lock.clone()],
+            |serialport_info| {
+                if !serialport_info.holdback.is_empty()
+                    && serialport_info.holdback.len() >= min_size
+                {
+                    let n = size
+                        .unwrap_or(serialport_info.holdback.len())
+                        .min(serialport_info.holdback.len());
+                    return Ok(ReadSource::Held(
+                        serialport_info.holdback.drain(..n).collect(),
+                    ));
+                }
+
+                // Without any timeout the read waits for data until cancelled
+                let deadline = timeout
+                    .map(Duration::from_millis)
+                    .or(serialport_info.read_timeout)
+                    .map(|timeout| Instant::now() + timeout);
+                let held = serialport_info.holdback.len();
+
+                Ok(match &serialport_info.listen_queue {
+                    Some(queue) => ReadSource::Queue {
+                        queue: queue.clone(),
+                        deadline,
+                        held,
+                    },
+                    None => ReadSource::Device {
+                        port: serialport_info.serialport.try_clone()?,
+                        stats: serialport_info.stats.clone(),
+                        capture: serialport_info.capture.clone(),
+                        deadline,
+                        held,
+                    },
+                })
+            },
+            |source| {
+                // Held-back bytes short of `min_size` start the result, and
+                // stay held back if the read fails
+                let read = match source {
+                    ReadSource::Held(data) => return Ok(data),
+                    ReadSource::Queue {
+                        queue,
+                        deadline,
+                        held,
+                    } => Self::take_listen_queue(
+                        &queue,
+                        deadline,
+                        size.map(|size| size - held),
+                        min_size - held,
+                        &cancel,
+                    )?,
+                    ReadSource::Device {
+                        mut port,
+                        stats,
+                        capture,
+                        deadline,
+                        held,
+                    } => {
+                        // The read is done in short slices so `cancel_operation`
+                        // is noticed promptly. The timeout is restored even
+                        // though the clone is dropped afterwards: on Windows all
+                        // handles of a port share their timeouts.
+                        let previous_timeout = port.timeout();
+                        let result = Self::read_at_least(
+                            port.as_mut(),
+                            size.map(|size| size - held),
+                            min_size - held,
+                            deadline,
+                            &cancel,
+                        );
+
+                        match &result {
+                            Ok(data) => {
+                                stats.record_read(data.len());
+                                Self::record_capture(&capture, CaptureDirection::Rx, data);
+                            }
+                            Err(Error::Cancelled(_)) => {}
+                            Err(_) => stats.record_read_error(),
+                        }
+
+                        port.set_timeout(previous_timeout).map_err(|e| {
+                            Error::String(format!("Failed to restore timeout: {}", e))
+                        })?;
+                        result?
+                    }
+                };
+
+                // Nothing else takes from the holdback while the read lock is held
+                let mut data = self.get_serialport(path.clone(), |serialport_info| {
+                    Ok(serialport_info.holdback.drain(..).collect::<Vec<u8>>())
+                })?;
+                data.extend(read);
+                Ok(data)
+            },
+        )?;
+
+        if data.is_empty() && size != Some(0) {
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            return Err(Error::Timeout {
+                message: format!("No data received within {} ms", elapsed_ms),
+                elapsed_ms,
+            });
+        }
+        Ok(data)
+    }
+
+    /// Read up to `size` bytes, or everything available with `None`, going on
+    /// until at least `min_size` bytes arrived or `deadline` passed; without a
+    /// deadline it waits for at least one byte
+    fn read_at_least(
+        serial: &mut dyn serialport::SerialPort,
+        size: Option<usize>,
+        min_size: usize,
+        deadline: Option<Instant>,
+        cancel: &AtomicBool,
+    ) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::new();
+        loop {
+            let chunk = match size {
+                Some(size) => {
+                    let mut buffer = vec![0; size - data.len()];
+                    let n = Self::read_cancellable(serial, &mut buffer, deadline, cancel)?;
+                    buffer.truncate(n);
+                    buffer
+                }
+                None => Self::read_pending(serial, deadline, cancel)?,
+            };
+            data.extend_from_slice(&chunk);
+            if chunk.is_empty() || data.len() >= min_size || Self::passed(deadline) {
+                return Ok(data);
+            }
+        }
+    }
+
+    /// Read bytes and return them as uppercase hex separated by spaces
+    pub fn read_hex(
+        &self,
+        path: String,
+        timeout: Option<u64>,
+        size: Option<usize>,
+    ) -> Result<String, Error> {
+        self.read_binary(path, timeout, size, None)
+            .map(|data| crate::hex::to_hex(&data))
+    }
+
+    /// Read exactly `size` bytes, for protocols that know how long a message is
+    ///
+    /// Waits up to `timeout` milliseconds for all of them. If only some
+    /// arrived the read fails with `Error::PartialRead`, which carries those
+    /// bytes; if none did, with `Error::Timeout`.
+    pub fn read_exact_or_partial(
+        &self,
+        path: String,
+        size: usize,
+        timeout: Option<u64>,
+    ) -> Result<Vec<u8>, Error> {
+        let data = self.read_binary(path, timeout, Some(size), Some(size))?;
+        if data.len() < size {
+            return Err(Error::PartialRead {
+                data,
+                expected: size,
+            });
+        }
+        Ok(data)
+    }
+
+    /// Read once and return the data both as bytes and as text
+    ///
+    /// For showing the same data in a hex and a text view: calling `read` and
+    /// `read_binary` one after the other would split it between the two. The
+    /// text is decoded like `read` does, as lossy UTF-8.
+    pub fn read_both(
+        &self,
+        path: String,
+        timeout: Option<u64>,
+        size: Option<usize>,
+    ) -> Result<BytesAndText, Error> {
+        let bytes = self.read_binary(path, timeout, size, None)?;
+        let text = String::from_utf8_lossy(&bytes).into_owned();
+        Ok(BytesAndText { bytes, text })
+    }
+
+    /// Read whatever is buffered right now, up to `max_size` bytes, without
+    /// waiting
+    ///
+    /// For poll loops: only as many bytes as `bytes_to_read` reports are read,
+    /// so the call returns at once, and an empty buffer gives an empty result
+    /// instead of `Error::Timeout`. While listening with `buffer_reads` the
+    /// listener's copy is taken from instead of the device.
+    pub fn read_available(&self, path: String, max_size: usize) -> Result<Vec<u8>, Error> {
+        // Quick enough to run under the map lock, but not in the middle of a read
+        self.with_port_io(
+            &path,
+            |port_info| vec![port_info.read_lock.clone()],
+            |port_info| {
+                let held = port_info.holdback.len().min(max_size);
+                let mut data: Vec<u8> = port_info.holdback.drain(..held).collect();
+                let wanted = max_size - data.len();
+
+                if let Some(queue) = &port_info.listen_queue {
+                    let mut queued = queue
+                        .data
+                        .lock()
+                        .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))?;
+                    let n = wanted.min(queued.len());
+                    data.extend(queued.drain(..n));
+                    return Ok(data);
+                }
+
+                let pending = port_info.serialport.bytes_to_read()? as usize;
+                let mut buffer = vec![0; wanted.min(pending)];
+                if buffer.is_empty() {
+                    return Ok(data);
+                }
+                let n = match port_info.serialport.read(&mut buffer) {
+                    Ok(n) => n,
+                    Err(e) if e.kind() == std::io::ErrorKind::TimedOut => 0,
+                    Err(e) => {
+                        port_info.stats.record_read_error();
+                        return Err(Error::String(format!("Failed to read data: {}", e)));
+                    }
+                };
+                buffer.truncate(n);
+                port_info.stats.record_read(n);
+                Self::record_capture(&port_info.capture, CaptureDirection::Rx, &buffer);
+                data.extend(buffer);
+                Ok(data)
+            },
+            Ok,
+        )
+    }
+
+    /// Read everything the device has buffered
+    ///
+    /// Waits until `deadline` for the first bytes, then keeps reading while more
+    /// are pending. The deadline also bounds the whole call, so a continuous
+    /// stream can't keep it reading forever.
+    fn read_pending(
+        serial: &mut dyn serialport::SerialPort,
+        deadline: Option<Instant>,
+        cancel: &AtomicBool,
+    ) -> Result<Vec<u8>, Error> {
+        let mut data = Vec::new();
+        let mut chunk = [0u8; 1024];
+
+        loop {
+            let n = Self::read_cancellable(serial, &mut chunk, deadline, cancel)?;
+            if n == 0 {
+                break;
+            }
+            data.extend_from_slice(&chunk[..n]);
+
+            let pending = serial
+                .bytes_to_read()
+                .map_err(|e| Error::String(format!("Failed to read data: {}", e)))?;
+            if Self::passed(deadline) || pending == 0 {
+                break;
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// A single read that gives up at `deadline` (returning 0 bytes) or with
+    /// `Error::Cancelled` once `cancel` is set
+    ///
+    /// Changes the port timeout; callers restore it afterwards.
+    fn read_cancellable(
+        serial: &mut dyn serialport::SerialPort,
+        buffer: &mut [u8],
+        deadline: Option<Instant>,
+        cancel: &AtomicBool,
+    ) -> Result<usize, Error> {
+        loop {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(Error::Cancelled("Read was cancelled".to_string()));
+            }
+
+            let slice = match deadline {
+                Some(deadline) => deadline
+                    .saturating_duration_since(Instant::now())
+                    .min(CANCEL_POLL_INTERVAL),
+                None => CANCEL_POLL_INTERVAL,
+            };
+            serial
+                .set_timeout(slice)
                 .map_err(|e| Error::String(format!("Failed to set timeout: {}", e)))?;
 
-            match serialport_info.serialport.read(&mut buffer) {
-                Ok(n) => {
-                    let data = String::from_utf8_lossy(&buffer[..n]).to_string();
-                    Ok(data)
+            match serial.read(buffer) {
+                Ok(n) => return Ok(n),
+                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => {
+                    if Self::passed(deadline) {
+                        return Ok(0);
+                    }
                 }
-                Err(e) if e.kind() == std::io::ErrorKind::TimedOut => Ok(String::new()),
-                Err(e) => Err(Error::String(format!("Failed to read data: {}", e))),
+                Err(e) => return Err(Error::String(format!("Failed to read data: {}", e))),
             }
-        })
+        }
+    }
+
+    /// The cancel flag for one-shot reads on `path`
+    ///
+    /// Kept outside the port map so `cancel_operation` can reach it while a read
+    /// holds the map lock.
+    fn cancel_flag(&self, path: &str) -> Result<Arc<AtomicBool>, Error> {
+        let mut cancel_flags = self
+            .cancel_flags
+            .lock()
+            .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))?;
+        Ok(cancel_flags
+            .entry(port_key(path))
+            .or_insert_with(|| Arc::new(AtomicBool::new(false)))
+            .clone())
+    }
+
+    /// Whether `deadline` has passed; never without one
+    fn passed(deadline: Option<Instant>) -> bool {
+        deadline.is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Make an in-flight `read`, `drain` or `write_binary_paced` on `path`
+    /// return `Error::Cancelled`
+    pub fn cancel_operation(&self, path: String) -> Result<(), Error> {
+        self.cancel_flag(&path)?.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// Send a request and read its response as one operation
+    ///
+    /// Stale input is discarded first so the tail of an earlier reply can't be
+    /// mistaken for the response. Reads until `read_size` bytes have arrived or
+    /// `response_timeout` milliseconds have passed, returning what was received.
+    /// Other reads and writes to the port wait until the response is in; the
+    /// exchange itself, including any wait for CTS, runs with the map
+    /// unlocked.
+    pub fn transaction(
+        &self,
+        path: String,
+        request: Vec<u8>,
+        read_size: usize,
+        response_timeout: u64,
+    ) -> Result<Vec<u8>, Error> {
+        let cancel = self.cancel_flag(&path)?;
+        cancel.store(false, Ordering::Relaxed);
+        let path = port_key(&path);
+
+        self.with_port_io(
+            &path,
+            |port_info| vec![port_info.write_lock.clone(), port_info.read_lock.clone()],
+            |serialport_info| {
+                serialport_info.holdback.clear();
+                serialport_info
+                    .serialport
+                    .clear(serialport::ClearBuffer::Input)
+                    .map_err(|e| Error::String(format!("Failed to clear input buffer: {}", e)))?;
+                WriteHandle::new(serialport_info)
+            },
+            |mut handle| {
+                self.write_all_bytes(&path, &mut handle, &request)
+                    .map_err(|e| Error::String(format!("Failed to write request: {}", e)))?;
+
+                let previous_timeout = handle.port.timeout();
+                let deadline = Instant::now() + Duration::from_millis(response_timeout);
+                let mut response = vec![0; read_size];
+                let mut received = 0;
+                let mut result = Ok(());
+                while received < read_size {
+                    match Self::read_cancellable(
+                        handle.port.as_mut(),
+                        &mut response[received..],
+                        Some(deadline),
+                        &cancel,
+                    ) {
+                        Ok(0) => break,
+                        Ok(n) => {
+                            handle.stats.record_read(n);
+                            Self::record_capture(
+                                &handle.capture,
+                                CaptureDirection::Rx,
+                                &response[received..received + n],
+                            );
+                            received += n;
+                        }
+                        Err(e) => {
+                            if !matches!(e, Error::Cancelled(_)) {
+                                handle.stats.record_read_error();
+                            }
+                            result = Err(e);
+                            break;
+                        }
+                    }
+                }
+
+                // On Windows all handles of a port share their timeouts
+                handle
+                    .port
+                    .set_timeout(previous_timeout)
+                    .map_err(|e| Error::String(format!("Failed to restore timeout: {}", e)))?;
+
+                result.map(|_| {
+                    response.truncate(received);
+                    response
+                })
+            },
+        )
+    }
+
+    /// Inspect up to `size` bytes of pending input without consuming them
+    ///
+    /// Since the OS offers no peek, bytes are read from the device into a
+    /// per-port holdback buffer and a copy is returned. The next `read` drains
+    /// the holdback buffer before reading anything new from the device.
+    ///
+    /// While a listener started with `buffer_reads` feeds `read`, the front of
+    /// its queue is returned instead, without waiting for more data.
+    pub fn peek(&self, path: String, size: usize) -> Result<Vec<u8>, Error> {
+        // Bounded by the read timeout, so it runs under the map lock, but not
+        // in the middle of a read
+        self.with_port_io(
+            &path,
+            |port_info| vec![port_info.read_lock.clone()],
+            |serialport_info| {
+                let missing = size.saturating_sub(serialport_info.holdback.len());
+                // `read` drains the holdback buffer first, then the queue
+                if let Some(queue) = &serialport_info.listen_queue {
+                    let queued = queue
+                        .data
+                        .lock()
+                        .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))?;
+                    let n = size.min(serialport_info.holdback.len());
+                    let mut data = serialport_info.holdback[..n].to_vec();
+                    data.extend(queued.iter().take(missing));
+                    return Ok(data);
+                }
+
+                if missing > 0 {
+                    let previous_timeout = serialport_info.serialport.timeout();
+                    if let Some(read_timeout) = serialport_info.read_timeout {
+                        serialport_info.serialport.set_timeout(read_timeout)?;
+                    }
+
+                    let mut buffer = vec![0; missing];
+                    let result = match serialport_info.serialport.read(&mut buffer) {
+                        Ok(n) => {
+                            serialport_info.stats.record_read(n);
+                            Self::record_capture(
+                                &serialport_info.capture,
+                                CaptureDirection::Rx,
+                                &buffer[..n],
+                            );
+                            serialport_info.holdback.extend_from_slice(&buffer[..n]);
+                            Ok(())
+                        }
+                        Err(e) if e.kind() == std::io::ErrorKind::TimedOut => Ok(()),
+                        Err(e) => {
+                            serialport_info.stats.record_read_error();
+                            Err(Error::String(format!("Failed to peek data: {}", e)))
+                        }
+                    };
+
+                    if serialport_info.read_timeout.is_some() {
+                        serialport_info.serialport.set_timeout(previous_timeout)?;
+                    }
+                    result?;
+                }
+
+                let n = size.min(serialport_info.holdback.len());
+                Ok(serialport_info.holdback[..n].to_vec())
+            },
+            Ok,
+        )
     }
 
     /// Write data to the serial port
+    ///
+    /// Keeps writing until every byte has been sent, so a short write on a slow
+    /// or flow-controlled link is never reported as success.
+    ///
+    /// If the device turns out to be gone the port is closed, see `write_binary`.
     pub fn write(&self, path: String, value: String) -> Result<usize, Error> {
-        self.get_serialport(path.clone(), |serialport_info| {
-            serialport_info
-                .serialport
-                .write(value.as_bytes())
-                .map_err(|e| Error::String(format!("Failed to write data: {}", e)))
-        })
+        self.write_managed(&path, value.as_bytes())
+            .map_err(|e| match e {
+                Error::Disconnected(_) => e,
+                e => Error::String(format!("Failed to write data: {}", e)),
+            })
     }
 
-    /// Write binary data to the serial port
+    /// Write binary data to the serial port, looping until all bytes are sent
+    ///
+    /// If the write fails because the device is gone, e.g. unplugged, the port
+    /// is closed and `plugin-serialplugin-disconnected-*` emitted as when the
+    /// listener loses it, and `Error::Disconnected` returned.
     pub fn write_binary(&self, path: String, value: Vec<u8>) -> Result<usize, Error> {
-        self.get_serialport(path.clone(), |serialport_info| {
-            serialport_info
-                .serialport
-                .write(&value)
-                .map_err(|e| Error::String(format!("Failed to write binary data: {}", e)))
+        self.write_managed(&path, &value).map_err(|e| match e {
+            Error::Disconnected(_) => e,
+            e => Error::String(format!("Failed to write binary data: {}", e)),
         })
     }
 
-    /// Set the baud rate
-    pub fn set_baud_rate(&self, path: String, baud_rate: u32) -> Result<(), Error> {
+    /// Write binary data without letting the OS output buffer fill past
+    /// `max_pending` bytes
+    ///
+    /// The data goes out in chunks sized to the room left below the threshold,
+    /// with `bytes_to_write` polled in between while the port is unlocked, so a
+    /// large transfer never sits in one long blocking write and
+    /// `cancel_operation` stops it between chunks with `Error::Cancelled`.
+    /// Drivers that don't report their output queue get chunks of
+    /// `max_pending` bytes.
+    pub fn write_binary_paced(
+        &self,
+        path: String,
+        value: Vec<u8>,
+        max_pending: usize,
+    ) -> Result<usize, Error> {
+        if max_pending == 0 {
+            return Err(Error::InvalidInput(
+                "max_pending must be at least 1 byte".to_string(),
+            ));
+        }
+        let cancel = self.cancel_flag(&path)?;
+        cancel.store(false, Ordering::Relaxed);
+
+        let mut written = 0;
+        while written < value.len() {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(Error::Cancelled(format!(
+                    "Write was cancelled after {} of {} bytes",
+                    written,
+                    value.len()
+                )));
+            }
+            let pending = self.get_serialport(path.clone(), |port_info| {
+                Ok(port_info.serialport.bytes_to_write()? as usize)
+            })?;
+            if pending >= max_pending {
+                thread::sleep(DRAIN_POLL_INTERVAL);
+                continue;
+            }
+            let end = value.len().min(written + max_pending - pending);
+            written += self.write_binary(path.clone(), value[written..end].to_vec())?;
+        }
+        Ok(written)
+    }
+
+    /// Write to a managed port, closing it if the device turns out to be gone
+    ///
+    /// Left in the map, a vanished port would fail every later call with the
+    /// same write error instead of reporting that it's no longer open. The
+    /// data goes out on a clone of the handle with the map unlocked, so a slow
+    /// write only holds up other writes to the same port.
+    fn write_managed(&self, path: &str, data: &[u8]) -> Result<usize, Error> {
+        let path = &port_key(path);
+        let mut write_lock = None;
+        let mut log_level = None;
+        let result = self.with_port_io(
+            path,
+            |port_info| vec![port_info.write_lock.clone()],
+            |port_info| {
+                write_lock = Some(port_info.write_lock.clone());
+                log_level = Some(port_info.log_level.clone());
+                WriteHandle::new(port_info)
+            },
+            |mut handle| self.write_all_bytes(path, &mut handle, data),
+        );
+
+        if let Err(Error::Disconnected(reason)) = &result {
+            let log_level = log_level.unwrap_or_default();
+            log_warn!(
+                port = &log_level,
+                "Serial port {} disconnected during a write: {}",
+                path,
+                reason
+            );
+            let mut serialports = lock_ports(&self.serialports);
+            // Unless it was closed, or closed and opened again, meanwhile
+            let same_port = serialports
+                .get(path)
+                .zip(write_lock.as_ref())
+                .is_some_and(|(port_info, lock)| Arc::ptr_eq(&port_info.write_lock, lock));
+            if !same_port {
+                return result;
+            }
+            if let Some(port_info) = serialports.remove(path) {
+                if let Err(e) = Self::shut_down(port_info) {
+                    log_error!(port = &log_level, "Error closing disconnected port: {}", e);
+                }
+            }
+            drop(serialports);
+
+            let disconnected_event =
+                format!("plugin-serialplugin-disconnected-{}", event_path(path));
+            if let Err(e) = self.app.emit(
+                &disconnected_event,
+                format!("Serial port {} disconnected!", path),
+            ) {
+                log_error!(
+                    port = &log_level,
+                    "Failed to send disconnection event: {}",
+                    e
+                );
+            }
+        }
+        result
+    }
+
+    /// SLIP-encode `payload` as one frame and write it, returning the encoded length
+    pub fn write_slip(&self, path: String, payload: Vec<u8>) -> Result<usize, Error> {
+        self.write_binary(path, framing::slip_encode(&payload))
+    }
+
+    /// Write bytes given as hex, e.g. `"01 03 00 00 00 0A"` or `"01:03:00"`
+    pub fn write_hex(&self, path: String, hex: String) -> Result<usize, Error> {
+        self.write_binary(path, crate::hex::parse_hex(&hex)?)
+    }
+
+    /// Write several messages in order, pausing `gap_ms` milliseconds between
+    /// them, and return the total number of bytes written
+    pub fn write_sequence(
+        &self,
+        path: String,
+        messages: Vec<Vec<u8>>,
+        gap_ms: Option<u64>,
+    ) -> Result<usize, Error> {
+        let gap = Duration::from_millis(gap_ms.unwrap_or(0));
+        let mut written = 0;
+        for (index, message) in messages.into_iter().enumerate() {
+            if index > 0 && !gap.is_zero() {
+                thread::sleep(gap);
+            }
+            written += self.write_binary(path.clone(), message)?;
+        }
+        Ok(written)
+    }
+
+    /// Write the TX records of a capture file back out of a port, returning the
+    /// number of bytes written
+    ///
+    /// With `respect_timing` the gaps between the original writes are kept;
+    /// otherwise the records are sent back to back. `file_path` is resolved
+    /// like for `start_capture`.
+    pub fn replay_capture(
+        &self,
+        path: String,
+        file_path: String,
+        respect_timing: bool,
+    ) -> Result<usize, Error> {
+        let mut written = 0;
+        let mut previous: Option<u64> = None;
+        let file_path = self.capture_path(&file_path)?;
+        for record in crate::state::read_capture(&file_path)?
+            .into_iter()
+            .filter(|record| record.direction == CaptureDirection::Tx)
+        {
+            if let (true, Some(previous)) = (respect_timing, previous) {
+                thread::sleep(Duration::from_millis(
+                    record.timestamp.saturating_sub(previous),
+                ));
+            }
+            previous = Some(record.timestamp);
+            written += self.write_binary(path.clone(), record.data)?;
+        }
+        Ok(written)
+    }
+
+    /// Enable or disable RS485 half-duplex mode
+    ///
+    /// While enabled, every write raises RTS before sending and releases it once
+    /// the output buffer has drained. On Linux the driver is asked to do this
+    /// with `TIOCSRS485`, timed by the UART; drivers without RS485 support,
+    /// delays over 100 ms and other platforms fall back to toggling RTS around
+    /// each write. Pass `None` to disable.
+    pub fn set_rs485(&self, path: String, config: Option<Rs485Config>) -> Result<(), Error> {
         self.get_serialport(path, |port_info| {
+            let driver_config = config.filter(rs485::fits_driver);
+            if driver_config.is_some() || port_info.rs485_driver {
+                port_info.rs485_driver =
+                    rs485::set(port_info.raw_handle, driver_config.as_ref())
+                        .map_err(|e| Error::String(format!("Failed to set RS485 mode: {}", e)))?;
+            }
+            if let (Some(config), false) = (&config, port_info.rs485_driver) {
+                port_info
+                    .serialport
+                    .write_request_to_send(!config.rts_active_high)
+                    .map_err(|e| Error::String(format!("Failed to set RTS: {}", e)))?;
+            }
+            port_info.rs485 = config;
+            Ok(())
+        })
+    }
+
+    /// Set the baud rate, returning the rate the driver actually configured
+    ///
+    /// Non-standard rates such as 250000 for DMX512 are passed to the driver
+    /// as is (on Linux through `termios2`), so the result shows whether the
+    /// adapter accepted the rate exactly or rounded it.
+    pub fn set_baud_rate(&self, path: String, baud_rate: u32) -> Result<u32, Error> {
+        self.get_serialport(path.clone(), |port_info| {
+            let data_bits = port_info.serialport.data_bits()?.into();
+            let stop_bits = port_info.serialport.stop_bits()?.into();
+            validate_line_settings(baud_rate, data_bits, stop_bits)?;
             port_info
                 .serialport
                 .set_baud_rate(baud_rate)
-                .map_err(|e| Error::String(format!("Failed to set baud rate: {}", e)))
+                .map_err(|e| Error::String(format!("Failed to set baud rate: {}", e)))?;
+
+            let actual = port_info.serialport.baud_rate()?;
+            if actual != baud_rate {
+                log_warn!(
+                    port = &port_info.log_level,
+                    "Requested {} baud on {}, the driver set {}",
+                    baud_rate,
+                    path,
+                    actual
+                );
+            }
+            port_info.settings.baud_rate = actual;
+            Ok(actual)
         })
     }
 
     /// Set the data bits
     pub fn set_data_bits(&self, path: String, data_bits: DataBits) -> Result<(), Error> {
         self.get_serialport(path, |port_info| {
-            port_info
-                .serialport
-                .set_data_bits(data_bits.into())
-                .map_err(Error::from)
+            let baud_rate = port_info.serialport.baud_rate()?;
+            let stop_bits = port_info.serialport.stop_bits()?.into();
+            validate_line_settings(baud_rate, data_bits, stop_bits)?;
+            port_info.serialport.set_data_bits(data_bits.into())?;
+            port_info.settings.data_bits = data_bits;
+            Ok(())
         })
     }
 
     /// Set the flow control
     pub fn set_flow_control(&self, path: String, flow_control: FlowControl) -> Result<(), Error> {
         self.get_serialport(path, |port_info| {
-            port_info
-                .serialport
-                .set_flow_control(flow_control.into())
-                .map_err(Error::from)
+            port_info.serialport.set_flow_control(flow_control.into())?;
+            port_info.settings.flow_control = flow_control;
+            Ok(())
         })
     }
 
+    /// Choose the XON/XOFF bytes for software flow control
+    ///
+    /// The bytes are handed to the OS driver, through termios on Unix and the
+    /// DCB on Windows. Loopback and network ports only take the standard bytes.
+    pub fn set_flow_control_chars(&self, path: String, xon: u8, xoff: u8) -> Result<(), Error> {
+        if xon == xoff {
+            return Err(Error::InvalidInput(
+                "XON and XOFF must be different bytes".to_string(),
+            ));
+        }
+
+        self.get_serialport(path.clone(), |port_info| {
+            let chars = FlowControlChars { xon, xoff };
+            if let Err(e) = flow_chars::set(port_info.raw_handle, chars) {
+                log_warn!(
+                    port = &port_info.log_level,
+                    "Failed to set XON/XOFF bytes {:#04x}/{:#04x} on {}: {}",
+                    xon,
+                    xoff,
+                    path,
+                    e
+                );
+                return Err(e);
+            }
+            port_info.flow_control_chars = chars;
+            Ok(())
+        })
+    }
+
+    /// Get the XON/XOFF bytes chosen for software flow control
+    pub fn get_flow_control_chars(&self, path: String) -> Result<FlowControlChars, Error> {
+        self.get_serialport(path, |port_info| Ok(port_info.flow_control_chars))
+    }
+
     /// Set the parity
     pub fn set_parity(&self, path: String, parity: Parity) -> Result<(), Error> {
         self.get_serialport(path, |port_info| {
-            port_info
-                .serialport
-                .set_parity(parity.into())
-                .map_err(Error::from)
+            port_info.serialport.set_parity(parity.try_into()?)?;
+            port_info.settings.parity = parity;
+            Ok(())
+        })
+    }
+
+    /// Set the stop bits
+    pub fn set_stop_bits(&self, path: String, stop_bits: StopBits) -> Result<(), Error> {
+        self.get_serialport(path, |port_info| {
+            let baud_rate = port_info.serialport.baud_rate()?;
+            let data_bits = port_info.serialport.data_bits()?.into();
+            validate_line_settings(baud_rate, data_bits, stop_bits)?;
+            port_info.serialport.set_stop_bits(stop_bits.into())?;
+            port_info.settings.stop_bits = stop_bits;
+            Ok(())
+        })
+    }
+
+    /// Set the timeout
+    pub fn set_timeout(&self, path: String, timeout: Duration) -> Result<(), Error> {
+        self.get_serialport(path, |port_info| {
+            port_info.serialport.set_timeout(timeout)?;
+            port_info.settings.timeout = timeout.as_millis() as u64;
+            Ok(())
+        })
+    }
+
+    /// Set the timeout used by reads, leaving the port timeout for everything else
+    pub fn set_read_timeout(&self, path: String, timeout: Duration) -> Result<(), Error> {
+        self.get_serialport(path, |port_info| {
+            port_info.read_timeout = Some(timeout);
+            Ok(())
+        })
+    }
+
+    /// Set the timeout used by writes, leaving the port timeout for everything else
+    pub fn set_write_timeout(&self, path: String, timeout: Duration) -> Result<(), Error> {
+        self.get_serialport(path, |port_info| {
+            port_info.write_timeout = Some(timeout);
+            Ok(())
+        })
+    }
+
+    /// Hold writes until CTS is asserted, a software RTS/CTS handshake for
+    /// adapters whose hardware flow control is unreliable
+    ///
+    /// A write fails with `Error::Timeout` if CTS stays low for `timeout`
+    /// milliseconds (1000 by default).
+    pub fn set_wait_for_cts(
+        &self,
+        path: String,
+        enabled: bool,
+        timeout: Option<u64>,
+    ) -> Result<(), Error> {
+        self.get_serialport(path, |port_info| {
+            port_info.wait_for_cts =
+                enabled.then(|| Duration::from_millis(timeout.unwrap_or(1000)));
+            Ok(())
+        })
+    }
+
+    /// Send written data one byte at a time, pausing `delay_ms` milliseconds
+    /// between bytes; 0 goes back to writing whole buffers
+    pub fn set_inter_byte_delay(&self, path: String, delay_ms: u64) -> Result<(), Error> {
+        self.get_serialport(path, |port_info| {
+            port_info.inter_byte_delay = (delay_ms > 0).then(|| Duration::from_millis(delay_ms));
+            Ok(())
+        })
+    }
+
+    /// Use `level` instead of the global log level for messages about the
+    /// port, or go back to the global level with `None`
+    ///
+    /// The level is kept with the port, so closing it drops the override.
+    pub fn set_port_log_level(&self, path: String, level: Option<LogLevel>) -> Result<(), Error> {
+        self.get_serialport(path, |port_info| {
+            port_info.log_level.set(level);
+            Ok(())
         })
     }
 
-    /// Set the stop bits
-    pub fn set_stop_bits(&self, path: String, stop_bits: StopBits) -> Result<(), Error> {
+    /// Emit `plugin-serialplugin-write-complete-*` with the byte count after
+    /// each write to the port
+    pub fn set_write_events(&self, path: String, enabled: bool) -> Result<(), Error> {
         self.get_serialport(path, |port_info| {
-            port_info
-                .serialport
-                .set_stop_bits(stop_bits.into())
-                .map_err(Error::from)
+            port_info.write_events = enabled;
+            Ok(())
         })
     }
 
-    /// Set the timeout
-    pub fn set_timeout(&self, path: String, timeout: Duration) -> Result<(), Error> {
+    /// Split writes into flushed chunks of `chunk_size` bytes, emitting
+    /// `plugin-serialplugin-write-progress-*` after each; 0 writes whole
+    /// buffers again
+    pub fn set_write_chunk_size(&self, path: String, chunk_size: usize) -> Result<(), Error> {
         self.get_serialport(path, |port_info| {
-            port_info
-                .serialport
-                .set_timeout(timeout)
-                .map_err(Error::from)
+            port_info.write_chunk_size = (chunk_size > 0).then_some(chunk_size);
+            Ok(())
         })
     }
 
@@ -581,6 +3235,110 @@ impl<R: Runtime> SerialPort<R> {
         })
     }
 
+    /// Set DTR and then RTS under one lock, leaving a line unchanged when its
+    /// level is `None`
+    ///
+    /// Nothing else can touch the port between the two changes, which keeps
+    /// reset sequences like the ESP32 DTR/RTS dance predictable.
+    pub fn set_control_lines(
+        &self,
+        path: String,
+        dtr: Option<bool>,
+        rts: Option<bool>,
+    ) -> Result<(), Error> {
+        self.get_serialport(path, |port_info| {
+            if let Some(dtr) = dtr {
+                port_info.serialport.write_data_terminal_ready(dtr)?;
+            }
+            if let Some(rts) = rts {
+                port_info.serialport.write_request_to_send(rts)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Reset an Arduino-style board by pulsing DTR low, then high again
+    ///
+    /// Writes to the port wait for the pulse; other calls and ports carry on
+    /// meanwhile.
+    pub fn reset_via_dtr(&self, path: String, pulse_ms: u64) -> Result<(), Error> {
+        self.with_port_io(
+            &path,
+            |port_info| vec![port_info.write_lock.clone()],
+            |port_info| Ok(port_info.serialport.try_clone()?),
+            |mut port| {
+                port.write_data_terminal_ready(false)?;
+                thread::sleep(Duration::from_millis(pulse_ms));
+                port.write_data_terminal_ready(true).map_err(Error::from)
+            },
+        )
+    }
+
+    /// Reset an ESP32/ESP8266 into its serial bootloader
+    ///
+    /// This is esptool's classic reset for the usual auto-program circuit, where
+    /// RTS drives EN and DTR drives GPIO0 through transistors: hold the chip in
+    /// reset, release it with GPIO0 low so it boots into download mode, then
+    /// let GPIO0 go again. Like `reset_via_dtr`, it only holds up writes to
+    /// the port.
+    pub fn enter_esp_bootloader(&self, path: String) -> Result<(), Error> {
+        self.with_port_io(
+            &path,
+            |port_info| vec![port_info.write_lock.clone()],
+            |port_info| Ok(port_info.serialport.try_clone()?),
+            |mut port| {
+                port.write_data_terminal_ready(false)?;
+                port.write_request_to_send(true)?;
+                thread::sleep(ESP_RESET_PULSE);
+                port.write_data_terminal_ready(true)?;
+                port.write_request_to_send(false)?;
+                thread::sleep(ESP_BOOT_DELAY);
+                port.write_data_terminal_ready(false).map_err(Error::from)
+            },
+        )
+    }
+
+    /// Reset an ESP32/ESP8266 into its normal firmware by pulsing EN through RTS
+    pub fn reset_esp(&self, path: String) -> Result<(), Error> {
+        self.with_port_io(
+            &path,
+            |port_info| vec![port_info.write_lock.clone()],
+            |port_info| Ok(port_info.serialport.try_clone()?),
+            |mut port| {
+                port.write_data_terminal_ready(false)?;
+                port.write_request_to_send(true)?;
+                thread::sleep(ESP_RESET_PULSE);
+                port.write_request_to_send(false).map_err(Error::from)
+            },
+        )
+    }
+
+    /// Perform the "1200bps touch" that puts native-USB boards into their bootloader
+    ///
+    /// The port must not be managed by the plugin; it is opened at 1200 baud,
+    /// DTR is asserted then dropped, and the port is closed again.
+    pub fn trigger_bootloader_1200(&self, path: String) -> Result<(), Error> {
+        self.ensure_allowed(&path)?;
+
+        if lock_ports(&self.serialports).contains_key(&port_key(&path)) {
+            return Err(Error::String(format!(
+                "Serial port {} is open, close it before triggering the bootloader",
+                path
+            )));
+        }
+
+        let mut port = self
+            .opener()?
+            .open(&path, &PortSettings::new(1200))
+            .map_err(|e| Error::String(format!("Failed to open serial port: {}", e)))?;
+        port.write_data_terminal_ready(true)?;
+        thread::sleep(Duration::from_millis(50));
+        port.write_data_terminal_ready(false)?;
+        thread::sleep(Duration::from_millis(50));
+
+        Ok(())
+    }
+
     /// Read the CTS (Clear To Send) control signal state
     pub fn read_clear_to_send(&self, path: String) -> Result<bool, Error> {
         self.get_serialport(path, |port_info| {
@@ -621,6 +3379,13 @@ impl<R: Runtime> SerialPort<R> {
         })
     }
 
+    /// Read CTS, DSR, RI and CD together, under a single lock
+    pub fn get_modem_status(&self, path: String) -> Result<ModemStatus, Error> {
+        self.get_serialport(path, |port_info| {
+            Self::read_modem_status(port_info.serialport.as_mut()).map_err(Error::from)
+        })
+    }
+
     /// Get the number of bytes available to read
     pub fn bytes_to_read(&self, path: String) -> Result<u32, Error> {
         self.get_serialport(path, |port_info| {
@@ -635,13 +3400,51 @@ impl<R: Runtime> SerialPort<R> {
         })
     }
 
-    /// Clear input/output buffers
-    pub fn clear_buffer(&self, path: String, buffer_to_clear: ClearBuffer) -> Result<(), Error> {
+    /// Block until everything written to the port has been transmitted
+    ///
+    /// Unlike `clear_buffer`, nothing is discarded: this waits for the OS to
+    /// send its queue, with `tcdrain` on Unix (on Linux it returns once the
+    /// UART's transmitter is empty) and `FlushFileBuffers` on Windows. Drivers
+    /// that still report queued bytes afterwards are polled until they report
+    /// none. Use it before switching RS-485 direction, changing the baud rate
+    /// or closing, so the last bytes aren't cut off. `cancel_operation` ends
+    /// the wait.
+    pub fn drain(&self, path: String) -> Result<(), Error> {
+        let cancel = self.cancel_flag(&path)?;
+        cancel.store(false, Ordering::Relaxed);
+
+        // Waits on a clone of the handle, so only writes to this port are held up
+        self.with_port_io(
+            &path,
+            |port_info| vec![port_info.write_lock.clone()],
+            |port_info| Ok(port_info.serialport.try_clone()?),
+            |mut port| {
+                port.flush()
+                    .map_err(|e| Error::String(format!("Failed to drain output: {}", e)))?;
+                while port.bytes_to_write()? > 0 {
+                    if cancel.load(Ordering::Relaxed) {
+                        return Err(Error::Cancelled("Drain was cancelled".to_string()));
+                    }
+                    thread::sleep(DRAIN_POLL_INTERVAL);
+                }
+                Ok(())
+            },
+        )
+    }
+
+    /// Clear input/output buffers, returning how many bytes were discarded
+    pub fn clear_buffer(&self, path: String, buffer_to_clear: ClearBuffer) -> Result<u32, Error> {
         self.get_serialport(path, |port_info| {
-            port_info
-                .serialport
-                .clear(buffer_to_clear.into())
-                .map_err(Error::from)
+            let mut discarded = 0;
+            if matches!(buffer_to_clear, ClearBuffer::Input | ClearBuffer::All) {
+                discarded += port_info.serialport.bytes_to_read()?;
+            }
+            if matches!(buffer_to_clear, ClearBuffer::Output | ClearBuffer::All) {
+                discarded += port_info.serialport.bytes_to_write()?;
+            }
+
+            port_info.serialport.clear(buffer_to_clear.into())?;
+            Ok(discarded)
         })
     }
 
@@ -659,24 +3462,609 @@ impl<R: Runtime> SerialPort<R> {
         })
     }
 
+    /// Transmit a break signal for `duration_ms` milliseconds
+    ///
+    /// Writes to the port wait for the whole break, so none can slip into it;
+    /// other calls and ports carry on meanwhile.
+    pub fn send_break(&self, path: String, duration_ms: u64) -> Result<(), Error> {
+        self.with_port_io(
+            &path,
+            |port_info| vec![port_info.write_lock.clone()],
+            |port_info| Ok(port_info.serialport.try_clone()?),
+            |port| {
+                port.set_break()?;
+                thread::sleep(Duration::from_millis(duration_ms));
+                port.clear_break().map_err(Error::from)
+            },
+        )
+    }
+
+    /// Whether the plugin has the port at `path` open
+    pub fn is_open(&self, path: String) -> Result<bool, Error> {
+        let serialports = lock_ports(&self.serialports);
+        Ok(serialports.contains_key(&port_key(&path)))
+    }
+
+    /// Run `f` with the OS handle of a managed port, for ioctls the plugin
+    /// doesn't wrap
+    ///
+    /// The port stays locked while `f` runs, so no other plugin call uses it
+    /// in between. It fails for ports without a handle (loopback and network
+    /// ports) and while a listener is reading from the port; pause it with
+    /// `pause_listening(path, true)` first.
+    ///
+    /// # Safety
+    ///
+    /// The handle is only valid during `f`; it must not be kept, closed or
+    /// duplicated past it. `f` must not read or write data through it, and
+    /// line settings changed through it aren't known to the plugin, so
+    /// `reconnect` and `get_port_state` won't reflect them.
+    pub unsafe fn with_raw_handle<T>(
+        &self,
+        path: String,
+        f: impl FnOnce(RawPortHandle) -> T,
+    ) -> Result<T, Error> {
+        self.get_serialport(path.clone(), |port_info| {
+            let handle = match port_info.raw_handle {
+                Some(handle) => handle,
+                None => {
+                    return Err(Error::String(format!(
+                        "Serial port {} has no OS handle",
+                        path
+                    )))
+                }
+            };
+
+            // The listener reads through a clone of the handle, which would
+            // race with whatever `f` does to the device
+            let listen_state = ListenState::from_u8(port_info.listen_state.load(Ordering::Relaxed));
+            if port_info.thread_handle.is_some() && listen_state != ListenState::Paused {
+                return Err(Error::PortBusy(format!(
+                    "Serial port {} is being listened to; pause the listener first",
+                    path
+                )));
+            }
+
+            Ok(f(handle))
+        })
+    }
+
+    /// Paths of the ports the plugin has open
+    pub fn managed_ports(&self) -> Result<Vec<String>, Error> {
+        let serialports = lock_ports(&self.serialports);
+        let mut paths: Vec<String> = serialports.keys().cloned().collect();
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// The ports the plugin has open, with their settings and listener status
+    pub fn managed_ports_detailed(&self) -> Result<HashMap<String, ManagedPortInfo>, Error> {
+        // Listed before taking the port lock, as enumerating can be slow
+        let ports = Self::enumerate_ports(&self.opener);
+        let serialports = lock_ports(&self.serialports);
+
+        Ok(serialports
+            .iter()
+            .map(|(path, port_info)| {
+                let port_type = match Self::listed_port(&ports, path) {
+                    Some(port) => Self::get_port_info(port.port_type.clone())
+                        .remove("type")
+                        .unwrap_or_else(|| UNKNOWN.to_string()),
+                    None => UNKNOWN.to_string(),
+                };
+                let listen_state =
+                    ListenState::from_u8(port_info.listen_state.load(Ordering::Relaxed));
+                let info = ManagedPortInfo {
+                    settings: port_info.settings.clone(),
+                    exclusive: port_info.exclusive,
+                    listening: port_info.thread_handle.is_some(),
+                    paused: port_info.thread_handle.is_some()
+                        && listen_state != ListenState::Running,
+                    port_type,
+                };
+                (path.clone(), info)
+            })
+            .collect())
+    }
+
+    /// Whether the port is open, whether a listener runs for it and the line
+    /// settings it currently has
+    pub fn get_port_state(&self, path: String) -> Result<PortState, Error> {
+        let serialports = lock_ports(&self.serialports);
+        let port_info = match serialports.get(&port_key(&path)) {
+            Some(port_info) => port_info,
+            None => {
+                return Ok(PortState {
+                    open: false,
+                    listening: false,
+                    paused: false,
+                    config: None,
+                })
+            }
+        };
+
+        let port = &port_info.serialport;
+        let config = PortConfig {
+            baud_rate: port.baud_rate()?,
+            data_bits: port.data_bits()?.into(),
+            flow_control: port.flow_control()?.into(),
+            parity: port.parity()?.into(),
+            stop_bits: port.stop_bits()?.into(),
+            timeout: port.timeout().as_millis() as u64,
+        };
+        let listen_state = ListenState::from_u8(port_info.listen_state.load(Ordering::Relaxed));
+        Ok(PortState {
+            open: true,
+            listening: port_info.thread_handle.is_some(),
+            paused: port_info.thread_handle.is_some() && listen_state != ListenState::Running,
+            config: Some(config),
+        })
+    }
+
+    /// Baud rates and features the port at `path` is expected to support
+    ///
+    /// The port doesn't have to be open. Without probing the device this is a
+    /// best guess from the path and the USB vendor id; see `capabilities`.
+    pub fn get_port_capabilities(&self, path: String) -> Result<PortCapabilities, Error> {
+        let ports = Self::enumerate_ports(&self.opener);
+        let port_type = Self::listed_port(&ports, &path).map(|port| &port.port_type);
+        Ok(capabilities::for_port(&path, port_type))
+    }
+
+    /// The entry of `ports` for `path`, which may also be an alias such as a
+    /// /dev/serial/by-id link
+    fn listed_port<'a>(
+        ports: &'a [serialport::SerialPortInfo],
+        path: &str,
+    ) -> Option<&'a serialport::SerialPortInfo> {
+        if let Some(port) = ports.iter().find(|port| port.port_name == path) {
+            return Some(port);
+        }
+        let resolved = std::fs::canonicalize(path).ok()?;
+        ports
+            .iter()
+            .find(|port| std::fs::canonicalize(&port.port_name).ok().as_ref() == Some(&resolved))
+    }
+
+    /// The line settings of an open port as last applied, for saving as a
+    /// preset and opening with `open_with_config`
+    pub fn export_config(&self, path: String) -> Result<PortConfig, Error> {
+        self.get_serialport(path, |port_info| Ok(port_info.settings.clone()))
+    }
+
+    /// Traffic counters of a port since it was opened or last reset
+    pub fn get_stats(&self, path: String) -> Result<PortStatsSnapshot, Error> {
+        self.get_serialport(path, |serialport_info| Ok(serialport_info.stats.snapshot()))
+    }
+
+    /// Zero the traffic counters of a port
+    pub fn reset_stats(&self, path: String) -> Result<(), Error> {
+        self.get_serialport(path, |serialport_info| {
+            serialport_info.stats.reset();
+            Ok(())
+        })
+    }
+
+    /// Record all traffic on a port to `file_path`, replacing any running capture
+    ///
+    /// Covers writes, heartbeats, reads, peeks, transactions and the listener.
+    /// `file_path` is relative to the app's log directory, and must stay in it
+    /// or in one of `Config::capture_dirs`.
+    pub fn start_capture(
+        &self,
+        path: String,
+        file_path: String,
+        format: CaptureFormat,
+    ) -> Result<(), Error> {
+        let file_path = self.capture_path(&file_path)?;
+        self.get_serialport(path, |port_info| {
+            let capture = Capture::create(&file_path, format)
+                .map_err(|e| Error::String(format!("Failed to create capture file: {}", e)))?;
+            let mut slot = port_info
+                .capture
+                .lock()
+                .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))?;
+            *slot = Some(capture);
+            Ok(())
+        })
+    }
+
+    /// Stop capturing traffic on a port and close the file
+    pub fn stop_capture(&self, path: String) -> Result<(), Error> {
+        self.get_serialport(path, |port_info| {
+            let mut slot = port_info
+                .capture
+                .lock()
+                .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))?;
+            *slot = None;
+            Ok(())
+        })
+    }
+
+    /// Resolve a capture file path given by the frontend, which may only point
+    /// into the app's log directory or one of `Config::capture_dirs`
+    ///
+    /// Relative paths are taken relative to the log directory. Links and `..`
+    /// are resolved before the check, and the file itself may not be a link,
+    /// so a path can't be bent to reach any other file.
+    fn capture_path(&self, file_path: &str) -> Result<PathBuf, Error> {
+        let log_dir =
+            self.app.path().app_log_dir().map_err(|e| {
+                Error::String(format!("Failed to find the app log directory: {}", e))
+            })?;
+        let file_path = log_dir.join(file_path);
+        let (parent, name) = match (file_path.parent(), file_path.file_name()) {
+            (Some(parent), Some(name)) => (parent, name),
+            _ => {
+                return Err(Error::InvalidInput(format!(
+                    "{} is not a file path",
+                    file_path.display()
+                )))
+            }
+        };
+        if parent == log_dir {
+            std::fs::create_dir_all(&log_dir)?;
+        }
+
+        let parent = std::fs::canonicalize(parent)?;
+        let allowed = std::iter::once(log_dir)
+            .chain(self.config.capture_dirs.iter().flatten().map(PathBuf::from))
+            .filter_map(|dir| std::fs::canonicalize(dir).ok())
+            .any(|dir| parent.starts_with(dir));
+        let file_path = parent.join(name);
+        let is_link = std::fs::symlink_metadata(&file_path)
+            .is_ok_and(|metadata| metadata.file_type().is_symlink());
+        if !allowed || is_link {
+            return Err(Error::PermissionDenied(format!(
+                "Capture files can't be used at {}; use the app log directory or one of capture_dirs",
+                file_path.display()
+            )));
+        }
+        Ok(file_path)
+    }
+
+    /// Append traffic to the capture file, if one is open
+    ///
+    /// A failing file ends the capture instead of failing the I/O it records.
+    fn record_capture(capture: &Mutex<Option<Capture>>, direction: CaptureDirection, data: &[u8]) {
+        if data.is_empty() {
+            return;
+        }
+        if let Ok(mut slot) = capture.lock() {
+            if let Some(file) = slot.as_mut() {
+                if let Err(e) = file.record(direction, data) {
+                    log_error!("Failed to write capture, stopping it: {}", e);
+                    *slot = None;
+                }
+            }
+        }
+    }
+
+    /// Write the whole buffer, toggling RTS around it when RS485 mode is enabled
+    fn write_all_bytes(
+        &self,
+        path: &str,
+        handle: &mut WriteHandle,
+        data: &[u8],
+    ) -> Result<usize, Error> {
+        if let Some(cts_timeout) = handle.wait_for_cts {
+            Self::wait_for_cts(handle, cts_timeout)?;
+        }
+
+        let result = match handle.write_timeout {
+            Some(write_timeout) => {
+                // Restored even though the clone is dropped afterwards: on
+                // Windows all handles of a port share their timeouts
+                let previous_timeout = handle.port.timeout();
+                handle.port.set_timeout(write_timeout)?;
+                let result = self.write_all_rs485(path, handle, data);
+                handle.port.set_timeout(previous_timeout)?;
+                result
+            }
+            None => self.write_all_rs485(path, handle, data),
+        };
+
+        if let Ok(n) = result {
+            log_trace!(
+                port = &handle.log_level,
+                "Wrote {} bytes to {}: {:02X?}",
+                n,
+                path,
+                data
+            );
+            handle.stats.record_write(n);
+            Self::record_capture(&handle.capture, CaptureDirection::Tx, data);
+            if handle.write_events {
+                self.emit_write_progress("write-complete", path, handle, n, data.len());
+            }
+        }
+        result
+    }
+
+    /// Emit a `WriteProgress` event, `plugin-serialplugin-write-progress-*` or
+    /// `plugin-serialplugin-write-complete-*`
+    fn emit_write_progress(
+        &self,
+        kind: &str,
+        path: &str,
+        handle: &WriteHandle,
+        bytes_written: usize,
+        total: usize,
+    ) {
+        let event = format!("plugin-serialplugin-{}-{}", kind, event_path(path));
+        let progress = WriteProgress {
+            path: path.to_string(),
+            bytes_written,
+            total,
+        };
+        if let Err(e) = self.app.emit(&event, progress) {
+            log_error!(
+                port = &handle.log_level,
+                "Failed to emit {} event: {}",
+                kind,
+                e
+            );
+        }
+    }
+
+    /// Write the whole buffer, in flushed chunks with a
+    /// `plugin-serialplugin-write-progress-*` event after each when the port has
+    /// a chunk size
+    fn write_chunked(
+        &self,
+        path: &str,
+        handle: &mut WriteHandle,
+        data: &[u8],
+    ) -> std::io::Result<()> {
+        let chunk_size = match handle.write_chunk_size {
+            Some(chunk_size) => chunk_size,
+            None => return Self::write_paced(handle, data),
+        };
+
+        let mut written = 0;
+        for chunk in data.chunks(chunk_size) {
+            Self::write_paced(handle, chunk)?;
+            handle.port.flush()?;
+            written += chunk.len();
+            self.emit_write_progress("write-progress", path, handle, written, data.len());
+        }
+        Ok(())
+    }
+
+    /// Write the whole buffer, byte by byte when the port has an inter-byte delay
+    fn write_paced(handle: &mut WriteHandle, data: &[u8]) -> std::io::Result<()> {
+        let delay = match handle.inter_byte_delay {
+            Some(delay) => delay,
+            None => return handle.port.write_all(data),
+        };
+
+        for (index, byte) in data.iter().enumerate() {
+            if index > 0 {
+                thread::sleep(delay);
+            }
+            handle.port.write_all(std::slice::from_ref(byte))?;
+            // Wait for the byte to leave so the delay is measured on the wire
+            handle.port.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Poll CTS until the device asserts it
+    fn wait_for_cts(handle: &mut WriteHandle, timeout: Duration) -> Result<(), Error> {
+        let deadline = Instant::now() + timeout;
+        while !handle.port.read_clear_to_send()? {
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout {
+                    message: format!("CTS was not asserted within {} ms", timeout.as_millis()),
+                    elapsed_ms: timeout.as_millis() as u64,
+                });
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        Ok(())
+    }
+
+    /// Write the whole buffer, with the RTS handling of `write_all_bytes`
+    fn write_all_rs485(
+        &self,
+        path: &str,
+        handle: &mut WriteHandle,
+        data: &[u8],
+    ) -> Result<usize, Error> {
+        let rs485 = match handle.rs485 {
+            Some(rs485) if !handle.rs485_driver => rs485,
+            _ => {
+                self.write_chunked(path, handle, data)
+                    .map_err(Self::write_error)?;
+                return Ok(data.len());
+            }
+        };
+
+        handle.port.write_request_to_send(rs485.rts_active_high)?;
+        thread::sleep(Duration::from_millis(rs485.delay_before_tx));
+
+        let result = self
+            .write_chunked(path, handle, data)
+            .and_then(|_| handle.port.flush())
+            .map_err(Self::write_error)
+            // Wait for the driver to push everything onto the wire before releasing the bus
+            .and_then(|_| Self::wait_sent(handle, data.len()));
+
+        thread::sleep(Duration::from_millis(rs485.delay_after_tx));
+        handle.port.write_request_to_send(!rs485.rts_active_high)?;
+
+        result.map(|_| data.len())
+    }
+
+    /// Poll until the output queue is empty, failing with `Error::Timeout` once
+    /// `len` bytes would have been sent at the port's baud rate and the port
+    /// timeout has passed on top, so a driver that never drains can't hold
+    /// the RS485 bus forever
+    fn wait_sent(handle: &mut WriteHandle, len: usize) -> Result<(), Error> {
+        // At most 12 bits a character: start, 8 data, parity and 2 stop bits
+        let bits = len as u64 * 12;
+        let wire_time = Duration::from_micros(bits * 1_000_000 / handle.baud_rate.max(1) as u64);
+        let limit = wire_time + handle.port.timeout();
+        let deadline = Instant::now() + limit;
+        while handle.port.bytes_to_write()? > 0 {
+            if Instant::now() >= deadline {
+                return Err(Error::Timeout {
+                    message: format!("Output did not drain within {} ms", limit.as_millis()),
+                    elapsed_ms: limit.as_millis() as u64,
+                });
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        Ok(())
+    }
+
+    /// An `Error` for a failed write, `Error::Disconnected` if the device is gone
+    fn write_error(e: std::io::Error) -> Error {
+        if Self::is_disconnect_error(&e) {
+            Error::Disconnected(e.to_string())
+        } else {
+            Error::from(e)
+        }
+    }
+
+    /// Whether a failed I/O call means the device went away
+    fn is_disconnect_error(e: &std::io::Error) -> bool {
+        use std::io::ErrorKind;
+
+        if matches!(
+            e.kind(),
+            ErrorKind::BrokenPipe
+                | ErrorKind::NotConnected
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::NotFound
+        ) {
+            return true;
+        }
+        // ERROR_BAD_COMMAND, ERROR_GEN_FAILURE, ERROR_NO_SUCH_DEVICE and
+        // ERROR_DEVICE_NOT_CONNECTED, as USB-serial drivers report an unplug
+        #[cfg(windows)]
+        if matches!(e.raw_os_error(), Some(22 | 31 | 433 | 1167)) {
+            return true;
+        }
+        // `serialport` passes EIO and ENODEV on with only their description
+        #[cfg(unix)]
+        {
+            let message = e.to_string();
+            if message.contains("I/O error") || message.contains("No such device") {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Run a platform command and return its stdout, recording a failure instead of panicking
+    #[allow(dead_code)]
+    fn command_stdout(command: &mut std::process::Command, errors: &mut Vec<String>) -> String {
+        match command.output() {
+            Ok(output) => String::from_utf8_lossy(&output.stdout).to_string(),
+            Err(e) => {
+                errors.push(format!(
+                    "Failed to execute {:?}: {}",
+                    command.get_program(),
+                    e
+                ));
+                String::new()
+            }
+        }
+    }
+
+    /// Add the stable `/dev/serial/by-id/...` symlink of each port as a `by_id` field
+    #[cfg(target_os = "linux")]
+    fn add_by_id_paths(result_list: &mut HashMap<String, HashMap<String, String>>) {
+        let entries = match std::fs::read_dir("/dev/serial/by-id") {
+            Ok(entries) => entries,
+            // The directory only exists while a USB serial device is attached
+            Err(_) => return,
+        };
+
+        for entry in entries.flatten() {
+            let link = entry.path();
+            let target = match std::fs::canonicalize(&link) {
+                Ok(target) => target,
+                Err(_) => continue,
+            };
+            if let Some(port_info) = result_list.get_mut(&target.to_string_lossy().to_string()) {
+                port_info.insert("by_id".to_string(), link.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    /// Read all four modem status lines from a port
+    fn read_modem_status(
+        serialport: &mut dyn serialport::SerialPort,
+    ) -> Result<ModemStatus, serialport::Error> {
+        Ok(ModemStatus {
+            cts: serialport.read_clear_to_send()?,
+            dsr: serialport.read_data_set_ready()?,
+            ri: serialport.read_ring_indicator()?,
+            cd: serialport.read_carrier_detect()?,
+        })
+    }
+
     fn get_serialport<T, F: FnOnce(&mut SerialportInfo) -> Result<T, Error>>(
         &self,
         path: String,
         f: F,
     ) -> Result<T, Error> {
-        match self.serialports.lock() {
-            Ok(mut map) => match map.get_mut(&path) {
-                Some(serialport_info) => f(serialport_info),
-                None => Err(Error::String("Serial port not found".to_string())),
-            },
-            Err(error) => Err(Error::String(format!(
-                "Failed to acquire file lock! {}",
-                error
-            ))),
+        match lock_ports(&self.serialports).get_mut(&port_key(&path)) {
+            Some(serialport_info) => f(serialport_info),
+            None => Err(Error::String("Serial port not found".to_string())),
+        }
+    }
+
+    /// Run `f` on what `prepare` takes from the entry of `path`, with the map
+    /// unlocked, holding the port's locks picked by `io_locks` throughout
+    ///
+    /// Slow I/O runs like this on a clone of the handle, so other ports and
+    /// quick calls on this one aren't held up meanwhile. The I/O locks are
+    /// always taken before the map lock, never while holding it.
+    fn with_port_io<P, T>(
+        &self,
+        path: &str,
+        io_locks: fn(&SerialportInfo) -> Vec<Arc<Mutex<()>>>,
+        prepare: impl FnOnce(&mut SerialportInfo) -> Result<P, Error>,
+        f: impl FnOnce(P) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let key = port_key(path);
+        let locks = self.get_serialport(key.clone(), |port_info| Ok(io_locks(port_info)))?;
+        let _io: Vec<_> = locks
+            .iter()
+            .map(|lock| lock.lock().unwrap_or_else(|e| e.into_inner()))
+            .collect();
+
+        let prepared = match lock_ports(&self.serialports).get_mut(&key) {
+            // Not if it was closed, or closed and opened again, while waiting
+            Some(port_info)
+                if io_locks(port_info)
+                    .iter()
+                    .zip(&locks)
+                    .all(|(current, held)| Arc::ptr_eq(current, held)) =>
+            {
+                prepare(port_info)?
+            }
+            _ => return Err(Error::String("Serial port not found".to_string())),
+        };
+        f(prepared)
+    }
+
+    /// `get_port_info` of a listed port keyed by its path, with the remote
+    /// device's address and name for a Bluetooth port
+    fn listed_port_info(port: serialport::SerialPortInfo) -> (String, HashMap<String, String>) {
+        let is_bluetooth = matches!(port.port_type, serialport::SerialPortType::BluetoothPort);
+        let mut port_info = Self::get_port_info(port.port_type);
+        if is_bluetooth {
+            bluetooth::add_details(&port.port_name, &mut port_info);
         }
+        (port.port_name, port_info)
     }
 
-    fn get_port_info(&self, port: serialport::SerialPortType) -> HashMap<String, String> {
+    fn get_port_info(port: serialport::SerialPortType) -> HashMap<String, String> {
         let mut port_info: HashMap<String, String> = HashMap::new();
         port_info.insert("type".to_string(), UNKNOWN.to_string());
         port_info.insert("vid".to_string(), UNKNOWN.to_string());
@@ -717,3 +4105,299 @@ impl<R: Runtime> SerialPort<R> {
         port_info
     }
 }
+
+/// Where `read_binary` gets its data once the map is unlocked
+enum ReadSource {
+    /// Enough held-back bytes were there already
+    Held(Vec<u8>),
+    /// The listener's copy of the data
+    Queue {
+        queue: Arc<ListenQueue>,
+        deadline: Option<Instant>,
+        /// Held-back bytes that start the result
+        held: usize,
+    },
+    /// A clone of the handle
+    Device {
+        port: Box<dyn serialport::SerialPort>,
+        stats: Arc<PortStats>,
+        capture: Arc<Mutex<Option<Capture>>>,
+        deadline: Option<Instant>,
+        held: usize,
+    },
+}
+
+/// A clone of a port's handle with the options a write goes by, taken from
+/// its entry so the data can go out with the map unlocked
+struct WriteHandle {
+    port: Box<dyn serialport::SerialPort>,
+    wait_for_cts: Option<Duration>,
+    write_timeout: Option<Duration>,
+    rs485: Option<Rs485Config>,
+    rs485_driver: bool,
+    inter_byte_delay: Option<Duration>,
+    write_events: bool,
+    write_chunk_size: Option<usize>,
+    baud_rate: u32,
+    stats: Arc<PortStats>,
+    capture: Arc<Mutex<Option<Capture>>>,
+    log_level: Arc<PortLogLevel>,
+}
+
+impl WriteHandle {
+    fn new(port_info: &SerialportInfo) -> Result<Self, Error> {
+        Ok(Self {
+            port: port_info.serialport.try_clone()?,
+            wait_for_cts: port_info.wait_for_cts,
+            write_timeout: port_info.write_timeout,
+            rs485: port_info.rs485,
+            rs485_driver: port_info.rs485_driver,
+            inter_byte_delay: port_info.inter_byte_delay,
+            write_events: port_info.write_events,
+            write_chunk_size: port_info.write_chunk_size,
+            baud_rate: port_info.settings.baud_rate,
+            stats: port_info.stats.clone(),
+            capture: port_info.capture.clone(),
+            log_level: port_info.log_level.clone(),
+        })
+    }
+}
+
+/// Locks of the I/O running on clones of a port's handle, see `stop_io`
+type IoLocks = Vec<Arc<Mutex<()>>>;
+
+/// A frame decoder limited to `max_len` bytes per partial frame, if given
+fn frame_decoder(framing: Framing, max_len: Option<usize>) -> FrameDecoder {
+    match max_len {
+        Some(max_len) => FrameDecoder::with_max_len(framing, max_len),
+        None => FrameDecoder::new(framing),
+    }
+}
+
+/// Events per second `EmitStrategy::Coalesced` allows when no rate is given
+const DEFAULT_MAX_EMIT_RATE: u32 = 60;
+
+/// Turns the data a listener reads into read events as its `EmitStrategy` says
+struct ReadEmitter {
+    /// How long a batch collects data with `EmitStrategy::Batched`
+    window: Option<Duration>,
+    /// Quiet time that ends a batch with `EmitStrategy::Coalesced`
+    debounce: Option<Duration>,
+    /// Shortest time between two coalesced events
+    min_interval: Duration,
+    /// Batch size that triggers emitting before the window has passed
+    max_batch: Option<usize>,
+    batch: Vec<u8>,
+    batch_start: Instant,
+    last_data: Instant,
+    last_emit: Option<Instant>,
+    /// Emit `ReadText` instead of `ReadData`
+    decode: bool,
+    /// Start of a UTF-8 sequence cut off at the end of the last event,
+    /// completed by the next one
+    partial_char: Vec<u8>,
+}
+
+impl ReadEmitter {
+    fn new(strategy: Option<EmitStrategy>, max_buffer_size: Option<usize>, decode: bool) -> Self {
+        Self {
+            window: match strategy {
+                Some(EmitStrategy::Batched { window_ms }) => Some(Duration::from_millis(window_ms)),
+                _ => None,
+            },
+            debounce: match strategy {
+                Some(EmitStrategy::Coalesced { debounce_ms, .. }) => {
+                    Some(Duration::from_millis(debounce_ms))
+                }
+                _ => None,
+            },
+            min_interval: match strategy {
+                Some(EmitStrategy::Coalesced { max_rate, .. }) => {
+                    let max_rate = max_rate.unwrap_or(DEFAULT_MAX_EMIT_RATE).max(1);
+                    Duration::from_secs(1) / max_rate
+                }
+                _ => Duration::ZERO,
+            },
+            max_batch: max_buffer_size,
+            batch: Vec::new(),
+            batch_start: Instant::now(),
+            last_data: Instant::now(),
+            last_emit: None,
+            decode,
+            partial_char: Vec::new(),
+        }
+    }
+
+    /// Emit `data` as one read event, decoded to text if the listener was
+    /// started with `decode`
+    fn emit<R: Runtime>(
+        &mut self,
+        app: &AppHandle<R>,
+        event: &str,
+        data: &[u8],
+        base64: bool,
+        seq: &mut u64,
+    ) -> tauri::Result<()> {
+        if !self.decode {
+            return SerialPort::emit_read_data(app, event, data, base64, seq);
+        }
+
+        self.partial_char.extend_from_slice(data);
+        let complete = self.partial_char.len() - incomplete_utf8_tail(&self.partial_char);
+        if complete == 0 {
+            return Ok(());
+        }
+        let bytes: Vec<u8> = self.partial_char.drain(..complete).collect();
+        Self::emit_text(app, event, &bytes, seq)
+    }
+
+    /// Emit `bytes` as one `ReadText` event, decoded as lossy UTF-8
+    fn emit_text<R: Runtime>(
+        app: &AppHandle<R>,
+        event: &str,
+        bytes: &[u8],
+        seq: &mut u64,
+    ) -> tauri::Result<()> {
+        let current = *seq;
+        *seq += 1;
+        app.emit(
+            event,
+            ReadText {
+                data: String::from_utf8_lossy(bytes).into_owned(),
+                size: bytes.len(),
+                seq: current,
+                timestamp_ms: crate::state::unix_millis(),
+            },
+        )
+    }
+
+    /// Emit the batch and start a new one
+    fn emit_batch<R: Runtime>(
+        &mut self,
+        app: &AppHandle<R>,
+        event: &str,
+        base64: bool,
+        seq: &mut u64,
+    ) -> tauri::Result<()> {
+        let batch = std::mem::take(&mut self.batch);
+        self.last_emit = Some(Instant::now());
+        self.emit(app, event, &batch, base64, seq)
+    }
+
+    /// Emit `data` now or add it to the batch
+    fn push<R: Runtime>(
+        &mut self,
+        app: &AppHandle<R>,
+        event: &str,
+        data: &[u8],
+        base64: bool,
+        seq: &mut u64,
+    ) -> tauri::Result<()> {
+        if self.window.is_some() || self.debounce.is_some() {
+            if self.batch.is_empty() {
+                self.batch_start = Instant::now();
+            }
+            self.batch.extend_from_slice(data);
+            self.last_data = Instant::now();
+            // `max_buffer_size` wins over the rate limit, which only bounds latency
+            if self.max_batch.is_some_and(|max| self.batch.len() >= max) {
+                return self.emit_batch(app, event, base64, seq);
+            }
+            return Ok(());
+        }
+
+        self.emit(app, event, data, base64, seq)
+    }
+
+    /// Emit whatever the batch holds, due or not, and the start of a
+    /// character no later data will complete
+    fn flush<R: Runtime>(
+        &mut self,
+        app: &AppHandle<R>,
+        event: &str,
+        base64: bool,
+        seq: &mut u64,
+    ) -> tauri::Result<()> {
+        if !self.batch.is_empty() {
+            self.emit_batch(app, event, base64, seq)?;
+        }
+        // Only text is held back; undecoded events carry every byte as read
+        let tail = std::mem::take(&mut self.partial_char);
+        if tail.is_empty() {
+            return Ok(());
+        }
+        Self::emit_text(app, event, &tail, seq)
+    }
+
+    /// Time left until `flush_due` emits the batch, if one is collecting
+    fn until_due(&self) -> Option<Duration> {
+        if self.batch.is_empty() {
+            return None;
+        }
+
+        let due = match (self.window, self.debounce) {
+            (Some(window), _) => self.batch_start + window,
+            (None, Some(debounce)) => {
+                let quiet = (self.last_data + debounce).min(self.batch_start + self.min_interval);
+                match self.last_emit {
+                    Some(last) => quiet.max(last + self.min_interval),
+                    None => quiet,
+                }
+            }
+            (None, None) => return None,
+        };
+        Some(due.saturating_duration_since(Instant::now()))
+    }
+
+    /// Emit the batch once its window has passed, or once the line went quiet
+    /// and the rate limit allows another event
+    fn flush_due<R: Runtime>(
+        &mut self,
+        app: &AppHandle<R>,
+        event: &str,
+        base64: bool,
+        seq: &mut u64,
+    ) -> tauri::Result<()> {
+        if self.batch.is_empty() {
+            return Ok(());
+        }
+
+        let due = match (self.window, self.debounce) {
+            (Some(window), _) => self.batch_start.elapsed() >= window,
+            (None, Some(debounce)) => {
+                let allowed = self
+                    .last_emit
+                    .map_or(true, |last| last.elapsed() >= self.min_interval);
+                // Data that never stops is emitted at the maximum rate
+                allowed
+                    && (self.last_data.elapsed() >= debounce
+                        || self.batch_start.elapsed() >= self.min_interval)
+            }
+            (None, None) => false,
+        };
+        if due {
+            self.emit_batch(app, event, base64, seq)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Length of the UTF-8 sequence `data` ends in the middle of, 0 if it ends on
+/// a character boundary
+fn incomplete_utf8_tail(data: &[u8]) -> usize {
+    for back in 1..=data.len().min(3) {
+        let byte = data[data.len() - back];
+        // Continuation bytes are 10xxxxxx; anything else starts a character
+        if byte & 0xC0 != 0x80 {
+            let len = match byte {
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                0xF0..=0xF7 => 4,
+                _ => 1,
+            };
+            return if len > back { back } else { 0 };
+        }
+    }
+    0
+}
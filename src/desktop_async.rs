@@ -0,0 +1,57 @@
+//! Async variants of the blocking desktop API, enabled with the `tokio` feature
+//!
+//! Each call runs the blocking implementation on the async runtime's blocking
+//! pool, so a slow read no longer ties up one of the command threads.
+
+use crate::desktop_api::SerialPort;
+use crate::error::Error;
+use crate::state::OpenConfig;
+use tauri::Runtime;
+
+impl<R: Runtime> SerialPort<R> {
+    /// Run a blocking call on the blocking pool with a handle to the same ports
+    async fn run_blocking<T, F>(&self, f: F) -> Result<T, Error>
+    where
+        T: Send + 'static,
+        F: FnOnce(SerialPort<R>) -> Result<T, Error> + Send + 'static,
+    {
+        let serial = self.clone();
+        tauri::async_runtime::spawn_blocking(move || f(serial))
+            .await
+            .map_err(|e| Error::String(format!("Failed to run blocking task: {}", e)))?
+    }
+
+    /// Async version of `open`
+    pub async fn open_async(&self, path: String, config: OpenConfig) -> Result<String, Error> {
+        self.run_blocking(move |serial| serial.open(path, config))
+            .await
+    }
+
+    /// Async version of `close`
+    pub async fn close_async(&self, path: String) -> Result<(), Error> {
+        self.run_blocking(move |serial| serial.close(path)).await
+    }
+
+    /// Async version of `read`
+    pub async fn read_async(
+        &self,
+        path: String,
+        timeout: Option<u64>,
+        size: Option<usize>,
+    ) -> Result<String, Error> {
+        self.run_blocking(move |serial| serial.read(path, timeout, size))
+            .await
+    }
+
+    /// Async version of `write`
+    pub async fn write_async(&self, path: String, value: String) -> Result<usize, Error> {
+        self.run_blocking(move |serial| serial.write(path, value))
+            .await
+    }
+
+    /// Async version of `write_binary`
+    pub async fn write_binary_async(&self, path: String, value: Vec<u8>) -> Result<usize, Error> {
+        self.run_blocking(move |serial| serial.write_binary(path, value))
+            .await
+    }
+}
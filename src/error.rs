@@ -1,3 +1,4 @@
+use serde::ser::SerializeStruct;
 use serde::{Serialize, Serializer};
 use std::io;
 #[cfg(target_os = "android")]
@@ -12,6 +13,27 @@ pub enum Error {
     String(String),
     /// Serial port error
     SerialPort(String),
+    /// An operation did not complete in the time allowed, e.g. a read that got
+    /// no data; serialized as `{ kind: "timeout", message, elapsed_ms }` so
+    /// callers can tell it from a failure
+    Timeout { message: String, elapsed_ms: u64 },
+    /// The port is already open
+    PortBusy(String),
+    /// The operation was cancelled before it completed
+    Cancelled(String),
+    /// An argument could not be parsed or is out of range
+    InvalidInput(String),
+    /// The configuration doesn't allow the operation
+    PermissionDenied(String),
+    /// The port or the platform can't do the operation
+    Unsupported(String),
+    /// As many ports as `Config::max_open_ports` allows are already open
+    TooManyPorts(String),
+    /// The device went away, e.g. it was unplugged
+    Disconnected(String),
+    /// Fewer bytes than asked for arrived in time; serialized as
+    /// `{ kind: "partial_read", message, data, expected }`
+    PartialRead { data: Vec<u8>, expected: usize },
 }
 
 impl Clone for Error {
@@ -20,6 +42,24 @@ impl Clone for Error {
             Error::Io(s) => Error::Io(s.clone()),
             Error::String(s) => Error::String(s.clone()),
             Error::SerialPort(s) => Error::SerialPort(s.clone()),
+            Error::Timeout {
+                message,
+                elapsed_ms,
+            } => Error::Timeout {
+                message: message.clone(),
+                elapsed_ms: *elapsed_ms,
+            },
+            Error::PortBusy(s) => Error::PortBusy(s.clone()),
+            Error::Cancelled(s) => Error::Cancelled(s.clone()),
+            Error::InvalidInput(s) => Error::InvalidInput(s.clone()),
+            Error::PermissionDenied(s) => Error::PermissionDenied(s.clone()),
+            Error::Unsupported(s) => Error::Unsupported(s.clone()),
+            Error::TooManyPorts(s) => Error::TooManyPorts(s.clone()),
+            Error::Disconnected(s) => Error::Disconnected(s.clone()),
+            Error::PartialRead { data, expected } => Error::PartialRead {
+                data: data.clone(),
+                expected: *expected,
+            },
         }
     }
 }
@@ -30,6 +70,21 @@ impl std::fmt::Display for Error {
             Error::Io(err) => write!(f, "IO error: {}", err),
             Error::String(s) => write!(f, "{}", s),
             Error::SerialPort(err) => write!(f, "Serial port error: {}", err),
+            Error::Timeout { message, .. } => write!(f, "Timeout: {}", message),
+            Error::PortBusy(err) => write!(f, "Port busy: {}", err),
+            Error::Cancelled(err) => write!(f, "Cancelled: {}", err),
+            Error::InvalidInput(err) => write!(f, "Invalid input: {}", err),
+            Error::PermissionDenied(err) => write!(f, "Permission denied: {}", err),
+            Error::Unsupported(err) => write!(f, "Unsupported: {}", err),
+            Error::TooManyPorts(err) => write!(f, "Too many ports: {}", err),
+            Error::Disconnected(err) => write!(f, "Disconnected: {}", err),
+            Error::PartialRead { data, expected } => write!(
+                f,
+                "Partial read: got {} of {} bytes, {} missing",
+                data.len(),
+                expected,
+                expected - data.len()
+            ),
         }
     }
 }
@@ -58,6 +113,17 @@ impl From<Error> for io::Error {
             Error::Io(s) => io::Error::new(io::ErrorKind::Other, s),
             Error::String(s) => io::Error::new(io::ErrorKind::Other, s),
             Error::SerialPort(s) => io::Error::new(io::ErrorKind::Other, s),
+            Error::Timeout { message, .. } => io::Error::new(io::ErrorKind::TimedOut, message),
+            Error::PortBusy(s) => io::Error::new(io::ErrorKind::Other, s),
+            Error::Cancelled(s) => io::Error::new(io::ErrorKind::Interrupted, s),
+            Error::InvalidInput(s) => io::Error::new(io::ErrorKind::InvalidInput, s),
+            Error::PermissionDenied(s) => io::Error::new(io::ErrorKind::PermissionDenied, s),
+            Error::Unsupported(s) => io::Error::new(io::ErrorKind::Unsupported, s),
+            Error::TooManyPorts(s) => io::Error::new(io::ErrorKind::Other, s),
+            Error::Disconnected(s) => io::Error::new(io::ErrorKind::NotConnected, s),
+            e @ Error::PartialRead { .. } => {
+                io::Error::new(io::ErrorKind::UnexpectedEof, e.to_string())
+            }
         }
     }
 }
@@ -67,7 +133,24 @@ impl Serialize for Error {
     where
         S: Serializer,
     {
-        serializer.serialize_str(self.to_string().as_ref())
+        match self {
+            Error::Timeout { elapsed_ms, .. } => {
+                let mut error = serializer.serialize_struct("Error", 3)?;
+                error.serialize_field("kind", "timeout")?;
+                error.serialize_field("message", &self.to_string())?;
+                error.serialize_field("elapsed_ms", elapsed_ms)?;
+                error.end()
+            }
+            Error::PartialRead { data, expected } => {
+                let mut error = serializer.serialize_struct("Error", 4)?;
+                error.serialize_field("kind", "partial_read")?;
+                error.serialize_field("message", &self.to_string())?;
+                error.serialize_field("data", data)?;
+                error.serialize_field("expected", expected)?;
+                error.end()
+            }
+            _ => serializer.serialize_str(self.to_string().as_ref()),
+        }
     }
 }
 
@@ -0,0 +1,96 @@
+//! The XON/XOFF bytes the serial driver uses for software flow control
+//!
+//! On Unix they are the `VSTART`/`VSTOP` control characters of the port's
+//! termios, on Windows the `XonChar`/`XoffChar` of its DCB. Ports without an
+//! OS handle (loopback and network ports) only know the standard bytes.
+
+use crate::error::Error;
+use crate::state::{FlowControlChars, RawPortHandle};
+
+/// Have the driver of the port with `handle` use `chars` for XON/XOFF
+#[cfg(any(unix, windows))]
+pub fn set(handle: Option<RawPortHandle>, chars: FlowControlChars) -> Result<(), Error> {
+    match handle {
+        Some(handle) => set_chars(handle, chars),
+        None if chars.is_standard() => Ok(()),
+        None => Err(Error::Unsupported(
+            "Custom XON/XOFF bytes need a port with an OS handle".to_string(),
+        )),
+    }
+}
+
+#[cfg(unix)]
+fn set_chars(handle: RawPortHandle, chars: FlowControlChars) -> Result<(), Error> {
+    use std::io;
+
+    // SAFETY: an all-zero termios is valid, and tcgetattr fills it in
+    let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+    if unsafe { libc::tcgetattr(handle.fd, &mut termios) } < 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    termios.c_cc[libc::VSTART] = chars.xon as libc::cc_t;
+    termios.c_cc[libc::VSTOP] = chars.xoff as libc::cc_t;
+    if unsafe { libc::tcsetattr(handle.fd, libc::TCSANOW, &termios) } < 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn set_chars(handle: RawPortHandle, chars: FlowControlChars) -> Result<(), Error> {
+    use std::io;
+    use std::os::windows::io::RawHandle;
+
+    /// `DCB` from `<winbase.h>`
+    #[repr(C)]
+    #[derive(Default)]
+    struct Dcb {
+        dcb_length: u32,
+        baud_rate: u32,
+        flags: u32,
+        reserved: u16,
+        xon_lim: u16,
+        xoff_lim: u16,
+        byte_size: u8,
+        parity: u8,
+        stop_bits: u8,
+        xon_char: u8,
+        xoff_char: u8,
+        error_char: u8,
+        eof_char: u8,
+        evt_char: u8,
+        reserved1: u16,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCommState(file: RawHandle, dcb: *mut Dcb) -> i32;
+        fn SetCommState(file: RawHandle, dcb: *const Dcb) -> i32;
+    }
+
+    let mut dcb = Dcb {
+        dcb_length: std::mem::size_of::<Dcb>() as u32,
+        ..Dcb::default()
+    };
+    // SAFETY: `dcb` matches the layout the API reads and writes
+    if unsafe { GetCommState(handle.handle, &mut dcb) } == 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    dcb.xon_char = chars.xon;
+    dcb.xoff_char = chars.xoff;
+    if unsafe { SetCommState(handle.handle, &dcb) } == 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+    Ok(())
+}
+
+/// Other platforms have no driver setting for the bytes
+#[cfg(not(any(unix, windows)))]
+pub fn set(_handle: Option<RawPortHandle>, chars: FlowControlChars) -> Result<(), Error> {
+    if chars.is_standard() {
+        return Ok(());
+    }
+    Err(Error::Unsupported(
+        "Custom XON/XOFF bytes are not supported on this platform".to_string(),
+    ))
+}
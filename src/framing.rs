@@ -0,0 +1,298 @@
+//! Splitting the listener's byte stream into protocol frames
+
+use crate::state::Framing;
+
+/// Default longest partial frame kept while waiting for its end; anything
+/// longer is noise or a lost delimiter, so it is dropped
+const MAX_FRAME_LEN: usize = 64 * 1024;
+
+/// SLIP frame delimiter
+const SLIP_END: u8 = 0xC0;
+/// SLIP escape byte
+const SLIP_ESC: u8 = 0xDB;
+/// Escaped `SLIP_END`
+const SLIP_ESC_END: u8 = 0xDC;
+/// Escaped `SLIP_ESC`
+const SLIP_ESC_ESC: u8 = 0xDD;
+
+/// NMEA 0183 checksum: the XOR of every byte between the leading `$`/`!` and
+/// the `*`
+///
+/// Both markers are optional, so a bare sentence body works as well as a full
+/// `$GPGGA,...*47` sentence.
+pub fn nmea_checksum(sentence: &str) -> u8 {
+    let body = sentence
+        .strip_prefix('$')
+        .or_else(|| sentence.strip_prefix('!'))
+        .unwrap_or(sentence);
+    let body = match body.find('*') {
+        Some(end) => &body[..end],
+        None => body,
+    };
+    body.bytes().fold(0, |checksum, byte| checksum ^ byte)
+}
+
+/// SLIP-encode (RFC 1055) a payload as one frame
+///
+/// The frame is delimited by `END` on both sides, so line noise received
+/// before it is flushed as a separate (discarded) frame by the receiver.
+pub fn slip_encode(payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 2);
+    frame.push(SLIP_END);
+    for &byte in payload {
+        match byte {
+            SLIP_END => frame.extend_from_slice(&[SLIP_ESC, SLIP_ESC_END]),
+            SLIP_ESC => frame.extend_from_slice(&[SLIP_ESC, SLIP_ESC_ESC]),
+            _ => frame.push(byte),
+        }
+    }
+    frame.push(SLIP_END);
+    frame
+}
+
+/// Whether `line` is a complete NMEA sentence whose `*XX` checksum matches
+fn nmea_valid(line: &[u8]) -> bool {
+    let line = match std::str::from_utf8(line) {
+        Ok(line) => line,
+        Err(_) => return false,
+    };
+    if !(line.starts_with('$') || line.starts_with('!')) {
+        return false;
+    }
+    let expected = match line.rfind('*') {
+        Some(star) if line.len() == star + 3 => &line[star + 1..],
+        _ => return false,
+    };
+    match u8::from_str_radix(expected, 16) {
+        Ok(expected) => expected == nmea_checksum(line),
+        Err(_) => false,
+    }
+}
+
+/// Accumulates received bytes and hands out each complete frame
+///
+/// This is what the listener uses for its `framing` option; it is public so
+/// data from `SerialPort::subscribe` can be framed the same way.
+pub struct FrameDecoder {
+    framing: Framing,
+    buffer: Vec<u8>,
+    max_len: usize,
+    /// Bytes dropped for going over `max_len` since the last `take_dropped`
+    dropped: usize,
+    /// SLIP: the previous byte was `ESC`
+    escaped: bool,
+    /// SLIP: the current frame had an invalid escape and is dropped at its end
+    invalid: bool,
+}
+
+impl FrameDecoder {
+    pub fn new(framing: Framing) -> Self {
+        Self::with_max_len(framing, MAX_FRAME_LEN)
+    }
+
+    /// A decoder dropping partial frames longer than `max_len` bytes
+    pub fn with_max_len(framing: Framing, max_len: usize) -> Self {
+        Self {
+            framing,
+            buffer: Vec::new(),
+            max_len: max_len.max(1),
+            dropped: 0,
+            escaped: false,
+            invalid: false,
+        }
+    }
+
+    /// Number of bytes dropped for lack of a frame end since the last call
+    pub fn take_dropped(&mut self) -> usize {
+        std::mem::take(&mut self.dropped)
+    }
+
+    /// The framing this decoder splits by
+    pub fn framing(&self) -> Framing {
+        self.framing
+    }
+
+    /// Add received bytes, returning the frames they completed
+    pub fn push(&mut self, data: &[u8]) -> Vec<Vec<u8>> {
+        let mut frames = Vec::new();
+        for &byte in data {
+            match self.framing {
+                Framing::Nmea => self.push_nmea(byte, &mut frames),
+                Framing::Slip => self.push_slip(byte, &mut frames),
+                Framing::Json => self.push_json(byte, &mut frames),
+            }
+        }
+        frames
+    }
+
+    /// Sentences end in `\r\n`; a bare `\n` is accepted too. Sentences with a
+    /// bad checksum are dropped.
+    fn push_nmea(&mut self, byte: u8, frames: &mut Vec<Vec<u8>>) {
+        if byte != b'\n' {
+            self.push_byte(byte);
+            return;
+        }
+
+        let mut line = std::mem::take(&mut self.buffer);
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        if nmea_valid(&line) {
+            frames.push(line);
+        } else if !line.is_empty() {
+            log_debug!(
+                "Dropping invalid NMEA sentence: {}",
+                String::from_utf8_lossy(&line)
+            );
+        }
+    }
+
+    /// Lines end in `\n`, optionally preceded by `\r`; blank lines are
+    /// skipped. Parsing is left to the caller.
+    fn push_json(&mut self, byte: u8, frames: &mut Vec<Vec<u8>>) {
+        if byte != b'\n' {
+            self.push_byte(byte);
+            return;
+        }
+
+        let mut line = std::mem::take(&mut self.buffer);
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        if !line.iter().all(u8::is_ascii_whitespace) {
+            frames.push(line);
+        }
+    }
+
+    /// Frames end at `END`; empty frames (back-to-back `END`s) are skipped and
+    /// frames with an invalid escape sequence are dropped.
+    fn push_slip(&mut self, byte: u8, frames: &mut Vec<Vec<u8>>) {
+        if byte == SLIP_END {
+            let frame = std::mem::take(&mut self.buffer);
+            if self.invalid || self.escaped {
+                log_debug!("Dropping SLIP frame with an invalid escape");
+            } else if !frame.is_empty() {
+                frames.push(frame);
+            }
+            self.escaped = false;
+            self.invalid = false;
+            return;
+        }
+
+        if self.escaped {
+            self.escaped = false;
+            match byte {
+                SLIP_ESC_END => self.push_byte(SLIP_END),
+                SLIP_ESC_ESC => self.push_byte(SLIP_ESC),
+                _ => self.invalid = true,
+            }
+        } else if byte == SLIP_ESC {
+            self.escaped = true;
+        } else {
+            self.push_byte(byte);
+        }
+    }
+
+    fn push_byte(&mut self, byte: u8) {
+        if self.buffer.len() >= self.max_len {
+            log_warn!("Dropping {} bytes without a frame end", self.buffer.len());
+            self.dropped += self.buffer.len();
+            self.buffer.clear();
+        }
+        self.buffer.push(byte);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GGA: &[u8] = b"$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47";
+
+    #[test]
+    fn nmea_checksum_matches_reference_sentence() {
+        assert_eq!(nmea_checksum(std::str::from_utf8(GGA).unwrap()), 0x47);
+        assert_eq!(nmea_checksum("GPGLL,4916.45,N,12311.12,W,225444,A"), 0x31);
+    }
+
+    #[test]
+    fn nmea_sentence_split_across_pushes() {
+        let mut decoder = FrameDecoder::new(Framing::Nmea);
+        let (head, tail) = GGA.split_at(20);
+        assert!(decoder.push(head).is_empty());
+        assert!(decoder.push(tail).is_empty());
+        assert_eq!(decoder.push(b"\r\n"), vec![GGA.to_vec()]);
+    }
+
+    #[test]
+    fn nmea_drops_bad_checksums_and_noise() {
+        let mut decoder = FrameDecoder::new(Framing::Nmea);
+        let mut bad = GGA.to_vec();
+        *bad.last_mut().unwrap() = b'8';
+        bad.extend_from_slice(b"\r\nnoise\n");
+        bad.extend_from_slice(GGA);
+        bad.push(b'\n');
+        assert_eq!(decoder.push(&bad), vec![GGA.to_vec()]);
+    }
+
+    #[test]
+    fn slip_round_trip_with_escapes() {
+        let payload = vec![0x01, SLIP_END, 0x02, SLIP_ESC, 0x03];
+        let frame = slip_encode(&payload);
+        assert_eq!(
+            frame,
+            vec![
+                SLIP_END,
+                0x01,
+                SLIP_ESC,
+                SLIP_ESC_END,
+                0x02,
+                SLIP_ESC,
+                SLIP_ESC_ESC,
+                0x03,
+                SLIP_END
+            ]
+        );
+
+        let mut decoder = FrameDecoder::new(Framing::Slip);
+        // Split right after an escape byte
+        assert!(decoder.push(&frame[..3]).is_empty());
+        assert_eq!(decoder.push(&frame[3..]), vec![payload]);
+    }
+
+    #[test]
+    fn slip_skips_empty_frames_and_drops_bad_escapes() {
+        let mut decoder = FrameDecoder::new(Framing::Slip);
+        let data = [
+            SLIP_END, SLIP_END, 0x01, SLIP_ESC, 0x42, 0x02, SLIP_END, 0x03, SLIP_END,
+        ];
+        assert_eq!(decoder.push(&data), vec![vec![0x03]]);
+        // A frame ending right after `ESC` is incomplete too
+        assert!(decoder.push(&[0x04, SLIP_ESC, SLIP_END]).is_empty());
+        assert_eq!(decoder.push(&[0x05, SLIP_END]), vec![vec![0x05]]);
+    }
+
+    #[test]
+    fn json_lines_skip_blanks_and_strip_cr() {
+        let mut decoder = FrameDecoder::new(Framing::Json);
+        assert!(decoder.push(b"{\"a\":").is_empty());
+        assert_eq!(
+            decoder.push(b"1}\r\n \r\n\n[2]\n"),
+            vec![b"{\"a\":1}".to_vec(), b"[2]".to_vec()]
+        );
+    }
+
+    #[test]
+    fn overlong_partial_frame_is_dropped_and_counted() {
+        let mut decoder = FrameDecoder::with_max_len(Framing::Json, 4);
+        assert!(decoder.push(b"abcdef").is_empty());
+        assert_eq!(decoder.take_dropped(), 4);
+        assert_eq!(decoder.take_dropped(), 0);
+        // What came after the overflow still makes a frame
+        assert_eq!(decoder.push(b"\n"), vec![b"ef".to_vec()]);
+
+        let mut decoder = FrameDecoder::with_max_len(Framing::Slip, 2);
+        assert_eq!(decoder.push(&[1, 2, 3, SLIP_END]), vec![vec![3]]);
+        assert_eq!(decoder.take_dropped(), 2);
+    }
+}
@@ -0,0 +1,33 @@
+//! Hex string conversions for `write_hex` and `read_hex`
+
+use crate::error::Error;
+
+/// Parse hex bytes such as `"01 03 00 0A"`, `"01:03:00:0A"` or `"0103000A"`
+///
+/// Whitespace and colons separate groups; each group must have an even
+/// number of hex digits.
+pub(crate) fn parse_hex(hex: &str) -> Result<Vec<u8>, Error> {
+    let mut bytes = Vec::new();
+    for group in hex
+        .split(|c: char| c.is_whitespace() || c == ':')
+        .filter(|group| !group.is_empty())
+    {
+        if group.len() % 2 != 0 || !group.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(Error::InvalidInput(format!("Malformed hex: {}", group)));
+        }
+        for i in (0..group.len()).step_by(2) {
+            // Validated above, so this can't fail
+            bytes.push(u8::from_str_radix(&group[i..i + 2], 16).unwrap_or_default());
+        }
+    }
+    Ok(bytes)
+}
+
+/// Format bytes as uppercase hex separated by spaces, e.g. `"01 03 00 0A"`
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{:02X}", byte))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,138 @@
+//! The latency timer of FTDI USB-serial adapters
+//!
+//! FTDI chips hold received bytes back until their buffer fills or the timer
+//! runs out, 16 ms by default, which dominates the round trip of short
+//! request/response exchanges. The Linux `ftdi_sio` driver exposes the timer
+//! as a sysfs attribute of the port, which applies at once. The Windows VCP
+//! driver keeps it in the port's `Device Parameters` registry key and reads it
+//! when the port is opened. On macOS the timer is only reachable through the
+//! vendor's D2XX library, so it reports `Unsupported`.
+
+use crate::error::Error;
+
+/// Error for a port whose driver has no latency timer
+#[cfg(any(target_os = "linux", target_os = "windows"))]
+fn not_ftdi(path: &str) -> Error {
+    Error::Unsupported(format!(
+        "{} is not an FTDI adapter with a latency timer",
+        path
+    ))
+}
+
+/// Set the latency timer of the FTDI adapter at `path` to `ms` milliseconds
+#[cfg(target_os = "linux")]
+pub fn set(path: &str, ms: u8) -> Result<(), Error> {
+    use std::io;
+
+    // Virtual and network ports have no device node
+    let device = std::fs::canonicalize(path).map_err(|_| not_ftdi(path))?;
+    let name = device
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| not_ftdi(path))?;
+    let attribute = format!("/sys/bus/usb-serial/devices/{}/latency_timer", name);
+
+    // Only `ftdi_sio` creates the attribute
+    if !std::path::Path::new(&attribute).exists() {
+        return Err(not_ftdi(path));
+    }
+
+    std::fs::write(&attribute, ms.to_string()).map_err(|e| match e.kind() {
+        io::ErrorKind::PermissionDenied => Error::PermissionDenied(format!(
+            "{} is only writable by root unless a udev rule allows it",
+            attribute
+        )),
+        _ => e.into(),
+    })
+}
+
+/// Store the latency timer in the settings the FTDI driver keeps for the port
+/// at `path`; it applies from the next time the port is opened
+#[cfg(target_os = "windows")]
+pub fn set(path: &str, ms: u8) -> Result<(), Error> {
+    let port = path.strip_prefix(r"\\.\").unwrap_or(path);
+    let key = windows::device_parameters(port)?.ok_or_else(|| not_ftdi(path))?;
+    windows::set_latency_timer(&key, ms)
+}
+
+/// The latency timer can only be changed on Linux and Windows
+#[cfg(not(any(target_os = "linux", target_os = "windows")))]
+pub fn set(_path: &str, _ms: u8) -> Result<(), Error> {
+    Err(Error::Unsupported(
+        "The latency timer can only be set on Linux and Windows".to_string(),
+    ))
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use crate::error::Error;
+    use std::os::windows::process::CommandExt;
+    use std::process::{Command, Output};
+
+    /// Devices enumerated by the FTDI bus driver, one key per adapter
+    const FTDIBUS: &str = r"HKLM\SYSTEM\CurrentControlSet\Enum\FTDIBUS";
+    /// Keeps `reg.exe` from flashing a console window in GUI apps
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+    fn reg(args: &[&str]) -> Result<Output, Error> {
+        Ok(Command::new("reg")
+            .args(args)
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()?)
+    }
+
+    /// The `Device Parameters` key of the FTDI adapter whose `PortName` is
+    /// `port`, e.g. `COM3`
+    pub fn device_parameters(port: &str) -> Result<Option<String>, Error> {
+        let output = reg(&["query", FTDIBUS, "/s", "/v", "PortName"])?;
+        // Fails when no FTDI adapter was ever installed
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        // Each match is a key line followed by `PortName    REG_SZ    COM3`
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut key = None;
+        for line in stdout.lines() {
+            if line.starts_with("HKEY_") {
+                key = Some(line.trim());
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            if fields.next() == Some("PortName")
+                && fields.next() == Some("REG_SZ")
+                && fields
+                    .next()
+                    .is_some_and(|name| name.eq_ignore_ascii_case(port))
+            {
+                return Ok(key.map(str::to_string));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Write `LatencyTimer` to the `Device Parameters` key `key`
+    pub fn set_latency_timer(key: &str, ms: u8) -> Result<(), Error> {
+        let ms = ms.to_string();
+        let args = [
+            "add",
+            key,
+            "/v",
+            "LatencyTimer",
+            "/t",
+            "REG_DWORD",
+            "/d",
+            ms.as_str(),
+            "/f",
+        ];
+        let output = reg(&args)?;
+        if output.status.success() {
+            return Ok(());
+        }
+        // The key belongs to the system, so this is what usually fails
+        Err(Error::PermissionDenied(format!(
+            "Failed to write the FTDI driver settings, which takes administrator rights: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
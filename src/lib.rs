@@ -3,6 +3,7 @@
 // SPDX-License-Identifier: MIT
 
 use crate::commands::*;
+pub use crate::config::{AllowedPort, Config};
 use tauri::{
     plugin::{Builder, TauriPlugin},
     Manager, Runtime,
@@ -12,71 +13,205 @@ use tauri::{
 const PLUGIN_IDENTIFIER: &str = "app.tauri.serialplugin";
 
 #[cfg(desktop)]
-use crate::desktop_api::SerialPort;
+pub use crate::desktop_api::SerialPort;
+#[cfg(desktop)]
+pub use crate::managed_io::{ManagedReader, ManagedWriter};
 #[cfg(target_os = "android")]
 use crate::mobile_api::SerialPort;
 #[cfg(desktop)]
+pub use crate::opener::{PortOpener, PortSettings, SystemPortOpener};
+#[cfg(desktop)]
 use std::collections::HashMap;
 #[cfg(desktop)]
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
+
+#[macro_use]
+pub mod logger;
 
+#[cfg(desktop)]
+mod bluetooth;
+#[cfg(desktop)]
+mod capabilities;
+pub mod checksum;
 mod commands;
+mod config;
 
 #[cfg(desktop)]
 mod desktop_api;
+#[cfg(all(desktop, feature = "tokio"))]
+mod desktop_async;
 mod error;
+#[cfg(desktop)]
+mod flow_chars;
+pub mod framing;
+mod hex;
+#[cfg(desktop)]
+mod latency;
+#[cfg(desktop)]
+mod line_errors;
+#[cfg(all(desktop, feature = "loopback"))]
+pub mod loopback;
+#[cfg(desktop)]
+mod managed_io;
 #[cfg(mobile)]
 mod mobile_api;
+#[cfg(desktop)]
+mod opener;
+#[cfg(desktop)]
+mod rs485;
 pub mod state;
+#[cfg(all(desktop, feature = "tcp"))]
+pub mod tcp;
 
-pub fn init<R: Runtime>() -> TauriPlugin<R> {
-    Builder::new("serialplugin")
+/// Initialize the plugin, configured by the `plugins.serialplugin` block of
+/// `tauri.conf.json`
+pub fn init<R: Runtime>() -> TauriPlugin<R, Option<Config>> {
+    build(None)
+}
+
+/// Initialize the plugin with `config`, ignoring `tauri.conf.json`
+pub fn init_with_config<R: Runtime>(config: Config) -> TauriPlugin<R, Option<Config>> {
+    build(Some(config))
+}
+
+fn build<R: Runtime>(config: Option<Config>) -> TauriPlugin<R, Option<Config>> {
+    Builder::<R, Option<Config>>::new("serialplugin")
         .js_init_script(include_str!("api-iife.js").to_string())
         .invoke_handler(tauri::generate_handler![
             available_ports,
             available_ports_direct,
+            available_ports_merged,
+            available_ports_filtered,
+            start_port_scan,
+            stop_port_scan,
             cancel_read,
+            cancel_operation,
             close,
             close_all,
+            reconnect,
+            reconfigure,
             force_close,
             open,
+            open_with_config,
+            open_when_available,
+            cancel_open_when_available,
+            add_usb_filter,
+            has_permission,
+            request_permission,
             start_listening,
             stop_listening,
+            pause_listening,
+            resume_listening,
             read,
+            read_binary,
+            read_hex,
+            read_both,
+            read_exact_or_partial,
+            read_available,
+            peek,
+            transaction,
             write,
             write_binary,
+            write_binary_paced,
+            write_slip,
+            write_hex,
+            write_sequence,
+            set_rs485,
             set_baud_rate,
             set_data_bits,
             set_flow_control,
+            set_flow_control_chars,
+            get_flow_control_chars,
             set_parity,
             set_stop_bits,
             set_timeout,
+            set_read_timeout,
+            set_write_timeout,
+            set_wait_for_cts,
+            set_inter_byte_delay,
+            set_write_events,
+            set_write_chunk_size,
             write_request_to_send,
             write_data_terminal_ready,
+            set_control_lines,
+            reset_via_dtr,
+            enter_esp_bootloader,
+            reset_esp,
+            trigger_bootloader_1200,
+            start_heartbeat,
+            stop_heartbeat,
+            idle_auto_close,
             read_clear_to_send,
             read_data_set_ready,
             read_ring_indicator,
             read_carrier_detect,
+            get_modem_status,
+            watch_modem_signals,
+            stop_watching_modem_signals,
+            get_line_errors,
+            set_latency_timer,
             bytes_to_read,
             bytes_to_write,
+            drain,
             clear_buffer,
             set_break,
             clear_break,
+            send_break,
+            is_open,
+            managed_ports,
+            managed_ports_detailed,
+            get_port_state,
+            export_config,
+            get_port_capabilities,
+            get_stats,
+            reset_stats,
+            start_capture,
+            stop_capture,
+            replay_capture,
+            set_log_level,
+            get_log_level,
+            checksum,
+            set_port_log_level,
         ])
-        .setup(|app, _api| {
+        .setup(move |app, api| {
+            let config = config.or_else(|| api.config().clone()).unwrap_or_default();
+            if let Some(level) = config.log_level {
+                logger::set_log_level(level);
+            }
+            let config = std::sync::Arc::new(config);
+
             #[cfg(target_os = "android")]
-            let handle = _api.register_android_plugin(PLUGIN_IDENTIFIER, "SerialPlugin")?;
+            let handle = api.register_android_plugin(PLUGIN_IDENTIFIER, "SerialPlugin")?;
             #[cfg(target_os = "android")]
-            let serialplugin = SerialPort(handle);
+            let serialplugin = SerialPort(handle, config, Default::default());
             // app.manage(SerialPort(handle));
             #[cfg(desktop)]
             let serialplugin = SerialPort {
                 app: app.clone(),
                 serialports: Arc::new(Mutex::new(HashMap::new())),
+                port_scan: Arc::new(Mutex::new(None)),
+                pending_opens: Arc::new(Mutex::new(HashMap::new())),
+                cancel_flags: Arc::new(Mutex::new(HashMap::new())),
+                opener: Arc::new(RwLock::new(Arc::new(SystemPortOpener))),
+                config,
             };
 
             app.manage(serialplugin);
             Ok(())
         })
+        .on_event(|_app, _event| {
+            // Don't leave open_when_available waiting on a closed app, and release
+            // every device so it isn't left locked after exit
+            #[cfg(desktop)]
+            if let tauri::RunEvent::Exit = _event {
+                if let Some(serial) = _app.try_state::<SerialPort<R>>() {
+                    let _ = serial.cancel_open_when_available();
+                    let _ = serial.stop_port_scan();
+                    if let Err(e) = serial.close_all() {
+                        log_error!("Failed to close ports on exit: {}", e);
+                    }
+                }
+            }
+        })
         .build()
 }
@@ -0,0 +1,84 @@
+//! Parity, framing and overrun error counters kept by the serial driver
+//!
+//! Reads deliver corrupted bytes without saying so, but on Linux the driver
+//! counts line errors per device and hands them out with `TIOCGICOUNT`. The
+//! ioctl needs the port's file descriptor, which `serialport` doesn't expose,
+//! so it is looked up in `/proc/self/fd` by the device it points to. Other
+//! platforms and drivers without the counters report an error.
+
+use crate::error::Error;
+use crate::state::LineErrors;
+
+/// Whether this platform can report line errors at all
+pub const SUPPORTED: bool = cfg!(target_os = "linux");
+
+/// Read the line error counters of the open port at `path`
+#[cfg(target_os = "linux")]
+pub fn read(path: &str) -> Result<LineErrors, Error> {
+    use std::io;
+
+    /// `struct serial_icounter_struct` from `<linux/serial.h>`
+    #[repr(C)]
+    #[derive(Default)]
+    struct SerialIcounter {
+        cts: libc::c_int,
+        dsr: libc::c_int,
+        rng: libc::c_int,
+        dcd: libc::c_int,
+        rx: libc::c_int,
+        tx: libc::c_int,
+        frame: libc::c_int,
+        overrun: libc::c_int,
+        parity: libc::c_int,
+        brk: libc::c_int,
+        buf_overrun: libc::c_int,
+        reserved: [libc::c_int; 9],
+    }
+
+    let fd = find_fd(path)?;
+    let mut counts = SerialIcounter::default();
+    // SAFETY: `counts` matches the layout the kernel writes for TIOCGICOUNT
+    let result = unsafe { libc::ioctl(fd, libc::TIOCGICOUNT, &mut counts) };
+    if result < 0 {
+        return Err(io::Error::last_os_error().into());
+    }
+
+    Ok(LineErrors {
+        framing: counts.frame as u32,
+        parity: counts.parity as u32,
+        overrun: counts.overrun as u32,
+        buffer_overrun: counts.buf_overrun as u32,
+        breaks: counts.brk as u32,
+    })
+}
+
+/// Line errors are only counted by the Linux serial drivers
+#[cfg(not(target_os = "linux"))]
+pub fn read(_path: &str) -> Result<LineErrors, Error> {
+    Err(Error::String(
+        "Line error counters are only available on Linux".to_string(),
+    ))
+}
+
+/// Find a file descriptor of this process open on the device at `path`
+#[cfg(target_os = "linux")]
+fn find_fd(path: &str) -> Result<libc::c_int, Error> {
+    let device = std::fs::canonicalize(path)?;
+    for entry in std::fs::read_dir("/proc/self/fd")? {
+        let entry = entry?;
+        if std::fs::read_link(entry.path()).ok().as_ref() != Some(&device) {
+            continue;
+        }
+        if let Some(fd) = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| name.parse().ok())
+        {
+            return Ok(fd);
+        }
+    }
+    Err(Error::String(format!(
+        "No open file descriptor for {}",
+        path
+    )))
+}
@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+
+/// Verbosity of the plugin's own diagnostic output
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LogLevel {
+    /// No output at all
+    None,
+    /// Failures only
+    Error,
+    /// Failures and recoverable problems
+    Warn,
+    /// General progress messages
+    Info,
+    /// Internal steps
+    Debug,
+    /// Everything, including the bytes sent and received
+    Trace,
+}
+
+impl LogLevel {
+    fn from_u8(value: u8) -> Self {
+        match value {
+            0 => LogLevel::None,
+            1 => LogLevel::Error,
+            2 => LogLevel::Warn,
+            3 => LogLevel::Info,
+            4 => LogLevel::Debug,
+            _ => LogLevel::Trace,
+        }
+    }
+}
+
+static LOG_LEVEL: AtomicU8 = AtomicU8::new(LogLevel::Info as u8);
+
+/// Set the most verbose level that is still printed
+pub fn set_log_level(level: LogLevel) {
+    LOG_LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Get the current log level
+pub fn get_log_level() -> LogLevel {
+    LogLevel::from_u8(LOG_LEVEL.load(Ordering::Relaxed))
+}
+
+/// Whether a message at `level` should be printed
+pub fn enabled(level: LogLevel) -> bool {
+    level != LogLevel::None && level <= get_log_level()
+}
+
+/// Stored in a `PortLogLevel` while the port goes by the global level
+const GLOBAL_LEVEL: u8 = u8::MAX;
+
+/// Level overriding the global one for messages about one port
+///
+/// It is kept with the port's state and shared with the threads working on
+/// the port, so it goes away when the port is closed.
+#[derive(Debug)]
+pub struct PortLogLevel(AtomicU8);
+
+impl Default for PortLogLevel {
+    fn default() -> Self {
+        Self(AtomicU8::new(GLOBAL_LEVEL))
+    }
+}
+
+impl PortLogLevel {
+    /// Use `level` for this port, or go back to the global level with `None`
+    pub fn set(&self, level: Option<LogLevel>) {
+        let value = level.map_or(GLOBAL_LEVEL, |level| level as u8);
+        self.0.store(value, Ordering::Relaxed);
+    }
+
+    /// The level overriding the global one, if any
+    pub fn get(&self) -> Option<LogLevel> {
+        match self.0.load(Ordering::Relaxed) {
+            GLOBAL_LEVEL => None,
+            value => Some(LogLevel::from_u8(value)),
+        }
+    }
+}
+
+/// Whether a message at `level` about the port with `port` as its level
+/// should be printed
+pub fn enabled_for(port: &PortLogLevel, level: LogLevel) -> bool {
+    let max = port.get().unwrap_or_else(get_log_level);
+    level != LogLevel::None && level <= max
+}
+
+type LogSink = Arc<dyn Fn(LogLevel, &str) + Send + Sync>;
+
+fn log_sink() -> &'static RwLock<Option<LogSink>> {
+    static LOG_SINK: OnceLock<RwLock<Option<LogSink>>> = OnceLock::new();
+    LOG_SINK.get_or_init(Default::default)
+}
+
+/// Send the plugin's log messages to `sink` instead of stdout/stderr
+///
+/// The sink only receives messages the log level lets through. It may be
+/// called from any thread, and must not log through the plugin itself.
+pub fn set_log_sink<F: Fn(LogLevel, &str) + Send + Sync + 'static>(sink: F) {
+    *log_sink().write().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(sink));
+}
+
+/// Go back to printing log messages to stdout/stderr
+pub fn clear_log_sink() {
+    *log_sink().write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Deliver a message at `level` to the sink, or print it; used by the `log_*` macros
+pub fn log(level: LogLevel, args: fmt::Arguments) {
+    let sink = log_sink().read().unwrap_or_else(|e| e.into_inner()).clone();
+    match sink {
+        Some(sink) => sink(level, &args.to_string()),
+        None if level <= LogLevel::Warn => eprintln!("{}", args),
+        None => println!("{}", args),
+    }
+}
+
+// Each macro also accepts `port = &log_level, ...` for messages about one
+// port, which are filtered by that port's `PortLogLevel`
+// On mobile only the shared frame decoder logs, with `log_warn!` and
+// `log_debug!`, as the mobile API doesn't log yet
+#[cfg_attr(mobile, allow(unused_macros))]
+macro_rules! log_error {
+    (port = $level:expr, $($arg:tt)*) => {
+        if $crate::logger::enabled_for($level, $crate::logger::LogLevel::Error) {
+            $crate::logger::log($crate::logger::LogLevel::Error, format_args!($($arg)*));
+        }
+    };
+    ($($arg:tt)*) => {
+        if $crate::logger::enabled($crate::logger::LogLevel::Error) {
+            $crate::logger::log($crate::logger::LogLevel::Error, format_args!($($arg)*));
+        }
+    };
+}
+
+macro_rules! log_warn {
+    (port = $level:expr, $($arg:tt)*) => {
+        if $crate::logger::enabled_for($level, $crate::logger::LogLevel::Warn) {
+            $crate::logger::log($crate::logger::LogLevel::Warn, format_args!($($arg)*));
+        }
+    };
+    ($($arg:tt)*) => {
+        if $crate::logger::enabled($crate::logger::LogLevel::Warn) {
+            $crate::logger::log($crate::logger::LogLevel::Warn, format_args!($($arg)*));
+        }
+    };
+}
+
+#[cfg_attr(mobile, allow(unused_macros))]
+macro_rules! log_info {
+    (port = $level:expr, $($arg:tt)*) => {
+        if $crate::logger::enabled_for($level, $crate::logger::LogLevel::Info) {
+            $crate::logger::log($crate::logger::LogLevel::Info, format_args!($($arg)*));
+        }
+    };
+    ($($arg:tt)*) => {
+        if $crate::logger::enabled($crate::logger::LogLevel::Info) {
+            $crate::logger::log($crate::logger::LogLevel::Info, format_args!($($arg)*));
+        }
+    };
+}
+
+macro_rules! log_debug {
+    (port = $level:expr, $($arg:tt)*) => {
+        if $crate::logger::enabled_for($level, $crate::logger::LogLevel::Debug) {
+            $crate::logger::log($crate::logger::LogLevel::Debug, format_args!($($arg)*));
+        }
+    };
+    ($($arg:tt)*) => {
+        if $crate::logger::enabled($crate::logger::LogLevel::Debug) {
+            $crate::logger::log($crate::logger::LogLevel::Debug, format_args!($($arg)*));
+        }
+    };
+}
+
+#[cfg_attr(mobile, allow(unused_macros))]
+macro_rules! log_trace {
+    (port = $level:expr, $($arg:tt)*) => {
+        if $crate::logger::enabled_for($level, $crate::logger::LogLevel::Trace) {
+            $crate::logger::log($crate::logger::LogLevel::Trace, format_args!($($arg)*));
+        }
+    };
+    ($($arg:tt)*) => {
+        if $crate::logger::enabled($crate::logger::LogLevel::Trace) {
+            $crate::logger::log($crate::logger::LogLevel::Trace, format_args!($($arg)*));
+        }
+    };
+}
@@ -0,0 +1,307 @@
+//! In-memory virtual ports, enabled with the `loopback` feature
+//!
+//! Opening a path with the `loopback://` scheme creates one end of a virtual
+//! null-modem cable instead of touching a device. A name ending in `A` is
+//! wired to the same name ending in `B` (and the other way round), so bytes
+//! written to `loopback://A` are read from `loopback://B`; any other name is
+//! wired to itself and echoes back what is written. This lets the serial logic
+//! of an app be tested end to end without hardware. Once neither end of a
+//! pair is open any more, whatever was left unread is discarded.
+
+use crate::opener::PortSettings;
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, StopBits};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, OnceLock, Weak};
+use std::time::{Duration, Instant};
+
+/// Path prefix that selects a virtual port
+pub const SCHEME: &str = "loopback://";
+
+/// Bytes kept for an end nobody reads from; like a real line without a
+/// listener, anything beyond this is lost
+const MAX_BUFFERED: usize = 64 * 1024;
+
+/// One end of a virtual cable: the bytes waiting to be read from it and the
+/// control lines it drives
+struct Endpoint {
+    name: String,
+    buffer: Mutex<VecDeque<u8>>,
+    ready: Condvar,
+    rts: AtomicBool,
+    dtr: AtomicBool,
+}
+
+impl Drop for Endpoint {
+    fn drop(&mut self) {
+        let mut endpoints = endpoints();
+        // An end of the same name may have been created since the last
+        // handle went away
+        if endpoints
+            .get(&self.name)
+            .is_some_and(|endpoint| endpoint.strong_count() == 0)
+        {
+            endpoints.remove(&self.name);
+        }
+    }
+}
+
+/// The ends held by open ports, by name, so both sides of a pair share state
+///
+/// An end is dropped with the last port holding it, along with anything still
+/// buffered in it, so opening the name again starts afresh.
+fn endpoints() -> MutexGuard<'static, HashMap<String, Weak<Endpoint>>> {
+    static ENDPOINTS: OnceLock<Mutex<HashMap<String, Weak<Endpoint>>>> = OnceLock::new();
+    ENDPOINTS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+}
+
+/// The end called `name`, created if no open port holds it
+fn endpoint(name: &str) -> Arc<Endpoint> {
+    let mut endpoints = endpoints();
+    if let Some(endpoint) = endpoints.get(name).and_then(Weak::upgrade) {
+        return endpoint;
+    }
+    let endpoint = Arc::new(Endpoint {
+        name: name.to_string(),
+        buffer: Mutex::new(VecDeque::new()),
+        ready: Condvar::new(),
+        rts: AtomicBool::new(false),
+        dtr: AtomicBool::new(false),
+    });
+    endpoints.insert(name.to_string(), Arc::downgrade(&endpoint));
+    endpoint
+}
+
+/// Name of the end that `name` is wired to
+fn peer_name(name: &str) -> String {
+    if let Some(base) = name.strip_suffix('A') {
+        format!("{}B", base)
+    } else if let Some(base) = name.strip_suffix('B') {
+        format!("{}A", base)
+    } else {
+        name.to_string()
+    }
+}
+
+fn lock_error<T>(e: std::sync::PoisonError<T>) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e.to_string())
+}
+
+/// A virtual port implementing `serialport::SerialPort`
+///
+/// Line settings are stored but have no effect on the data. The control lines
+/// are crossed like a null-modem cable: CTS follows the peer's RTS, DSR and CD
+/// follow the peer's DTR.
+pub struct LoopbackPort {
+    path: String,
+    own: Arc<Endpoint>,
+    peer: Arc<Endpoint>,
+    baud_rate: u32,
+    data_bits: DataBits,
+    flow_control: FlowControl,
+    parity: Parity,
+    stop_bits: StopBits,
+    timeout: Duration,
+}
+
+impl LoopbackPort {
+    /// Open the end named by `path`, which must start with `loopback://`
+    pub fn open(path: &str, settings: &PortSettings) -> serialport::Result<Self> {
+        let name = match path.strip_prefix(SCHEME) {
+            Some(name) if !name.is_empty() => name,
+            _ => {
+                return Err(serialport::Error::new(
+                    serialport::ErrorKind::InvalidInput,
+                    format!("{} is not a loopback port path", path),
+                ))
+            }
+        };
+
+        Ok(Self {
+            path: path.to_string(),
+            own: endpoint(name),
+            peer: endpoint(&peer_name(name)),
+            baud_rate: settings.baud_rate,
+            data_bits: settings.data_bits,
+            flow_control: settings.flow_control,
+            parity: settings.parity,
+            stop_bits: settings.stop_bits,
+            timeout: settings.timeout,
+        })
+    }
+}
+
+impl Read for LoopbackPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let deadline = Instant::now() + self.timeout;
+        let mut buffer = self.own.buffer.lock().map_err(lock_error)?;
+        while buffer.is_empty() {
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "Operation timed out",
+                ));
+            }
+            buffer = self
+                .own
+                .ready
+                .wait_timeout(buffer, deadline - now)
+                .map_err(lock_error)?
+                .0;
+        }
+
+        let count = buf.len().min(buffer.len());
+        for (slot, byte) in buf.iter_mut().zip(buffer.drain(..count)) {
+            *slot = byte;
+        }
+        Ok(count)
+    }
+}
+
+impl Write for LoopbackPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut buffer = self.peer.buffer.lock().map_err(lock_error)?;
+        let room = MAX_BUFFERED.saturating_sub(buffer.len());
+        buffer.extend(buf.iter().take(room));
+        self.peer.ready.notify_all();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl serialport::SerialPort for LoopbackPort {
+    fn name(&self) -> Option<String> {
+        Some(self.path.clone())
+    }
+
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        Ok(self.baud_rate)
+    }
+
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        Ok(self.data_bits)
+    }
+
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        Ok(self.flow_control)
+    }
+
+    fn parity(&self) -> serialport::Result<Parity> {
+        Ok(self.parity)
+    }
+
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        Ok(self.stop_bits)
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> serialport::Result<()> {
+        self.baud_rate = baud_rate;
+        Ok(())
+    }
+
+    fn set_data_bits(&mut self, data_bits: DataBits) -> serialport::Result<()> {
+        self.data_bits = data_bits;
+        Ok(())
+    }
+
+    fn set_flow_control(&mut self, flow_control: FlowControl) -> serialport::Result<()> {
+        self.flow_control = flow_control;
+        Ok(())
+    }
+
+    fn set_parity(&mut self, parity: Parity) -> serialport::Result<()> {
+        self.parity = parity;
+        Ok(())
+    }
+
+    fn set_stop_bits(&mut self, stop_bits: StopBits) -> serialport::Result<()> {
+        self.stop_bits = stop_bits;
+        Ok(())
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        self.timeout = timeout;
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, level: bool) -> serialport::Result<()> {
+        self.own.rts.store(level, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, level: bool) -> serialport::Result<()> {
+        self.own.dtr.store(level, Ordering::Relaxed);
+        Ok(())
+    }
+
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        Ok(self.peer.rts.load(Ordering::Relaxed))
+    }
+
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        Ok(self.peer.dtr.load(Ordering::Relaxed))
+    }
+
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        Ok(self.peer.dtr.load(Ordering::Relaxed))
+    }
+
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        let buffer = self.own.buffer.lock().map_err(lock_error)?;
+        Ok(buffer.len() as u32)
+    }
+
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        // Writes land in the peer's buffer immediately
+        Ok(0)
+    }
+
+    fn clear(&self, buffer_to_clear: ClearBuffer) -> serialport::Result<()> {
+        if matches!(buffer_to_clear, ClearBuffer::Input | ClearBuffer::All) {
+            self.own.buffer.lock().map_err(lock_error)?.clear();
+        }
+        Ok(())
+    }
+
+    fn try_clone(&self) -> serialport::Result<Box<dyn serialport::SerialPort>> {
+        Ok(Box::new(Self {
+            path: self.path.clone(),
+            own: self.own.clone(),
+            peer: self.peer.clone(),
+            baud_rate: self.baud_rate,
+            data_bits: self.data_bits,
+            flow_control: self.flow_control,
+            parity: self.parity,
+            stop_bits: self.stop_bits,
+            timeout: self.timeout,
+        }))
+    }
+
+    fn set_break(&self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn clear_break(&self) -> serialport::Result<()> {
+        Ok(())
+    }
+}
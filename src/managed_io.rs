@@ -0,0 +1,68 @@
+use crate::desktop_api::lock_ports;
+use crate::state::SerialportInfo;
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+
+/// `std::io::Read` over a managed port, see `SerialPort::reader`
+pub struct ManagedReader {
+    pub(crate) path: String,
+    pub(crate) serialports: Arc<Mutex<HashMap<String, SerialportInfo>>>,
+    pub(crate) serialport: Box<dyn serialport::SerialPort>,
+}
+
+/// `std::io::Write` over a managed port, see `SerialPort::writer`
+pub struct ManagedWriter {
+    pub(crate) path: String,
+    pub(crate) serialports: Arc<Mutex<HashMap<String, SerialportInfo>>>,
+    pub(crate) serialport: Box<dyn serialport::SerialPort>,
+}
+
+/// Fail once the port has been closed through the plugin, even though the
+/// cloned handle would otherwise keep the device open
+fn ensure_managed(
+    serialports: &Mutex<HashMap<String, SerialportInfo>>,
+    path: &str,
+) -> io::Result<()> {
+    if lock_ports(serialports).contains_key(path) {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::NotConnected,
+            format!("Serial port {} is not open!", path),
+        ))
+    }
+}
+
+impl ManagedReader {
+    /// Path of the managed port this reader belongs to
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl ManagedWriter {
+    /// Path of the managed port this writer belongs to
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Read for ManagedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        ensure_managed(&self.serialports, &self.path)?;
+        self.serialport.read(buf)
+    }
+}
+
+impl Write for ManagedWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        ensure_managed(&self.serialports, &self.path)?;
+        self.serialport.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        ensure_managed(&self.serialports, &self.path)?;
+        self.serialport.flush()
+    }
+}
@@ -1,14 +1,41 @@
+use crate::config::Config;
 use crate::error::Error;
-use crate::state::{ClearBuffer, DataBits, FlowControl, Parity, StopBits};
+use crate::logger::LogLevel;
+use crate::state::{
+    validate_line_settings, BytesAndText, CaptureDirection, CaptureFormat, ClearBuffer, DataBits,
+    EmitStrategy, FlowControl, FlowControlChars, Framing, LineErrors, ManagedPortInfo, ModemStatus,
+    OpenConfig, Parity, PortCapabilities, PortConfig, PortFilter, PortState, PortStatsSnapshot,
+    PortTarget, Rs485Config, StopBits,
+};
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tauri::plugin::PluginHandle;
 use tauri::Runtime;
 
+/// Timeout of each call a read without a timeout makes while it waits for data
+const BLOCKING_READ_SLICE: u64 = 1000;
+
+/// How long EN is held low when resetting an ESP chip, as esptool does
+const ESP_RESET_PULSE: Duration = Duration::from_millis(100);
+
+/// How long GPIO0 stays low after an ESP chip leaves reset
+const ESP_BOOT_DELAY: Duration = Duration::from_millis(50);
+
 /// Access to the serial port APIs for mobile platforms.
-pub struct SerialPort<R: Runtime>(pub PluginHandle<R>);
+pub struct SerialPort<R: Runtime>(
+    pub PluginHandle<R>,
+    pub(crate) Arc<Config>,
+    /// Per-path flags tripped by `cancel_operation`
+    pub(crate) Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+);
+
+/// How often `drain` checks whether the driver's output queue has emptied
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(5);
 
 #[derive(Debug, Serialize, Deserialize)]
 struct MobileResponse<T> {
@@ -33,6 +60,30 @@ struct AvailablePortsResponse {
     ports: HashMap<String, PortInfo>,
 }
 
+/// Binary data crosses the bridge base64-encoded, which is far smaller than a JSON number array
+#[derive(Deserialize, Debug)]
+struct ReadBinaryResponse {
+    data: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct PermissionResponse {
+    granted: bool,
+}
+
+/// Result of `write` and `writeBinary`
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct WriteResponse {
+    bytes_written: usize,
+}
+
+/// Result of `bytesToRead` and `bytesToWrite`
+#[derive(Deserialize, Debug)]
+struct BytesResponse {
+    bytes: u32,
+}
+
 impl<R: Runtime> SerialPort<R> {
     /// Lists all available serial ports
     pub fn available_ports(&self) -> Result<HashMap<String, HashMap<String, String>>, Error> {
@@ -59,45 +110,160 @@ impl<R: Runtime> SerialPort<R> {
     }
 
     /// Lists all available serial ports using direct system commands
+    ///
+    /// Android offers no system commands to query, so this is the USB host
+    /// enumeration of `available_ports`.
     pub fn available_ports_direct(
         &self,
     ) -> Result<HashMap<String, HashMap<String, String>>, Error> {
-        match self.0.run_mobile_plugin("availablePortsDirect", ()) {
-            Ok(Value::Object(result)) => serde_json::from_value(Value::Object(result))
-                .map_err(|e| Error::String(format!("Failed to parse ports: {}", e))),
-            Ok(_) => Err(Error::String("Invalid response format".to_string())),
+        self.available_ports()
+    }
+
+    /// Lists the available serial ports passing `filter`
+    pub fn available_ports_filtered(
+        &self,
+        filter: PortFilter,
+    ) -> Result<HashMap<String, HashMap<String, String>>, Error> {
+        let mut ports = self.available_ports()?;
+        ports.retain(|_, info| filter.matches(info));
+        Ok(ports)
+    }
+
+    /// Lists all available serial ports keyed by their device path
+    ///
+    /// Android has a single source of port information, so this is the same as
+    /// `available_ports`.
+    pub fn available_ports_merged(
+        &self,
+    ) -> Result<HashMap<String, HashMap<String, String>>, Error> {
+        self.available_ports()
+    }
+
+    /// Waiting for a port to appear is only available on desktop
+    pub fn open_when_available(
+        &self,
+        _target: PortTarget,
+        _config: OpenConfig,
+        _timeout: u64,
+    ) -> Result<String, Error> {
+        Err(Error::String(
+            "open_when_available is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Waiting for a port to appear is only available on desktop
+    pub fn cancel_open_when_available(&self) -> Result<(), Error> {
+        Err(Error::String(
+            "open_when_available is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Treat USB devices with this vendor/product id as serial adapters
+    ///
+    /// `driver` picks the protocol: `cdc` (the default), `ch34x`, `cp21xx`,
+    /// `ftdi` or `prolific`.
+    pub fn add_usb_filter(&self, vid: u16, pid: u16, driver: Option<String>) -> Result<(), Error> {
+        let params = serde_json::json!({
+            "vid": vid,
+            "pid": pid,
+            "driver": driver,
+        });
+
+        // Failures are reported by rejecting the call, there is no result value
+        match self.0.run_mobile_plugin::<Value>("addUsbFilter", params) {
+            Ok(_) => Ok(()),
             Err(e) => Err(Error::String(format!("Plugin error: {}", e))),
         }
     }
 
-    /// Opens a serial port with the specified settings
-    pub fn open(
-        &self,
-        path: String,
-        baud_rate: u32,
-        data_bits: Option<DataBits>,
-        flow_control: Option<FlowControl>,
-        parity: Option<Parity>,
-        stop_bits: Option<StopBits>,
-        timeout: Option<u64>,
-    ) -> Result<(), Error> {
+    /// Whether the app may open the USB device at `path`
+    pub fn has_permission(&self, path: String) -> Result<bool, Error> {
+        let params = serde_json::json!({ "path": path });
+        match self
+            .0
+            .run_mobile_plugin::<PermissionResponse>("hasPermission", params)
+        {
+            Ok(response) => Ok(response.granted),
+            Err(e) => Err(Error::String(format!("Plugin error: {}", e))),
+        }
+    }
+
+    /// Ask the user for access to the USB device at `path`
+    ///
+    /// Shows the system permission dialog unless access was already granted and
+    /// returns the user's answer.
+    pub fn request_permission(&self, path: String) -> Result<bool, Error> {
+        let params = serde_json::json!({ "path": path });
+        match self
+            .0
+            .run_mobile_plugin::<PermissionResponse>("requestPermission", params)
+        {
+            Ok(response) => Ok(response.granted),
+            Err(e) => Err(Error::String(format!("Plugin error: {}", e))),
+        }
+    }
+
+    /// Background port scanning is only available on desktop; Android reports
+    /// attached devices through the USB intent instead
+    pub fn start_port_scan(&self, _interval: u64) -> Result<(), Error> {
+        Err(Error::String(
+            "Port scanning is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Background port scanning is only available on desktop
+    pub fn stop_port_scan(&self) -> Result<(), Error> {
+        Err(Error::String(
+            "Port scanning is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Opens a serial port with the specified settings, returning its path
+    ///
+    /// Busy retries and `fail_if_open` are handled by the desktop backend only.
+    pub fn open(&self, path: String, config: OpenConfig) -> Result<String, Error> {
+        validate_line_settings(
+            config.baud_rate,
+            config.data_bits.unwrap_or(DataBits::Eight),
+            config.stop_bits.unwrap_or(StopBits::One),
+        )?;
+
+        let port_info = if self.1.needs_port_info() {
+            self.available_ports()?.remove(&path)
+        } else {
+            None
+        };
+        if !self.1.allows(&path, port_info.as_ref()) {
+            return Err(Error::PermissionDenied(format!(
+                "Serial port {} is not in the allowed ports",
+                path
+            )));
+        }
+
         let params = serde_json::json!({
             "path": path,
-            "baudRate": baud_rate,
-            "dataBits": data_bits.unwrap_or(DataBits::Eight).as_u8(),
-            "flowControl": flow_control.unwrap_or(FlowControl::None).as_u8(),
-            "parity": parity.unwrap_or(Parity::None).as_u8(),
-            "stopBits": stop_bits.unwrap_or(StopBits::One).as_u8(),
-            "timeout": timeout.unwrap_or(1000),
+            "baudRate": config.baud_rate,
+            "dataBits": config.data_bits.unwrap_or(DataBits::Eight).as_u8(),
+            "flowControl": config.flow_control.unwrap_or(FlowControl::None).as_u8(),
+            "parity": config.parity.unwrap_or(Parity::None).as_u8(),
+            "stopBits": config.stop_bits.unwrap_or(StopBits::One).as_u8(),
+            "timeout": config.timeout.or(self.1.default_timeout).unwrap_or(1000),
+            "initialDtr": config.initial_dtr,
+            "initialRts": config.initial_rts,
         });
 
         match self.0.run_mobile_plugin("open", params) {
-            Ok(Value::Bool(true)) => Ok(()),
+            Ok(Value::Bool(true)) => Ok(path),
             Ok(_) => Err(Error::String("Failed to open port".to_string())),
             Err(e) => Err(Error::String(format!("Plugin error: {}", e))),
         }
     }
 
+    /// Open a port with saved line settings
+    pub fn open_with_config(&self, path: String, config: PortConfig) -> Result<String, Error> {
+        self.open(path, config.into())
+    }
+
     /// Closes a serial port
     pub fn close(&self, path: String) -> Result<(), Error> {
         let params = serde_json::json!({ "path": path });
@@ -114,6 +280,28 @@ impl<R: Runtime> SerialPort<R> {
         }
     }
 
+    /// Reopening with the last-known settings is only available on desktop
+    pub fn reconnect(&self, _path: String) -> Result<(), Error> {
+        Err(Error::String(
+            "reconnect is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Reopening with new settings under a running listener is only available
+    /// on desktop
+    pub fn reconfigure(&self, _path: String, _config: PortConfig) -> Result<(), Error> {
+        Err(Error::String(
+            "reconfigure is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// The latency timer can only be set on desktop
+    pub fn set_latency_timer(&self, _path: String, _ms: u32) -> Result<(), Error> {
+        Err(Error::Unsupported(
+            "set_latency_timer is not supported on mobile".to_string(),
+        ))
+    }
+
     /// Closes all open serial ports
     pub fn close_all(&self) -> Result<(), Error> {
         let response: MobileResponse<bool> = self
@@ -152,9 +340,8 @@ impl<R: Runtime> SerialPort<R> {
             "value": data,
         });
 
-        match self.0.run_mobile_plugin("write", params) {
-            Ok(Value::Number(n)) => Ok(n.as_u64().unwrap_or(0) as usize),
-            Ok(_) => Err(Error::String("Invalid response format".to_string())),
+        match self.0.run_mobile_plugin::<WriteResponse>("write", params) {
+            Ok(response) => Ok(response.bytes_written),
             Err(e) => Err(Error::String(format!("Plugin error: {}", e))),
         }
     }
@@ -163,43 +350,308 @@ impl<R: Runtime> SerialPort<R> {
     pub fn write_binary(&self, path: String, data: Vec<u8>) -> Result<usize, Error> {
         let params = serde_json::json!({
             "path": path,
-            "value": data,
+            "value": base64::engine::general_purpose::STANDARD.encode(&data),
         });
 
-        match self.0.run_mobile_plugin("writeBinary", params) {
-            Ok(Value::Number(n)) => Ok(n.as_u64().unwrap_or(0) as usize),
-            Ok(_) => Err(Error::String("Invalid response format".to_string())),
+        match self
+            .0
+            .run_mobile_plugin::<WriteResponse>("writeBinary", params)
+        {
+            Ok(response) => Ok(response.bytes_written),
             Err(e) => Err(Error::String(format!("Plugin error: {}", e))),
         }
     }
 
+    /// Writes binary data in chunks, waiting between them while the driver
+    /// queues `max_pending` bytes or more
+    pub fn write_binary_paced(
+        &self,
+        path: String,
+        data: Vec<u8>,
+        max_pending: usize,
+    ) -> Result<usize, Error> {
+        if max_pending == 0 {
+            return Err(Error::InvalidInput(
+                "max_pending must be at least 1 byte".to_string(),
+            ));
+        }
+
+        let mut written = 0;
+        while written < data.len() {
+            let pending = self.bytes_to_write(path.clone())? as usize;
+            if pending >= max_pending {
+                std::thread::sleep(Duration::from_millis(5));
+                continue;
+            }
+            let end = data.len().min(written + max_pending - pending);
+            written += self.write_binary(path.clone(), data[written..end].to_vec())?;
+        }
+        Ok(written)
+    }
+
+    /// SLIP-encodes `payload` as one frame and writes it
+    pub fn write_slip(&self, path: String, payload: Vec<u8>) -> Result<usize, Error> {
+        self.write_binary(path, crate::framing::slip_encode(&payload))
+    }
+
+    /// Write bytes given as hex, e.g. `"01 03 00 00 00 0A"` or `"01:03:00"`
+    pub fn write_hex(&self, path: String, hex: String) -> Result<usize, Error> {
+        self.write_binary(path, crate::hex::parse_hex(&hex)?)
+    }
+
+    /// Write several messages in order, pausing `gap_ms` milliseconds between
+    /// them, and return the total number of bytes written
+    pub fn write_sequence(
+        &self,
+        path: String,
+        messages: Vec<Vec<u8>>,
+        gap_ms: Option<u64>,
+    ) -> Result<usize, Error> {
+        let gap = Duration::from_millis(gap_ms.unwrap_or(0));
+        let mut written = 0;
+        for (index, message) in messages.into_iter().enumerate() {
+            if index > 0 && !gap.is_zero() {
+                std::thread::sleep(gap);
+            }
+            written += self.write_binary(path.clone(), message)?;
+        }
+        Ok(written)
+    }
+
+    /// Write the TX records of a capture file back out of a port, returning the
+    /// number of bytes written
+    ///
+    /// With `respect_timing` the gaps between the original writes are kept;
+    /// otherwise the records are sent back to back.
+    pub fn replay_capture(
+        &self,
+        path: String,
+        file_path: String,
+        respect_timing: bool,
+    ) -> Result<usize, Error> {
+        let mut written = 0;
+        let mut previous: Option<u64> = None;
+        for record in crate::state::read_capture(&file_path)?
+            .into_iter()
+            .filter(|record| record.direction == CaptureDirection::Tx)
+        {
+            if let (true, Some(previous)) = (respect_timing, previous) {
+                std::thread::sleep(Duration::from_millis(
+                    record.timestamp.saturating_sub(previous),
+                ));
+            }
+            previous = Some(record.timestamp);
+            written += self.write_binary(path.clone(), record.data)?;
+        }
+        Ok(written)
+    }
+
     /// Reads data from the serial port
+    ///
+    /// Without a `timeout` (and no `defaultTimeout` configured) the read
+    /// blocks until data arrives or the port is closed.
     pub fn read(
         &self,
         path: String,
         timeout: Option<u64>,
         size: Option<usize>,
     ) -> Result<String, Error> {
+        let timeout = timeout.or(self.1.default_timeout);
+        loop {
+            let params = serde_json::json!({
+                "path": path,
+                "timeout": timeout.unwrap_or(BLOCKING_READ_SLICE),
+                "size": size.unwrap_or(1024),
+            });
+
+            match self.0.run_mobile_plugin("read", params) {
+                Ok(Value::String(data)) if data.is_empty() && timeout.is_none() => continue,
+                Ok(Value::String(data)) if data.is_empty() => {
+                    let elapsed_ms = timeout.unwrap_or_default();
+                    return Err(Error::Timeout {
+                        message: format!("No data received within {} ms", elapsed_ms),
+                        elapsed_ms,
+                    });
+                }
+                Ok(Value::String(data)) => return Ok(data),
+                Ok(_) => return Err(Error::String("Invalid response format".to_string())),
+                Err(e) => return Err(Error::String(format!("Plugin error: {}", e))),
+            }
+        }
+    }
+
+    /// Reads raw bytes from the serial port
+    ///
+    /// Without a `timeout` (and no `defaultTimeout` configured) the read
+    /// blocks until at least one byte, or `min_size` bytes, arrived or the
+    /// port is closed.
+    pub fn read_binary(
+        &self,
+        path: String,
+        timeout: Option<u64>,
+        size: Option<usize>,
+        min_size: Option<usize>,
+    ) -> Result<Vec<u8>, Error> {
+        let timeout = timeout.or(self.1.default_timeout);
+        let size = size.unwrap_or(1024);
+        let min_size = match timeout {
+            Some(_) => min_size.unwrap_or(0).min(size),
+            None => min_size.unwrap_or(1).max(1).min(size),
+        };
+        let started = std::time::Instant::now();
+        let deadline = timeout.map(|timeout| started + Duration::from_millis(timeout));
+
+        // With `min_size`, read again until enough arrived or the time is up
+        let mut data =
+            self.read_binary_once(path.clone(), timeout.unwrap_or(BLOCKING_READ_SLICE), size)?;
+        while data.len() < min_size {
+            let remaining = match deadline {
+                Some(deadline) => deadline.saturating_duration_since(std::time::Instant::now()),
+                None => Duration::from_millis(BLOCKING_READ_SLICE),
+            };
+            if remaining.is_zero() {
+                break;
+            }
+            let chunk = self.read_binary_once(
+                path.clone(),
+                remaining.as_millis() as u64,
+                size - data.len(),
+            )?;
+            data.extend(chunk);
+        }
+
+        if data.is_empty() {
+            let elapsed_ms = started.elapsed().as_millis() as u64;
+            return Err(Error::Timeout {
+                message: format!("No data received within {} ms", elapsed_ms),
+                elapsed_ms,
+            });
+        }
+        Ok(data)
+    }
+
+    fn read_binary_once(&self, path: String, timeout: u64, size: usize) -> Result<Vec<u8>, Error> {
         let params = serde_json::json!({
             "path": path,
-            "timeout": timeout.unwrap_or(1000),
-            "size": size.unwrap_or(1024),
+            "timeout": timeout,
+            "size": size,
         });
 
-        match self.0.run_mobile_plugin("read", params) {
-            Ok(Value::String(data)) => Ok(data),
+        match self.0.run_mobile_plugin("readBinary", params) {
+            Ok(Value::Object(result)) => {
+                let response: ReadBinaryResponse = serde_json::from_value(Value::Object(result))
+                    .map_err(|e| Error::String(format!("Failed to parse data: {}", e)))?;
+                base64::engine::general_purpose::STANDARD
+                    .decode(response.data)
+                    .map_err(|e| Error::String(format!("Failed to decode data: {}", e)))
+            }
             Ok(_) => Err(Error::String("Invalid response format".to_string())),
             Err(e) => Err(Error::String(format!("Plugin error: {}", e))),
         }
     }
 
+    /// Read bytes and return them as uppercase hex separated by spaces
+    pub fn read_hex(
+        &self,
+        path: String,
+        timeout: Option<u64>,
+        size: Option<usize>,
+    ) -> Result<String, Error> {
+        self.read_binary(path, timeout, size, None)
+            .map(|data| crate::hex::to_hex(&data))
+    }
+
+    /// Read exactly `size` bytes, for protocols that know how long a message is
+    ///
+    /// Waits up to `timeout` milliseconds for all of them. If only some
+    /// arrived the read fails with `Error::PartialRead`, which carries those
+    /// bytes; if none did, with `Error::Timeout`.
+    pub fn read_exact_or_partial(
+        &self,
+        path: String,
+        size: usize,
+        timeout: Option<u64>,
+    ) -> Result<Vec<u8>, Error> {
+        let data = self.read_binary(path, timeout, Some(size), Some(size))?;
+        if data.len() < size {
+            return Err(Error::PartialRead {
+                data,
+                expected: size,
+            });
+        }
+        Ok(data)
+    }
+
+    /// Read once and return the data both as bytes and as text
+    ///
+    /// For showing the same data in a hex and a text view: calling `read` and
+    /// `read_binary` one after the other would split it between the two. The
+    /// text is decoded like `read` does, as lossy UTF-8.
+    pub fn read_both(
+        &self,
+        path: String,
+        timeout: Option<u64>,
+        size: Option<usize>,
+    ) -> Result<BytesAndText, Error> {
+        let bytes = self.read_binary(path, timeout, size, None)?;
+        let text = String::from_utf8_lossy(&bytes).into_owned();
+        Ok(BytesAndText { bytes, text })
+    }
+
+    /// Reads whatever the driver has buffered, up to `max_size` bytes, or
+    /// returns an empty result when there is nothing
+    pub fn read_available(&self, path: String, max_size: usize) -> Result<Vec<u8>, Error> {
+        let pending = self.bytes_to_read(path.clone())? as usize;
+        if pending == 0 || max_size == 0 {
+            return Ok(Vec::new());
+        }
+        // The bytes are already there, so the timeout is never waited out
+        match self.read_binary(path, Some(1), Some(pending.min(max_size)), None) {
+            Err(Error::Timeout { .. }) => Ok(Vec::new()),
+            result => result,
+        }
+    }
+
+    /// Request/response transactions are only available on desktop
+    pub fn transaction(
+        &self,
+        _path: String,
+        _request: Vec<u8>,
+        _read_size: usize,
+        _response_timeout: u64,
+    ) -> Result<Vec<u8>, Error> {
+        Err(Error::String(
+            "Transactions are not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Peeking at buffered input is only available on desktop
+    pub fn peek(&self, _path: String, _size: usize) -> Result<Vec<u8>, Error> {
+        Err(Error::String("Peek is not supported on mobile".to_string()))
+    }
+
     /// Starts listening for data on the serial port
     pub fn start_listening(
         &self,
         path: String,
         timeout: Option<u64>,
         size: Option<usize>,
+        _base64: Option<bool>,
+        _buffer_reads: Option<bool>,
+        _idle_timeout: Option<u64>,
+        _framing: Option<Framing>,
+        _emit_strategy: Option<EmitStrategy>,
+        _max_buffer_size: Option<usize>,
+        decode: Option<bool>,
     ) -> Result<(), Error> {
+        // Mobile emits `plugin-serialplugin-read-*` only, so a caller asking
+        // for the text or bytes event would never receive anything
+        if decode.is_some() {
+            return Err(Error::Unsupported(
+                "The decode option is not supported on mobile".to_string(),
+            ));
+        }
+
         let params = serde_json::json!({ "path": path, "timeout": timeout, "size": size });
         let response: MobileResponse<bool> = self.0.run_mobile_plugin("startListening", params)?;
         match response.data {
@@ -212,6 +664,39 @@ impl<R: Runtime> SerialPort<R> {
         }
     }
 
+    /// Make an in-flight `drain` on `path` return `Error::Cancelled`; reads
+    /// and writes can only be cancelled on desktop
+    pub fn cancel_operation(&self, path: String) -> Result<(), Error> {
+        self.cancel_flag(&path)?.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    /// The cancel flag for `drain` on `path`
+    fn cancel_flag(&self, path: &str) -> Result<Arc<AtomicBool>, Error> {
+        let mut cancel_flags = self
+            .2
+            .lock()
+            .map_err(|e| Error::String(format!("Failed to acquire lock: {}", e)))?;
+        Ok(cancel_flags
+            .entry(path.to_string())
+            .or_insert_with(|| Arc::new(AtomicBool::new(false)))
+            .clone())
+    }
+
+    /// Pausing a listener in place is only available on desktop
+    pub fn pause_listening(&self, _path: String, _stop_reading: bool) -> Result<(), Error> {
+        Err(Error::String(
+            "Pausing a listener is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Pausing a listener in place is only available on desktop
+    pub fn resume_listening(&self, _path: String) -> Result<(), Error> {
+        Err(Error::String(
+            "Pausing a listener is not supported on mobile".to_string(),
+        ))
+    }
+
     /// Stops listening for data on the serial port
     pub fn stop_listening(&self, path: String) -> Result<(), Error> {
         let params = serde_json::json!({ "path": path });
@@ -226,15 +711,30 @@ impl<R: Runtime> SerialPort<R> {
         }
     }
 
+    /// RS485 half-duplex mode is only available on desktop
+    pub fn set_rs485(&self, _path: String, _config: Option<Rs485Config>) -> Result<(), Error> {
+        Err(Error::String(
+            "RS485 mode is not supported on mobile".to_string(),
+        ))
+    }
+
     /// Sets the baud rate for the serial port
-    pub fn set_baud_rate(&self, path: String, baud_rate: u32) -> Result<(), Error> {
+    ///
+    /// Android doesn't read the rate back, so the requested one is returned.
+    pub fn set_baud_rate(&self, path: String, baud_rate: u32) -> Result<u32, Error> {
+        if baud_rate == 0 {
+            return Err(Error::InvalidInput(
+                "Baud rate must be greater than 0".to_string(),
+            ));
+        }
+
         let params = serde_json::json!({
             "path": path,
             "baudRate": baud_rate,
         });
 
         match self.0.run_mobile_plugin("setBaudRate", params) {
-            Ok(Value::Bool(true)) => Ok(()),
+            Ok(Value::Bool(true)) => Ok(baud_rate),
             Ok(_) => Err(Error::String("Failed to set baud rate".to_string())),
             Err(e) => Err(Error::String(format!("Plugin error: {}", e))),
         }
@@ -268,6 +768,20 @@ impl<R: Runtime> SerialPort<R> {
         }
     }
 
+    /// Custom XON/XOFF bytes are only available on desktop
+    pub fn set_flow_control_chars(&self, _path: String, _xon: u8, _xoff: u8) -> Result<(), Error> {
+        Err(Error::Unsupported(
+            "Custom XON/XOFF bytes are not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Custom XON/XOFF bytes are only available on desktop
+    pub fn get_flow_control_chars(&self, _path: String) -> Result<FlowControlChars, Error> {
+        Err(Error::String(
+            "Custom XON/XOFF bytes are not supported on mobile".to_string(),
+        ))
+    }
+
     /// Sets the parity for the serial port
     pub fn set_parity(&self, path: String, parity: Parity) -> Result<(), Error> {
         let params = serde_json::json!({
@@ -310,6 +824,58 @@ impl<R: Runtime> SerialPort<R> {
         }
     }
 
+    /// Separate read and write timeouts are only available on desktop
+    pub fn set_read_timeout(&self, _path: String, _timeout: Duration) -> Result<(), Error> {
+        Err(Error::String(
+            "Separate read timeouts are not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Separate read and write timeouts are only available on desktop
+    pub fn set_write_timeout(&self, _path: String, _timeout: Duration) -> Result<(), Error> {
+        Err(Error::String(
+            "Separate write timeouts are not supported on mobile".to_string(),
+        ))
+    }
+
+    /// The software CTS handshake is only available on desktop
+    pub fn set_wait_for_cts(
+        &self,
+        _path: String,
+        _enabled: bool,
+        _timeout: Option<u64>,
+    ) -> Result<(), Error> {
+        Err(Error::String(
+            "Waiting for CTS is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Pacing writes byte by byte is only available on desktop
+    pub fn set_inter_byte_delay(&self, _path: String, _delay_ms: u64) -> Result<(), Error> {
+        Err(Error::String(
+            "Inter-byte delays are not supported on mobile".to_string(),
+        ))
+    }
+
+    /// The mobile API doesn't log yet, so there is nothing to filter
+    pub fn set_port_log_level(&self, _path: String, _level: Option<LogLevel>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Write events are only available on desktop
+    pub fn set_write_events(&self, _path: String, _enabled: bool) -> Result<(), Error> {
+        Err(Error::String(
+            "Write events are not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Chunked writes with progress events are only available on desktop
+    pub fn set_write_chunk_size(&self, _path: String, _chunk_size: usize) -> Result<(), Error> {
+        Err(Error::String(
+            "Chunked writes are not supported on mobile".to_string(),
+        ))
+    }
+
     /// Sets the RTS (Request To Send) signal
     pub fn write_request_to_send(&self, path: String, level: bool) -> Result<(), Error> {
         let params = serde_json::json!({
@@ -338,14 +904,62 @@ impl<R: Runtime> SerialPort<R> {
         }
     }
 
+    /// Sets DTR and then RTS, leaving a line unchanged when its level is `None`
+    pub fn set_control_lines(
+        &self,
+        path: String,
+        dtr: Option<bool>,
+        rts: Option<bool>,
+    ) -> Result<(), Error> {
+        if let Some(dtr) = dtr {
+            self.write_data_terminal_ready(path.clone(), dtr)?;
+        }
+        if let Some(rts) = rts {
+            self.write_request_to_send(path, rts)?;
+        }
+        Ok(())
+    }
+
+    /// Resets an Arduino-style board by pulsing DTR low, then high again
+    pub fn reset_via_dtr(&self, path: String, pulse_ms: u64) -> Result<(), Error> {
+        self.write_data_terminal_ready(path.clone(), false)?;
+        std::thread::sleep(Duration::from_millis(pulse_ms));
+        self.write_data_terminal_ready(path, true)
+    }
+
+    /// Resets an ESP32/ESP8266 into its serial bootloader with esptool's classic
+    /// reset, RTS driving EN and DTR driving GPIO0
+    pub fn enter_esp_bootloader(&self, path: String) -> Result<(), Error> {
+        self.set_control_lines(path.clone(), Some(false), Some(true))?;
+        std::thread::sleep(ESP_RESET_PULSE);
+        self.set_control_lines(path.clone(), Some(true), Some(false))?;
+        std::thread::sleep(ESP_BOOT_DELAY);
+        self.write_data_terminal_ready(path, false)
+    }
+
+    /// Resets an ESP32/ESP8266 into its normal firmware by pulsing EN through RTS
+    pub fn reset_esp(&self, path: String) -> Result<(), Error> {
+        self.set_control_lines(path.clone(), Some(false), Some(true))?;
+        std::thread::sleep(ESP_RESET_PULSE);
+        self.write_request_to_send(path, false)
+    }
+
+    /// The 1200bps touch requires reopening the device, which is only available on desktop
+    pub fn trigger_bootloader_1200(&self, _path: String) -> Result<(), Error> {
+        Err(Error::String(
+            "1200bps bootloader touch is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Cancels a read waiting for data on the port
     pub fn cancel_read(&self, path: String) -> Result<(), Error> {
         let params = serde_json::json!({
             "path": path,
         });
 
-        match self.0.run_mobile_plugin("cancelRead", params) {
-            Ok(Value::Bool(true)) => Ok(()),
-            Ok(_) => Err(Error::String("Failed to cancel read".to_string())),
+        // Failures are reported by rejecting the call, there is no result value
+        match self.0.run_mobile_plugin::<Value>("cancelRead", params) {
+            Ok(_) => Ok(()),
             Err(e) => Err(Error::String(format!("Plugin error: {}", e))),
         }
     }
@@ -390,12 +1004,71 @@ impl<R: Runtime> SerialPort<R> {
         }
     }
 
+    /// Reads CTS, DSR, RI and CD, one plugin call each
+    pub fn get_modem_status(&self, path: String) -> Result<ModemStatus, Error> {
+        Ok(ModemStatus {
+            cts: self.read_clear_to_send(path.clone())?,
+            dsr: self.read_data_set_ready(path.clone())?,
+            ri: self.read_ring_indicator(path.clone())?,
+            cd: self.read_carrier_detect(path)?,
+        })
+    }
+
+    /// The heartbeat writer is only available on desktop
+    pub fn start_heartbeat(
+        &self,
+        _path: String,
+        _data: Vec<u8>,
+        _interval: u64,
+    ) -> Result<(), Error> {
+        Err(Error::String(
+            "Heartbeat is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// The heartbeat writer is only available on desktop
+    pub fn stop_heartbeat(&self, _path: String) -> Result<(), Error> {
+        Err(Error::String(
+            "Heartbeat is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// The idle watchdog is only available on desktop
+    pub fn idle_auto_close(&self, _path: String, _timeout: Option<u64>) -> Result<(), Error> {
+        Err(Error::String(
+            "Idle auto-close is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Modem signal watching is only available on desktop
+    pub fn watch_modem_signals(&self, _path: String, _poll_interval: u64) -> Result<(), Error> {
+        Err(Error::String(
+            "Watching modem signals is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Modem signal watching is only available on desktop
+    pub fn stop_watching_modem_signals(&self, _path: String) -> Result<(), Error> {
+        Err(Error::String(
+            "Watching modem signals is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Line error counters are only available on desktop
+    pub fn get_line_errors(&self, _path: String) -> Result<LineErrors, Error> {
+        Err(Error::String(
+            "Line error counters are not supported on mobile".to_string(),
+        ))
+    }
+
     /// Gets the number of bytes available to read
     pub fn bytes_to_read(&self, path: String) -> Result<u32, Error> {
         let params = serde_json::json!({ "path": path });
-        match self.0.run_mobile_plugin("bytesToRead", params) {
-            Ok(Value::Number(n)) => Ok(n.as_u64().unwrap_or(0) as u32),
-            Ok(_) => Err(Error::String("Invalid response format".to_string())),
+        match self
+            .0
+            .run_mobile_plugin::<BytesResponse>("bytesToRead", params)
+        {
+            Ok(response) => Ok(response.bytes),
             Err(e) => Err(Error::String(format!("Plugin error: {}", e))),
         }
     }
@@ -403,23 +1076,51 @@ impl<R: Runtime> SerialPort<R> {
     /// Gets the number of bytes waiting to be written
     pub fn bytes_to_write(&self, path: String) -> Result<u32, Error> {
         let params = serde_json::json!({ "path": path });
-        match self.0.run_mobile_plugin("bytesToWrite", params) {
-            Ok(Value::Number(n)) => Ok(n.as_u64().unwrap_or(0) as u32),
-            Ok(_) => Err(Error::String("Invalid response format".to_string())),
+        match self
+            .0
+            .run_mobile_plugin::<BytesResponse>("bytesToWrite", params)
+        {
+            Ok(response) => Ok(response.bytes),
             Err(e) => Err(Error::String(format!("Plugin error: {}", e))),
         }
     }
 
-    /// Clears the specified buffer
-    pub fn clear_buffer(&self, path: String, buffer_type: ClearBuffer) -> Result<(), Error> {
+    /// Waits until the driver reports no bytes left to write
+    ///
+    /// USB writes on Android complete once the adapter has accepted the data,
+    /// so this only covers what the driver still queues, not the adapter's FIFO.
+    /// `cancel_operation` ends the wait.
+    pub fn drain(&self, path: String) -> Result<(), Error> {
+        let cancel = self.cancel_flag(&path)?;
+        cancel.store(false, Ordering::Relaxed);
+
+        while self.bytes_to_write(path.clone())? > 0 {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(Error::Cancelled("Drain was cancelled".to_string()));
+            }
+            std::thread::sleep(DRAIN_POLL_INTERVAL);
+        }
+        Ok(())
+    }
+
+    /// Clears the specified buffer, returning how many bytes were discarded
+    pub fn clear_buffer(&self, path: String, buffer_type: ClearBuffer) -> Result<u32, Error> {
+        let mut discarded = 0;
+        if matches!(buffer_type, ClearBuffer::Input | ClearBuffer::All) {
+            discarded += self.bytes_to_read(path.clone())?;
+        }
+        if matches!(buffer_type, ClearBuffer::Output | ClearBuffer::All) {
+            discarded += self.bytes_to_write(path.clone())?;
+        }
+
         let params = serde_json::json!({
             "path": path,
             "bufferType": buffer_type,
         });
 
-        match self.0.run_mobile_plugin("clearBuffer", params) {
-            Ok(Value::Bool(true)) => Ok(()),
-            Ok(_) => Err(Error::String("Failed to clear buffer".to_string())),
+        // Failures are reported by rejecting the call, there is no result value
+        match self.0.run_mobile_plugin::<Value>("clearBuffer", params) {
+            Ok(_) => Ok(discarded),
             Err(e) => Err(Error::String(format!("Plugin error: {}", e))),
         }
     }
@@ -443,4 +1144,85 @@ impl<R: Runtime> SerialPort<R> {
             Err(e) => Err(Error::String(format!("Plugin error: {}", e))),
         }
     }
+
+    /// Transmits a break signal for `duration_ms` milliseconds
+    pub fn send_break(&self, path: String, duration_ms: u64) -> Result<(), Error> {
+        self.set_break(path.clone())?;
+        std::thread::sleep(Duration::from_millis(duration_ms));
+        self.clear_break(path)
+    }
+
+    /// Traffic capture is only available on desktop
+    pub fn start_capture(
+        &self,
+        _path: String,
+        _file_path: String,
+        _format: CaptureFormat,
+    ) -> Result<(), Error> {
+        Err(Error::String(
+            "Traffic capture is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Traffic capture is only available on desktop
+    pub fn stop_capture(&self, _path: String) -> Result<(), Error> {
+        Err(Error::String(
+            "Traffic capture is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Whether the plugin has the port at `path` open
+    pub fn is_open(&self, path: String) -> Result<bool, Error> {
+        Ok(self.get_port_state(path)?.open)
+    }
+
+    /// Listing the open ports is only available on desktop
+    pub fn managed_ports(&self) -> Result<Vec<String>, Error> {
+        Err(Error::String(
+            "managed_ports is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Listing the open ports is only available on desktop
+    pub fn managed_ports_detailed(&self) -> Result<HashMap<String, ManagedPortInfo>, Error> {
+        Err(Error::String(
+            "managed_ports is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Line settings aren't reported on mobile
+    pub fn export_config(&self, _path: String) -> Result<PortConfig, Error> {
+        Err(Error::String(
+            "export_config is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Whether the port is open and whether a listener runs for it
+    pub fn get_port_state(&self, path: String) -> Result<PortState, Error> {
+        let params = serde_json::json!({ "path": path });
+        self.0
+            .run_mobile_plugin::<PortState>("getPortState", params)
+            .map_err(|e| Error::String(format!("Plugin error: {}", e)))
+    }
+
+    /// Port capabilities are only available on desktop
+    pub fn get_port_capabilities(&self, _path: String) -> Result<PortCapabilities, Error> {
+        Err(Error::String(
+            "get_port_capabilities is not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Traffic statistics are only available on desktop
+    pub fn get_stats(&self, _path: String) -> Result<PortStatsSnapshot, Error> {
+        Err(Error::String(
+            "Traffic statistics are not supported on mobile".to_string(),
+        ))
+    }
+
+    /// Traffic statistics are only available on desktop
+    pub fn reset_stats(&self, _path: String) -> Result<(), Error> {
+        Err(Error::String(
+            "Traffic statistics are not supported on mobile".to_string(),
+        ))
+    }
 }
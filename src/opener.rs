@@ -0,0 +1,167 @@
+//! The backend that opens and lists ports on desktop
+//!
+//! `SystemPortOpener` talks to real devices through the `serialport` crate.
+//! A different `PortOpener` can be installed with `SerialPort::set_port_opener`
+//! to back the plugin with virtual devices or network-bridged ports.
+
+use crate::state::RawPortHandle;
+use serialport::{DataBits, FlowControl, Parity, StopBits};
+use std::time::Duration;
+
+/// Line settings a port is opened with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PortSettings {
+    pub baud_rate: u32,
+    pub data_bits: DataBits,
+    pub flow_control: FlowControl,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    /// Timeout for reads and writes
+    pub timeout: Duration,
+    /// Lock the device against other openers (`TIOCEXCL` on Unix); Windows
+    /// always opens COM ports exclusively
+    pub exclusive: bool,
+    /// Level DTR is driven to as part of opening, before any data; `None`
+    /// leaves it as the driver sets it. The plugin applies it to whatever
+    /// port the `PortOpener` returns.
+    ///
+    /// Linux raises DTR and RTS while opening a tty, so they can still pulse
+    /// briefly before the requested levels are applied.
+    pub initial_dtr: Option<bool>,
+    /// Level RTS is driven to as part of opening, like `initial_dtr`
+    pub initial_rts: Option<bool>,
+}
+
+impl PortSettings {
+    /// 8N1 without flow control at `baud_rate`, with the plugin's default timeout
+    pub fn new(baud_rate: u32) -> Self {
+        Self {
+            baud_rate,
+            data_bits: DataBits::Eight,
+            flow_control: FlowControl::None,
+            parity: Parity::None,
+            stop_bits: StopBits::One,
+            timeout: Duration::from_millis(200),
+            exclusive: true,
+            initial_dtr: None,
+            initial_rts: None,
+        }
+    }
+}
+
+/// Opens and enumerates the ports the plugin works with
+pub trait PortOpener: Send + Sync {
+    /// Open the port at `path`
+    ///
+    /// The plugin drives DTR and RTS to `initial_dtr` and `initial_rts` on the
+    /// port returned, before using it, so an opener can leave them alone.
+    fn open(
+        &self,
+        path: &str,
+        settings: &PortSettings,
+    ) -> serialport::Result<Box<dyn serialport::SerialPort>>;
+
+    /// Open the port at `path`, along with its OS handle if it has one
+    ///
+    /// The handle is what `SerialPort::with_raw_handle` hands out. By default
+    /// the port is opened with `open` and has no handle.
+    fn open_with_handle(
+        &self,
+        path: &str,
+        settings: &PortSettings,
+    ) -> serialport::Result<(Box<dyn serialport::SerialPort>, Option<RawPortHandle>)> {
+        Ok((self.open(path, settings)?, None))
+    }
+
+    /// List the ports that currently exist
+    fn enumerate(&self) -> serialport::Result<Vec<serialport::SerialPortInfo>>;
+}
+
+/// The default backend, using the OS serial ports
+///
+/// With the `loopback` feature it also opens `loopback://` paths, and with the
+/// `tcp` feature `tcp://` and `rfc2217://` ones.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemPortOpener;
+
+impl PortOpener for SystemPortOpener {
+    fn open(
+        &self,
+        path: &str,
+        settings: &PortSettings,
+    ) -> serialport::Result<Box<dyn serialport::SerialPort>> {
+        self.open_with_handle(path, settings).map(|(port, _)| port)
+    }
+
+    fn open_with_handle(
+        &self,
+        path: &str,
+        settings: &PortSettings,
+    ) -> serialport::Result<(Box<dyn serialport::SerialPort>, Option<RawPortHandle>)> {
+        #[cfg(feature = "loopback")]
+        if path.starts_with(crate::loopback::SCHEME) {
+            let port = crate::loopback::LoopbackPort::open(path, settings)?;
+            return Ok((Box::new(port), None));
+        }
+
+        #[cfg(feature = "tcp")]
+        if path.starts_with(crate::tcp::TCP_SCHEME) || path.starts_with(crate::tcp::RFC2217_SCHEME)
+        {
+            let port = crate::tcp::TcpPort::open(path, settings)?;
+            return Ok((Box::new(port), None));
+        }
+
+        let builder = serialport::new(path, settings.baud_rate)
+            .data_bits(settings.data_bits)
+            .flow_control(settings.flow_control)
+            .parity(settings.parity)
+            .stop_bits(settings.stop_bits)
+            .timeout(settings.timeout);
+
+        // `serialport` takes the TIOCEXCL lock itself, so it only needs releasing
+        #[cfg(unix)]
+        {
+            use std::os::unix::io::AsRawFd;
+
+            let mut port = builder.open_native()?;
+            port.set_exclusive(settings.exclusive)?;
+            let handle = RawPortHandle {
+                fd: port.as_raw_fd(),
+            };
+            Ok((Box::new(port), Some(handle)))
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::io::AsRawHandle;
+
+            let port = builder.open_native()?;
+            let handle = RawPortHandle {
+                handle: port.as_raw_handle(),
+            };
+            Ok((Box::new(port), Some(handle)))
+        }
+        #[cfg(not(any(unix, windows)))]
+        {
+            Ok((builder.open()?, None))
+        }
+    }
+
+    fn enumerate(&self) -> serialport::Result<Vec<serialport::SerialPortInfo>> {
+        serialport::available_ports()
+    }
+}
+
+/// Drive DTR and RTS to the levels in `settings` on a port that was just
+/// opened, so the device never sees data with the lines in another state
+pub(crate) fn set_initial_lines(
+    port: &mut dyn serialport::SerialPort,
+    settings: &PortSettings,
+) -> serialport::Result<()> {
+    if let Some(level) = settings.initial_dtr {
+        port.write_data_terminal_ready(level)?;
+    }
+    if let Some(level) = settings.initial_rts {
+        port.write_request_to_send(level)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,78 @@
+//! RS485 direction control done by the serial driver
+//!
+//! Linux drivers with RS485 support toggle RTS around each transmission
+//! themselves once configured with `TIOCSRS485`, timed by the UART instead of
+//! by polling the output queue, so the bus is released right after the last
+//! stop bit. Other platforms, drivers without it and virtual ports leave it to
+//! the plugin, which toggles RTS in software around every write.
+
+use crate::error::Error;
+use crate::state::{RawPortHandle, Rs485Config};
+
+/// Longest `delay_before_tx`/`delay_after_tx` the driver applies; Linux
+/// clamps longer delays, so those are timed in software instead
+pub const MAX_DRIVER_DELAY_MS: u64 = 100;
+
+/// Whether the driver can time `config` exactly
+pub fn fits_driver(config: &Rs485Config) -> bool {
+    config.delay_before_tx <= MAX_DRIVER_DELAY_MS && config.delay_after_tx <= MAX_DRIVER_DELAY_MS
+}
+
+/// Hand RS485 direction control to the driver of the port with `handle`, or
+/// take it back with `None`
+///
+/// Returns whether the driver is in RS485 mode now: `false` for a driver
+/// without RS485 support or a port without an OS handle.
+#[cfg(target_os = "linux")]
+pub fn set(handle: Option<RawPortHandle>, config: Option<&Rs485Config>) -> Result<bool, Error> {
+    use std::io;
+
+    /// `struct serial_rs485` from `<linux/serial.h>`
+    #[repr(C)]
+    #[derive(Default)]
+    struct SerialRs485 {
+        flags: u32,
+        delay_rts_before_send: u32,
+        delay_rts_after_send: u32,
+        padding: [u32; 5],
+    }
+
+    const SER_RS485_ENABLED: u32 = 1 << 0;
+    const SER_RS485_RTS_ON_SEND: u32 = 1 << 1;
+    const SER_RS485_RTS_AFTER_SEND: u32 = 1 << 2;
+
+    let fd = match handle {
+        Some(handle) => handle.fd,
+        None => return Ok(false),
+    };
+
+    let mut rs485 = SerialRs485::default();
+    if let Some(config) = config {
+        rs485.flags = SER_RS485_ENABLED
+            | if config.rts_active_high {
+                SER_RS485_RTS_ON_SEND
+            } else {
+                SER_RS485_RTS_AFTER_SEND
+            };
+        rs485.delay_rts_before_send = config.delay_before_tx.min(MAX_DRIVER_DELAY_MS) as u32;
+        rs485.delay_rts_after_send = config.delay_after_tx.min(MAX_DRIVER_DELAY_MS) as u32;
+    }
+
+    // SAFETY: `rs485` matches the layout the kernel reads for TIOCSRS485
+    let result = unsafe { libc::ioctl(fd, libc::TIOCSRS485, &rs485) };
+    if result < 0 {
+        let e = io::Error::last_os_error();
+        // Drivers without RS485 support don't know the ioctl
+        if matches!(e.raw_os_error(), Some(libc::ENOTTY | libc::EINVAL)) {
+            return Ok(false);
+        }
+        return Err(e.into());
+    }
+    Ok(config.is_some())
+}
+
+/// Only the Linux drivers take over RS485 direction control
+#[cfg(not(target_os = "linux"))]
+pub fn set(_handle: Option<RawPortHandle>, _config: Option<&Rs485Config>) -> Result<bool, Error> {
+    Ok(false)
+}
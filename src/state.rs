@@ -1,13 +1,20 @@
+use crate::error::Error;
+use crate::logger::PortLogLevel;
 use serde::{Deserialize, Serialize};
 use serialport::{self, SerialPort};
 use serialport::{
     ClearBuffer as SerialClearBuffer, DataBits as SerialDataBits, FlowControl as SerialFlowControl,
     Parity as SerialParity, StopBits as SerialStopBits,
 };
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
 use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use std::{
-    collections::HashMap,
-    sync::{mpsc::Sender, Arc, Mutex},
+    collections::{HashMap, VecDeque},
+    sync::{mpsc::Sender, Arc, Condvar, Mutex},
 };
 
 #[derive(Default)]
@@ -19,6 +26,448 @@ pub struct SerialportInfo {
     pub serialport: Box<dyn SerialPort>,
     pub sender: Option<Sender<usize>>,
     pub thread_handle: Option<JoinHandle<()>>,
+    /// `start_listening` calls not yet balanced by `stop_listening`; the
+    /// listener runs until the last of them stops it
+    pub listeners: usize,
+    /// `decode` option of the running listener; it picks the read event name,
+    /// so callers sharing the listener must pass the same
+    pub listen_decode: Option<bool>,
+    pub rs485: Option<Rs485Config>,
+    /// Whether the driver toggles RTS for `rs485` itself (`TIOCSRS485`), so
+    /// writes leave it alone
+    pub rs485_driver: bool,
+    pub signal_sender: Option<Sender<usize>>,
+    pub signal_thread: Option<JoinHandle<()>>,
+    /// Bytes already read from the device by `peek` but not yet consumed
+    pub holdback: Vec<u8>,
+    /// `ListenState` of the listener thread, shared so it can be paused in place
+    pub listen_state: Arc<AtomicU8>,
+    /// Copy of the listener's data that `read` takes from instead of the device
+    pub listen_queue: Option<Arc<ListenQueue>>,
+    /// The handle the listener reads from, empty while `reconfigure` reopens
+    /// the device
+    pub listener_port: Option<ListenerPort>,
+    pub heartbeat_sender: Option<Sender<usize>>,
+    pub heartbeat_thread: Option<JoinHandle<()>>,
+    /// Stop signal and thread of the `idle_auto_close` watchdog
+    pub idle_sender: Option<Sender<usize>>,
+    pub idle_thread: Option<JoinHandle<()>>,
+    /// Rust-side consumers of the listener's data, see `SerialPort::subscribe`
+    pub subscribers: Arc<Mutex<Vec<Sender<Vec<u8>>>>>,
+    /// Timeout for reads, overriding the port timeout when set
+    pub read_timeout: Option<Duration>,
+    /// Timeout for writes, overriding the port timeout when set
+    pub write_timeout: Option<Duration>,
+    /// Hold writes until CTS is asserted, failing after this long
+    pub wait_for_cts: Option<Duration>,
+    /// Pause between bytes for devices with tiny receive FIFOs
+    pub inter_byte_delay: Option<Duration>,
+    /// Emit `plugin-serialplugin-write-complete-*` after each write
+    pub write_events: bool,
+    /// Split writes into flushed chunks of this many bytes, reporting progress
+    pub write_chunk_size: Option<usize>,
+    /// Traffic counters, shared with the listener thread
+    pub stats: Arc<PortStats>,
+    /// XON/XOFF bytes chosen for software flow control
+    pub flow_control_chars: FlowControlChars,
+    /// Traffic capture file, shared with the listener thread
+    pub capture: Arc<Mutex<Option<Capture>>>,
+    /// Line settings as last applied, kept up to date by the setters so
+    /// `reconnect` can restore them
+    pub settings: PortConfig,
+    /// Whether the device was opened with the exclusive lock
+    pub exclusive: bool,
+    /// DTR and RTS levels applied when opening, reapplied by `reconnect`
+    pub initial_dtr: Option<bool>,
+    pub initial_rts: Option<bool>,
+    /// OS handle of the device, for `with_raw_handle`; virtual ports have none
+    pub raw_handle: Option<RawPortHandle>,
+    /// Held by a write for as long as it runs on its own clone of the handle
+    /// with the map unlocked, so writes to the port don't interleave
+    pub write_lock: Arc<Mutex<()>>,
+    /// Held by a read running on its own clone of the handle, like `write_lock`
+    pub read_lock: Arc<Mutex<()>>,
+    /// Log level for messages about this port, shared with its threads
+    pub log_level: Arc<PortLogLevel>,
+}
+
+/// Port handle shared between a listener thread and the map
+pub type ListenerPort = Arc<Mutex<Option<Box<dyn SerialPort>>>>;
+
+impl SerialportInfo {
+    /// State for a freshly opened port, with no background threads running
+    pub fn new(serialport: Box<dyn SerialPort>, settings: PortConfig, exclusive: bool) -> Self {
+        Self {
+            serialport,
+            sender: None,
+            thread_handle: None,
+            listeners: 0,
+            listen_decode: None,
+            rs485: None,
+            rs485_driver: false,
+            signal_sender: None,
+            signal_thread: None,
+            holdback: Vec::new(),
+            listen_state: Arc::new(AtomicU8::new(ListenState::Running.as_u8())),
+            listen_queue: None,
+            listener_port: None,
+            heartbeat_sender: None,
+            heartbeat_thread: None,
+            idle_sender: None,
+            idle_thread: None,
+            subscribers: Arc::new(Mutex::new(Vec::new())),
+            read_timeout: None,
+            write_timeout: None,
+            wait_for_cts: None,
+            inter_byte_delay: None,
+            write_events: false,
+            write_chunk_size: None,
+            stats: Arc::new(PortStats::default()),
+            flow_control_chars: FlowControlChars::default(),
+            capture: Arc::new(Mutex::new(None)),
+            settings,
+            exclusive,
+            initial_dtr: None,
+            initial_rts: None,
+            raw_handle: None,
+            write_lock: Arc::new(Mutex::new(())),
+            read_lock: Arc::new(Mutex::new(())),
+            log_level: Arc::new(PortLogLevel::default()),
+        }
+    }
+}
+
+/// OS handle of an open port: a file descriptor on Unix, a `HANDLE` on Windows
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RawPortHandle {
+    #[cfg(unix)]
+    pub fd: std::os::unix::io::RawFd,
+    #[cfg(windows)]
+    pub handle: std::os::windows::io::RawHandle,
+}
+
+// A HANDLE is an opaque value; what may be done with it is up to the caller
+#[cfg(windows)]
+unsafe impl Send for RawPortHandle {}
+#[cfg(windows)]
+unsafe impl Sync for RawPortHandle {}
+
+/// The bytes used for software flow control
+///
+/// Custom bytes are applied to the OS driver; ports without an OS handle only
+/// support the standard values.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlowControlChars {
+    pub xon: u8,
+    pub xoff: u8,
+}
+
+impl FlowControlChars {
+    /// DC1, the standard XON
+    pub const XON: u8 = 0x11;
+    /// DC3, the standard XOFF
+    pub const XOFF: u8 = 0x13;
+
+    /// Whether these are the standard bytes the OS uses
+    pub fn is_standard(&self) -> bool {
+        self.xon == Self::XON && self.xoff == Self::XOFF
+    }
+}
+
+impl Default for FlowControlChars {
+    fn default() -> Self {
+        Self {
+            xon: Self::XON,
+            xoff: Self::XOFF,
+        }
+    }
+}
+
+/// Traffic counters of a port, see `SerialPort::get_stats`
+#[derive(Default)]
+pub struct PortStats {
+    pub bytes_read: AtomicU64,
+    pub bytes_written: AtomicU64,
+    pub read_errors: AtomicU64,
+    /// Milliseconds since the Unix epoch of the last read or write, 0 if none yet
+    pub last_activity: AtomicU64,
+}
+
+impl PortStats {
+    pub fn record_read(&self, n: usize) {
+        self.bytes_read.fetch_add(n as u64, Ordering::Relaxed);
+        self.touch();
+    }
+
+    pub fn record_write(&self, n: usize) {
+        self.bytes_written.fetch_add(n as u64, Ordering::Relaxed);
+        self.touch();
+    }
+
+    pub fn record_read_error(&self) {
+        self.read_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn reset(&self) {
+        self.bytes_read.store(0, Ordering::Relaxed);
+        self.bytes_written.store(0, Ordering::Relaxed);
+        self.read_errors.store(0, Ordering::Relaxed);
+        self.last_activity.store(0, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> PortStatsSnapshot {
+        let last_activity = self.last_activity.load(Ordering::Relaxed);
+        PortStatsSnapshot {
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            read_errors: self.read_errors.load(Ordering::Relaxed),
+            last_activity: if last_activity == 0 {
+                None
+            } else {
+                Some(last_activity)
+            },
+        }
+    }
+
+    fn touch(&self) {
+        self.last_activity.store(unix_millis(), Ordering::Relaxed);
+    }
+}
+
+/// Milliseconds since the Unix epoch
+pub(crate) fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// File format written by `start_capture`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CaptureFormat {
+    /// One text line per chunk: `<unix ms> <TX|RX> <hex bytes>`
+    Hexdump,
+    /// Binary records: unix ms (u64 BE), `b'T'` or `b'R'`, length (u32 BE), then the bytes
+    Raw,
+}
+
+/// Whether captured bytes were sent or received
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CaptureDirection {
+    Tx,
+    Rx,
+}
+
+/// One chunk of traffic read back from a capture file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaptureRecord {
+    /// Milliseconds since the Unix epoch
+    pub timestamp: u64,
+    pub direction: CaptureDirection,
+    pub data: Vec<u8>,
+}
+
+/// Read every record of a file written by `start_capture`, in either format
+///
+/// Raw files start with the high byte of a timestamp, which is always zero,
+/// while hexdump lines start with a digit, so the format is detected from the
+/// first byte.
+pub fn read_capture(path: impl AsRef<Path>) -> Result<Vec<CaptureRecord>, Error> {
+    let contents = std::fs::read(path)
+        .map_err(|e| Error::String(format!("Failed to read capture file: {}", e)))?;
+    let malformed = |what: &str| Error::InvalidInput(format!("Malformed capture file: {}", what));
+
+    let mut records = Vec::new();
+    if contents.first() == Some(&0) {
+        let mut rest = contents.as_slice();
+        while !rest.is_empty() {
+            if rest.len() < 13 {
+                return Err(malformed("truncated record header"));
+            }
+            let mut timestamp = [0u8; 8];
+            timestamp.copy_from_slice(&rest[..8]);
+            let direction = match rest[8] {
+                b'T' => CaptureDirection::Tx,
+                b'R' => CaptureDirection::Rx,
+                _ => return Err(malformed("unknown direction")),
+            };
+            let mut len = [0u8; 4];
+            len.copy_from_slice(&rest[9..13]);
+            let len = u32::from_be_bytes(len) as usize;
+            if rest.len() < 13 + len {
+                return Err(malformed("truncated record data"));
+            }
+            records.push(CaptureRecord {
+                timestamp: u64::from_be_bytes(timestamp),
+                direction,
+                data: rest[13..13 + len].to_vec(),
+            });
+            rest = &rest[13 + len..];
+        }
+    } else {
+        let text = String::from_utf8(contents).map_err(|_| malformed("not UTF-8"))?;
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let mut parts = line.splitn(3, ' ');
+            let timestamp = parts
+                .next()
+                .and_then(|timestamp| timestamp.parse().ok())
+                .ok_or_else(|| malformed(line))?;
+            let direction = match parts.next() {
+                Some("TX") => CaptureDirection::Tx,
+                Some("RX") => CaptureDirection::Rx,
+                _ => return Err(malformed(line)),
+            };
+            records.push(CaptureRecord {
+                timestamp,
+                direction,
+                data: crate::hex::parse_hex(parts.next().unwrap_or(""))?,
+            });
+        }
+    }
+    Ok(records)
+}
+
+/// An open capture file, see `SerialPort::start_capture`
+pub struct Capture {
+    file: BufWriter<File>,
+    format: CaptureFormat,
+}
+
+impl Capture {
+    /// Create (or truncate) the capture file
+    pub fn create(path: impl AsRef<Path>, format: CaptureFormat) -> io::Result<Self> {
+        Ok(Self {
+            file: BufWriter::new(File::create(path)?),
+            format,
+        })
+    }
+
+    /// Append one chunk of traffic, flushed right away so a crash keeps the trace
+    pub fn record(&mut self, direction: CaptureDirection, data: &[u8]) -> io::Result<()> {
+        let timestamp = unix_millis();
+        match self.format {
+            CaptureFormat::Hexdump => {
+                let marker = match direction {
+                    CaptureDirection::Tx => "TX",
+                    CaptureDirection::Rx => "RX",
+                };
+                writeln!(
+                    self.file,
+                    "{} {} {}",
+                    timestamp,
+                    marker,
+                    crate::hex::to_hex(data)
+                )?;
+            }
+            CaptureFormat::Raw => {
+                let marker = match direction {
+                    CaptureDirection::Tx => b'T',
+                    CaptureDirection::Rx => b'R',
+                };
+                self.file.write_all(&timestamp.to_be_bytes())?;
+                self.file.write_all(&[marker])?;
+                self.file.write_all(&(data.len() as u32).to_be_bytes())?;
+                self.file.write_all(data)?;
+            }
+        }
+        self.file.flush()
+    }
+}
+
+/// Point-in-time copy of `PortStats`, returned by `get_stats`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortStatsSnapshot {
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    pub read_errors: u64,
+    /// Milliseconds since the Unix epoch of the last read or write
+    pub last_activity: Option<u64>,
+}
+
+/// The data of one read in both forms, returned by `read_both`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BytesAndText {
+    pub bytes: Vec<u8>,
+    /// `bytes` decoded as UTF-8, invalid sequences replaced by U+FFFD
+    pub text: String,
+}
+
+/// Line settings of an open port, as currently applied
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortConfig {
+    pub baud_rate: u32,
+    pub data_bits: DataBits,
+    pub flow_control: FlowControl,
+    pub parity: Parity,
+    pub stop_bits: StopBits,
+    /// Port timeout in milliseconds
+    pub timeout: u64,
+}
+
+/// What a port supports as far as the plugin can tell, returned by
+/// `get_port_capabilities`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortCapabilities {
+    /// Standard rates the port is expected to accept, in ascending order;
+    /// only up to 115200 when the device isn't recognized
+    pub baud_rates: Vec<u32>,
+    /// Highest rate of the recognized USB-serial chip
+    pub max_baud_rate: Option<u32>,
+    /// Rates between the standard ones are applied exactly instead of rounded;
+    /// false for a chip that isn't recognized
+    pub custom_baud: bool,
+    /// A break condition can be sent
+    pub break_signal: bool,
+    /// DTR/RTS can be driven and CTS/DSR/RI/CD read
+    pub modem_lines: bool,
+    /// RTS can be toggled around writes for `set_rs485`
+    pub rs485: bool,
+    /// The driver counts errors for `get_line_errors`
+    pub line_errors: bool,
+    /// The recognized USB-serial chip family
+    pub chip: Option<String>,
+}
+
+/// A port the plugin has open, returned by `managed_ports_detailed`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManagedPortInfo {
+    /// Line settings as last applied
+    pub settings: PortConfig,
+    /// The device was opened with the exclusive lock
+    pub exclusive: bool,
+    /// A listener is running for the port
+    pub listening: bool,
+    /// The listener was paused with `pause_listening`
+    pub paused: bool,
+    /// `USB`, `Bluetooth`, `PCI` or `Unknown`, as in `available_ports`
+    #[serde(rename = "type")]
+    pub port_type: String,
+}
+
+/// Returned by `get_port_state`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PortState {
+    pub open: bool,
+    /// A listener is running for the port
+    pub listening: bool,
+    /// The listener was paused with `pause_listening`
+    #[serde(default)]
+    pub paused: bool,
+    /// Line settings while open; not reported on mobile
+    pub config: Option<PortConfig>,
+}
+
+/// Data read by a listener, kept for `read` calls made while it is running
+#[derive(Default)]
+pub struct ListenQueue {
+    pub data: Mutex<VecDeque<u8>>,
+    /// Signalled whenever the listener adds data
+    pub ready: Condvar,
+}
+
+impl ListenQueue {
+    /// Upper bound on queued bytes; the oldest are dropped beyond this
+    pub const CAPACITY: usize = 64 * 1024;
 }
 
 #[derive(Serialize, Clone)]
@@ -31,6 +480,371 @@ pub struct InvokeResult {
 pub struct ReadData<'a> {
     pub data: &'a [u8],
     pub size: usize,
+    /// Position of the event among the listener's events of the same name,
+    /// starting at 0; a gap means an event was dropped
+    pub seq: u64,
+    /// When the data was emitted, in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+}
+
+/// Payload of the `plugin-serialplugin-read-text-*` event, emitted instead of
+/// `ReadData` by a listener started with `decode`
+#[derive(Serialize, Clone)]
+pub struct ReadText {
+    /// The received bytes decoded as UTF-8, with invalid sequences replaced by
+    /// U+FFFD; a character split between reads is delivered whole with the
+    /// later event, and one left incomplete when the listener stops comes out
+    /// as U+FFFD in a last event
+    pub data: String,
+    /// Number of bytes decoded
+    pub size: usize,
+    /// Position of the event in the listener's stream, see `ReadData::seq`
+    pub seq: u64,
+    /// When the data was emitted, in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+}
+
+/// Payload of the `plugin-serialplugin-json-*` event
+#[derive(Serialize, Clone)]
+pub struct JsonData {
+    /// The parsed line
+    pub value: serde_json::Value,
+    /// Position of the event in the listener's stream, see `ReadData::seq`
+    pub seq: u64,
+    /// When the value was emitted, in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+}
+
+/// `ReadData` with the bytes base64-encoded, which is far smaller than a JSON number array
+#[derive(Serialize, Clone)]
+pub struct ReadDataBase64 {
+    pub data: String,
+    pub size: usize,
+    pub seq: u64,
+    pub timestamp_ms: u64,
+}
+
+/// Encode a port path for use in event names
+///
+/// Per-port events are named `plugin-serialplugin-{kind}-{event_path(path)}`.
+/// ASCII letters, digits and `-` are kept; every other byte of the UTF-8 path,
+/// `_` included, becomes `_` followed by two uppercase hex digits, so
+/// `/dev/tty.usb-1` becomes `_2Fdev_2Ftty_2Eusb-1`. The encoding is reversible,
+/// so different paths never share events, and only uses characters Tauri
+/// accepts in event names. The JS `eventPath` helper computes the same string.
+pub fn event_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("_{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// What went wrong in a background thread
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum ListenerErrorKind {
+    /// Reading from the device failed; the listener stops
+    Read,
+    /// Data was read but could not be delivered to the frontend
+    Emit,
+    /// A heartbeat write failed; the heartbeat stops
+    Heartbeat,
+    /// Data piled up past `max_buffer_size` without being delivered and was
+    /// dropped; the listener keeps running
+    Overflow,
+    /// A line received with `Framing::Json` is not valid JSON; the listener
+    /// keeps running
+    Parse,
+}
+
+/// Payload of the `plugin-serialplugin-error-*` event
+#[derive(Serialize, Clone)]
+pub struct ListenerError {
+    pub path: String,
+    pub kind: ListenerErrorKind,
+    pub message: String,
+}
+
+/// Payload of the `plugin-serialplugin-write-progress-*` and
+/// `plugin-serialplugin-write-complete-*` events
+#[derive(Serialize, Clone)]
+pub struct WriteProgress {
+    pub path: String,
+    pub bytes_written: usize,
+    pub total: usize,
+}
+
+/// Payload of the port added/removed events
+#[derive(Serialize, Clone)]
+pub struct PortChange {
+    pub path: String,
+    pub info: HashMap<String, String>,
+}
+
+/// How a listener splits incoming data into `plugin-serialplugin-frame-*` events
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Framing {
+    /// NMEA 0183 sentences ending in `\r\n`; sentences with a bad `*XX` checksum are dropped
+    Nmea,
+    /// SLIP (RFC 1055) frames delimited by `0xC0`, delivered un-escaped
+    Slip,
+    /// Newline-delimited JSON; each line is parsed and emitted as a
+    /// `plugin-serialplugin-json-*` event instead of a frame event, and lines
+    /// that don't parse are reported as `Parse` error events
+    Json,
+}
+
+/// A checksum computed by `checksum`; the CRCs are named as in the
+/// reveng CRC catalogue
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChecksumAlgorithm {
+    /// CRC-8/SMBUS
+    Crc8,
+    /// CRC-8/MAXIM-DOW of 1-Wire devices
+    Crc8Maxim,
+    /// CRC-16/CCITT-FALSE
+    Crc16Ccitt,
+    Crc16Xmodem,
+    Crc16Kermit,
+    Crc16Modbus,
+    /// CRC-32/ISO-HDLC, as in Ethernet, zip and PNG
+    Crc32,
+    /// CRC-32C (Castagnoli)
+    Crc32c,
+    /// XOR of all bytes
+    Xor8,
+    /// Two's complement of the byte sum
+    Sum8,
+}
+
+/// How a listener turns what it reads into `plugin-serialplugin-read-*` events
+///
+/// Without a strategy the listener reads once every `timeout` milliseconds and
+/// emits whatever arrived in between.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EmitStrategy {
+    /// Read continuously and emit every chunk as soon as it arrives
+    Immediate,
+    /// Read continuously and emit what arrived once `window_ms` milliseconds
+    /// have passed since the first byte of the batch; a batch still open when
+    /// the listener stops is emitted then
+    Batched {
+        #[serde(rename = "windowMs")]
+        window_ms: u64,
+    },
+    /// Read continuously and merge what arrives into one event, emitted once
+    /// no data came for `debounce_ms` milliseconds, but never more than
+    /// `max_rate` events per second (60 by default). A steady stream is
+    /// emitted at that rate, trading latency for less IPC traffic. What is
+    /// left when the listener stops is emitted then.
+    Coalesced {
+        #[serde(rename = "debounceMs")]
+        debounce_ms: u64,
+        #[serde(rename = "maxRate", default)]
+        max_rate: Option<u32>,
+    },
+}
+
+/// Whether a listener is delivering data or paused
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ListenState {
+    /// Reading and emitting data
+    Running,
+    /// Not reading, so incoming bytes stay in the OS buffer for a direct `read`
+    Paused,
+    /// Still reading, but holding the data back until resumed
+    PausedReading,
+}
+
+impl ListenState {
+    pub fn as_u8(&self) -> u8 {
+        match self {
+            ListenState::Running => 0,
+            ListenState::Paused => 1,
+            ListenState::PausedReading => 2,
+        }
+    }
+
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => ListenState::Paused,
+            2 => ListenState::PausedReading,
+            _ => ListenState::Running,
+        }
+    }
+}
+
+/// Snapshot of the modem status input lines
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct ModemStatus {
+    /// CTS (Clear To Send)
+    pub cts: bool,
+    /// DSR (Data Set Ready)
+    pub dsr: bool,
+    /// RI (Ring Indicator)
+    pub ri: bool,
+    /// CD (Carrier Detect)
+    pub cd: bool,
+}
+
+/// Line errors the driver counted since the device was opened, from
+/// `get_line_errors` and the `plugin-serialplugin-line-error-*` event
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LineErrors {
+    /// Characters received without a valid stop bit
+    pub framing: u32,
+    /// Characters received with the wrong parity
+    pub parity: u32,
+    /// Characters lost because the UART's receive register was full
+    pub overrun: u32,
+    /// Characters lost because the driver's buffer was full
+    pub buffer_overrun: u32,
+    /// Break conditions received
+    pub breaks: u32,
+}
+
+/// RS485 half-duplex settings, with the transceiver's driver enable wired to RTS
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rs485Config {
+    /// Whether RTS is driven high (true) or low (false) while transmitting
+    pub rts_active_high: bool,
+    /// Milliseconds to wait after asserting RTS before sending
+    #[serde(default)]
+    pub delay_before_tx: u64,
+    /// Milliseconds to wait after the output buffer drains before releasing RTS
+    #[serde(default)]
+    pub delay_after_tx: u64,
+}
+
+/// The port `open_when_available` waits for: either an exact path or a USB device
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum PortTarget {
+    /// Port path, e.g. `COM3` or `/dev/ttyUSB0`
+    Path(String),
+    /// First port belonging to a USB device with this vendor/product id
+    Usb {
+        vid: u16,
+        pid: u16,
+        #[serde(default, rename = "serialNumber")]
+        serial_number: Option<String>,
+    },
+}
+
+impl std::fmt::Display for PortTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PortTarget::Path(path) => write!(f, "{}", path),
+            PortTarget::Usb {
+                vid,
+                pid,
+                serial_number: Some(serial_number),
+            } => write!(f, "USB {:04x}:{:04x} ({})", vid, pid, serial_number),
+            PortTarget::Usb { vid, pid, .. } => write!(f, "USB {:04x}:{:04x}", vid, pid),
+        }
+    }
+}
+
+/// Constraints for `available_ports_filtered`; unset fields match any port
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortFilter {
+    /// Port type as reported in the port info: `USB`, `Bluetooth`, `PCI` or `Unknown`
+    pub port_type: Option<String>,
+    pub vid: Option<u16>,
+    pub pid: Option<u16>,
+    /// Case-insensitive substring of the manufacturer name
+    pub manufacturer: Option<String>,
+}
+
+impl PortFilter {
+    /// Whether a port info map, as returned by `available_ports`, passes the filter
+    pub fn matches(&self, info: &HashMap<String, String>) -> bool {
+        let field = |key: &str| info.get(key).map(String::as_str).unwrap_or(UNKNOWN);
+
+        if let Some(port_type) = &self.port_type {
+            if !field("type").eq_ignore_ascii_case(port_type) {
+                return false;
+            }
+        }
+        if let Some(vid) = self.vid {
+            if field("vid") != vid.to_string() {
+                return false;
+            }
+        }
+        if let Some(pid) = self.pid {
+            if field("pid") != pid.to_string() {
+                return false;
+            }
+        }
+        if let Some(manufacturer) = &self.manufacturer {
+            if !field("manufacturer")
+                .to_lowercase()
+                .contains(&manufacturer.to_lowercase())
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Settings used to open a port with `open`; unset options take their defaults
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenConfig {
+    pub baud_rate: u32,
+    pub data_bits: Option<DataBits>,
+    pub flow_control: Option<FlowControl>,
+    pub parity: Option<Parity>,
+    pub stop_bits: Option<StopBits>,
+    pub timeout: Option<u64>,
+    /// Times a busy port is retried, with a delay doubling from `retry_delay_ms`
+    pub open_retries: Option<u32>,
+    pub retry_delay_ms: Option<u64>,
+    /// Fail if the port is already open instead of reopening it (the default)
+    pub fail_if_open: Option<bool>,
+    pub exclusive: Option<bool>,
+    pub initial_dtr: Option<bool>,
+    pub initial_rts: Option<bool>,
+}
+
+impl OpenConfig {
+    /// Open at `baud_rate` with every other option left at its default
+    pub fn new(baud_rate: u32) -> Self {
+        Self {
+            baud_rate,
+            data_bits: None,
+            flow_control: None,
+            parity: None,
+            stop_bits: None,
+            timeout: None,
+            open_retries: None,
+            retry_delay_ms: None,
+            fail_if_open: None,
+            exclusive: None,
+            initial_dtr: None,
+            initial_rts: None,
+        }
+    }
+}
+
+impl From<PortConfig> for OpenConfig {
+    fn from(config: PortConfig) -> Self {
+        Self {
+            data_bits: Some(config.data_bits),
+            flow_control: Some(config.flow_control),
+            parity: Some(config.parity),
+            stop_bits: Some(config.stop_bits),
+            timeout: Some(config.timeout),
+            ..Self::new(config.baud_rate)
+        }
+    }
 }
 
 pub const UNKNOWN: &str = "Unknown";
@@ -62,6 +876,17 @@ impl From<DataBits> for SerialDataBits {
     }
 }
 
+impl From<SerialDataBits> for DataBits {
+    fn from(bits: SerialDataBits) -> Self {
+        match bits {
+            SerialDataBits::Five => DataBits::Five,
+            SerialDataBits::Six => DataBits::Six,
+            SerialDataBits::Seven => DataBits::Seven,
+            SerialDataBits::Eight => DataBits::Eight,
+        }
+    }
+}
+
 impl DataBits {
     pub fn as_u8(&self) -> u8 {
         match self {
@@ -94,6 +919,16 @@ impl From<FlowControl> for SerialFlowControl {
     }
 }
 
+impl From<SerialFlowControl> for FlowControl {
+    fn from(flow: SerialFlowControl) -> Self {
+        match flow {
+            SerialFlowControl::None => FlowControl::None,
+            SerialFlowControl::Software => FlowControl::Software,
+            SerialFlowControl::Hardware => FlowControl::Hardware,
+        }
+    }
+}
+
 impl FlowControl {
     pub fn as_u8(&self) -> u8 {
         match self {
@@ -113,24 +948,49 @@ pub enum Parity {
     Odd,
     /// Parity bit sets even number of 1 bits
     Even,
+    /// Parity bit always 1
+    Mark,
+    /// Parity bit always 0
+    Space,
 }
 
-impl From<Parity> for SerialParity {
-    fn from(parity: Parity) -> Self {
+/// The OS serial drivers used on desktop have no mark/space parity, so those
+/// are rejected instead of silently falling back to no parity
+impl TryFrom<Parity> for SerialParity {
+    type Error = Error;
+
+    fn try_from(parity: Parity) -> Result<Self, Self::Error> {
         match parity {
-            Parity::None => SerialParity::None,
-            Parity::Odd => SerialParity::Odd,
-            Parity::Even => SerialParity::Even,
+            Parity::None => Ok(SerialParity::None),
+            Parity::Odd => Ok(SerialParity::Odd),
+            Parity::Even => Ok(SerialParity::Even),
+            Parity::Mark | Parity::Space => Err(Error::String(format!(
+                "{:?} parity is not supported on this platform",
+                parity
+            ))),
+        }
+    }
+}
+
+impl From<SerialParity> for Parity {
+    fn from(parity: SerialParity) -> Self {
+        match parity {
+            SerialParity::None => Parity::None,
+            SerialParity::Odd => Parity::Odd,
+            SerialParity::Even => Parity::Even,
         }
     }
 }
 
 impl Parity {
+    /// Numeric value, matching the Android driver's parity constants
     pub fn as_u8(&self) -> u8 {
         match self {
             Parity::None => 0,
             Parity::Odd => 1,
             Parity::Even => 2,
+            Parity::Mark => 3,
+            Parity::Space => 4,
         }
     }
 }
@@ -153,6 +1013,36 @@ impl From<StopBits> for SerialStopBits {
     }
 }
 
+impl From<SerialStopBits> for StopBits {
+    fn from(bits: SerialStopBits) -> Self {
+        match bits {
+            SerialStopBits::One => StopBits::One,
+            SerialStopBits::Two => StopBits::Two,
+        }
+    }
+}
+
+/// Reject line settings a UART can't use, so they fail with a clear message
+/// instead of an opaque driver error
+pub fn validate_line_settings(
+    baud_rate: u32,
+    data_bits: DataBits,
+    stop_bits: StopBits,
+) -> Result<(), Error> {
+    if baud_rate == 0 {
+        return Err(Error::InvalidInput(
+            "Baud rate must be greater than 0".to_string(),
+        ));
+    }
+    if data_bits == DataBits::Five && stop_bits == StopBits::Two {
+        return Err(Error::InvalidInput(
+            "2 stop bits can't be used with 5 data bits; UARTs send 1.5 stop bits instead"
+                .to_string(),
+        ));
+    }
+    Ok(())
+}
+
 impl StopBits {
     pub fn as_u8(&self) -> u8 {
         match self {
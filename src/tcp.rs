@@ -0,0 +1,944 @@
+//! Serial ports bridged over the network, enabled with the `tcp` feature
+//!
+//! `tcp://host:port` is a raw socket to a serial device server (ser2net, a
+//! MOXA NPort in TCP server mode, ...): the bytes are passed through as-is and
+//! the line settings are whatever the server is configured with.
+//! `rfc2217://host:port` speaks the Telnet COM-PORT-OPTION (RFC 2217), so the
+//! baud rate, framing, flow control and control lines are applied on the
+//! remote port and its modem lines are reported back. Telnet BINARY is
+//! negotiated in both directions so data bytes pass through unchanged, and
+//! the server's other option requests are answered (refused unless supported).
+//!
+//! A background thread reads the socket for as long as the port is open, so
+//! Telnet negotiation is answered even while nobody reads the port.
+
+use crate::opener::PortSettings;
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, StopBits};
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Path prefix for a raw TCP port
+pub const TCP_SCHEME: &str = "tcp://";
+/// Path prefix for an RFC 2217 port
+pub const RFC2217_SCHEME: &str = "rfc2217://";
+
+/// How long connecting to the server may take
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long the server may take to answer the Telnet option negotiation
+const NEGOTIATION_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Telnet
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+const BINARY: u8 = 0;
+const SUPPRESS_GO_AHEAD: u8 = 3;
+const COM_PORT_OPTION: u8 = 44;
+
+/// Options we agree to perform when the server asks with `DO`
+const LOCAL_OPTIONS: [u8; 3] = [BINARY, SUPPRESS_GO_AHEAD, COM_PORT_OPTION];
+/// Options we let the server perform when it offers them with `WILL`
+const REMOTE_OPTIONS: [u8; 2] = [BINARY, SUPPRESS_GO_AHEAD];
+
+// RFC 2217 client commands; the server answers with the code plus 100
+const SET_BAUDRATE: u8 = 1;
+const SET_DATASIZE: u8 = 2;
+const SET_PARITY: u8 = 3;
+const SET_STOPSIZE: u8 = 4;
+const SET_CONTROL: u8 = 5;
+const PURGE_DATA: u8 = 12;
+const NOTIFY_MODEMSTATE: u8 = 107;
+
+// SET-CONTROL values
+const CONTROL_NO_FLOW: u8 = 1;
+const CONTROL_XON_XOFF: u8 = 2;
+const CONTROL_HARDWARE: u8 = 3;
+const CONTROL_BREAK_ON: u8 = 5;
+const CONTROL_BREAK_OFF: u8 = 6;
+const CONTROL_DTR_ON: u8 = 8;
+const CONTROL_DTR_OFF: u8 = 9;
+const CONTROL_RTS_ON: u8 = 11;
+const CONTROL_RTS_OFF: u8 = 12;
+
+// NOTIFY-MODEMSTATE bits
+const MODEM_CTS: u8 = 0x10;
+const MODEM_DSR: u8 = 0x20;
+const MODEM_RI: u8 = 0x40;
+const MODEM_CD: u8 = 0x80;
+
+/// Where the Telnet parser is within a command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TelnetState {
+    Data,
+    /// After `IAC`
+    Command,
+    /// After `IAC WILL/WONT/DO/DONT`, waiting for the option byte
+    Negotiation(u8),
+    /// Inside `IAC SB ... IAC SE`
+    Subnegotiation,
+    /// After `IAC` inside a subnegotiation
+    SubnegotiationIac,
+}
+
+/// Telnet options in effect on one side of the connection
+#[derive(Debug, Default)]
+struct Options {
+    enabled: Vec<u8>,
+    /// Asked for by us and not answered yet
+    requested: Vec<u8>,
+}
+
+/// Remove `option` from `list`, returning whether it was there
+fn take(list: &mut Vec<u8>, option: u8) -> bool {
+    let found = list.contains(&option);
+    list.retain(|&o| o != option);
+    found
+}
+
+impl Options {
+    /// The server asked for or agreed to `option`; returns whether to answer
+    /// that we go along with it, `None` when no answer is due
+    fn enable(&mut self, option: u8, supported: bool) -> Option<bool> {
+        if take(&mut self.requested, option) {
+            self.enabled.push(option);
+            None
+        } else if self.enabled.contains(&option) {
+            None
+        } else if supported {
+            self.enabled.push(option);
+            Some(true)
+        } else {
+            Some(false)
+        }
+    }
+
+    /// The server refused or turned off `option`; returns whether that has to
+    /// be acknowledged
+    fn disable(&mut self, option: u8) -> bool {
+        !take(&mut self.requested, option) && take(&mut self.enabled, option)
+    }
+}
+
+/// Data received from the server, shared by every clone of a port
+struct Inbound {
+    /// Strip Telnet commands from the stream (RFC 2217)
+    telnet: bool,
+    state: TelnetState,
+    subnegotiation: Vec<u8>,
+    /// Bytes received but not yet read
+    pending: VecDeque<u8>,
+    /// Last reported NOTIFY-MODEMSTATE bits
+    modem_state: u8,
+    /// Options we perform
+    local: Options,
+    /// Options the server performs
+    remote: Options,
+    /// Negotiation answers not sent yet
+    replies: Vec<u8>,
+    /// The server closed the connection
+    closed: bool,
+}
+
+impl Inbound {
+    fn new(telnet: bool) -> Self {
+        Self {
+            telnet,
+            state: TelnetState::Data,
+            subnegotiation: Vec::new(),
+            pending: VecDeque::new(),
+            // A raw socket has no modem lines; report them up while connected
+            modem_state: if telnet {
+                0
+            } else {
+                MODEM_CTS | MODEM_DSR | MODEM_CD
+            },
+            local: Options::default(),
+            remote: Options::default(),
+            replies: Vec::new(),
+            closed: false,
+        }
+    }
+
+    fn feed(&mut self, raw: &[u8]) {
+        if !self.telnet {
+            self.pending.extend(raw);
+            return;
+        }
+
+        for &byte in raw {
+            self.state = match self.state {
+                TelnetState::Data if byte == IAC => TelnetState::Command,
+                TelnetState::Data => {
+                    self.pending.push_back(byte);
+                    TelnetState::Data
+                }
+                TelnetState::Command => match byte {
+                    IAC => {
+                        self.pending.push_back(IAC);
+                        TelnetState::Data
+                    }
+                    SB => {
+                        self.subnegotiation.clear();
+                        TelnetState::Subnegotiation
+                    }
+                    WILL | WONT | DO | DONT => TelnetState::Negotiation(byte),
+                    _ => TelnetState::Data,
+                },
+                TelnetState::Negotiation(verb) => {
+                    self.negotiate(verb, byte);
+                    TelnetState::Data
+                }
+                TelnetState::Subnegotiation if byte == IAC => TelnetState::SubnegotiationIac,
+                TelnetState::Subnegotiation => {
+                    self.subnegotiation.push(byte);
+                    TelnetState::Subnegotiation
+                }
+                TelnetState::SubnegotiationIac => match byte {
+                    SE => {
+                        self.handle_subnegotiation();
+                        TelnetState::Data
+                    }
+                    _ => {
+                        self.subnegotiation.push(byte);
+                        TelnetState::Subnegotiation
+                    }
+                },
+            };
+        }
+    }
+
+    /// Answer `IAC verb option` as RFC 854 asks: accept or refuse requests,
+    /// acknowledge options being turned off, and never answer an answer
+    fn negotiate(&mut self, verb: u8, option: u8) {
+        let reply = match verb {
+            DO => self
+                .local
+                .enable(option, LOCAL_OPTIONS.contains(&option))
+                .map(|accept| if accept { WILL } else { WONT }),
+            DONT => self.local.disable(option).then_some(WONT),
+            WILL => self
+                .remote
+                .enable(option, REMOTE_OPTIONS.contains(&option))
+                .map(|accept| if accept { DO } else { DONT }),
+            WONT => self.remote.disable(option).then_some(DONT),
+            _ => None,
+        };
+        if let Some(reply) = reply {
+            self.replies.extend_from_slice(&[IAC, reply, option]);
+        }
+    }
+
+    /// Only modem state notifications matter; replies to our commands are
+    /// ignored
+    fn handle_subnegotiation(&mut self) {
+        if let [COM_PORT_OPTION, NOTIFY_MODEMSTATE, state, ..] = self.subnegotiation[..] {
+            self.modem_state = state;
+        }
+    }
+}
+
+fn lock_error<T>(e: std::sync::PoisonError<T>) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e.to_string())
+}
+
+/// State shared by every clone of a port and the thread reading its socket
+struct Shared {
+    inbound: Mutex<Inbound>,
+    /// Signalled when the reader thread has fed new bytes or seen the
+    /// connection close
+    changed: Condvar,
+    /// Held for each write, so negotiation answers from the reader thread
+    /// don't land inside an escaped data byte
+    writing: Mutex<()>,
+}
+
+impl Shared {
+    fn inbound(&self) -> io::Result<MutexGuard<'_, Inbound>> {
+        self.inbound.lock().map_err(lock_error)
+    }
+
+    fn send(&self, mut stream: &TcpStream, bytes: &[u8]) -> io::Result<()> {
+        let _writing = self.writing.lock().map_err(lock_error)?;
+        stream.write_all(bytes)
+    }
+
+    /// Wait until `done` holds, the connection closes or `deadline` passes
+    fn wait_until<'a>(
+        &'a self,
+        mut inbound: MutexGuard<'a, Inbound>,
+        deadline: Instant,
+        done: impl Fn(&Inbound) -> bool,
+    ) -> io::Result<MutexGuard<'a, Inbound>> {
+        while !done(&inbound) {
+            if inbound.closed {
+                return Err(io::Error::new(
+                    io::ErrorKind::ConnectionAborted,
+                    "Connection closed by the server",
+                ));
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "Operation timed out",
+                ));
+            }
+            inbound = self
+                .changed
+                .wait_timeout(inbound, deadline - now)
+                .map_err(lock_error)?
+                .0;
+        }
+        Ok(inbound)
+    }
+}
+
+/// Feed everything the server sends into `shared` until the connection is
+/// closed or shut down, answering Telnet negotiation as it arrives
+fn spawn_reader(mut stream: TcpStream, shared: Arc<Shared>) {
+    thread::spawn(move || {
+        let mut raw = [0u8; 4096];
+        loop {
+            let count = match stream.read(&mut raw) {
+                Ok(0) => break,
+                Ok(count) => count,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            };
+            let replies = {
+                let mut inbound = shared.inbound.lock().unwrap_or_else(|e| e.into_inner());
+                inbound.feed(&raw[..count]);
+                std::mem::take(&mut inbound.replies)
+            };
+            shared.changed.notify_all();
+            if !replies.is_empty() && shared.send(&stream, &replies).is_err() {
+                break;
+            }
+        }
+        shared
+            .inbound
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .closed = true;
+        shared.changed.notify_all();
+    });
+}
+
+/// The socket, shut down with the last clone of the port so the reader
+/// thread ends too
+struct Connection(TcpStream);
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        let _ = self.0.shutdown(Shutdown::Both);
+    }
+}
+
+/// A serial port on a remote device server
+pub struct TcpPort {
+    path: String,
+    connection: Arc<Connection>,
+    shared: Arc<Shared>,
+    telnet: bool,
+    baud_rate: u32,
+    data_bits: DataBits,
+    flow_control: FlowControl,
+    parity: Parity,
+    stop_bits: StopBits,
+    timeout: Duration,
+}
+
+impl TcpPort {
+    /// Connect to the server in `path`, which must start with `tcp://` or
+    /// `rfc2217://`
+    pub fn open(path: &str, settings: &PortSettings) -> serialport::Result<Self> {
+        let (address, telnet) = if let Some(address) = path.strip_prefix(TCP_SCHEME) {
+            (address, false)
+        } else if let Some(address) = path.strip_prefix(RFC2217_SCHEME) {
+            (address, true)
+        } else {
+            return Err(serialport::Error::new(
+                serialport::ErrorKind::InvalidInput,
+                format!("{} is not a network port path", path),
+            ));
+        };
+
+        let stream = Self::connect(address)?;
+        stream.set_nodelay(true)?;
+
+        let shared = Arc::new(Shared {
+            inbound: Mutex::new(Inbound::new(telnet)),
+            changed: Condvar::new(),
+            writing: Mutex::new(()),
+        });
+        spawn_reader(stream.try_clone()?, shared.clone());
+
+        let port = Self {
+            path: path.to_string(),
+            connection: Arc::new(Connection(stream)),
+            shared,
+            telnet,
+            baud_rate: settings.baud_rate,
+            data_bits: settings.data_bits,
+            flow_control: settings.flow_control,
+            parity: settings.parity,
+            stop_bits: settings.stop_bits,
+            timeout: settings.timeout,
+        };
+        port.apply_timeout()?;
+
+        if telnet {
+            port.negotiate()?;
+            port.send_command(SET_BAUDRATE, &settings.baud_rate.to_be_bytes())?;
+            port.send_command(SET_DATASIZE, &[Self::data_bits_value(settings.data_bits)])?;
+            port.send_command(SET_PARITY, &[Self::parity_value(settings.parity)])?;
+            port.send_command(SET_STOPSIZE, &[Self::stop_bits_value(settings.stop_bits)])?;
+            port.send_command(SET_CONTROL, &[Self::flow_value(settings.flow_control)])?;
+        }
+
+        Ok(port)
+    }
+
+    fn connect(address: &str) -> io::Result<TcpStream> {
+        let mut last_error = io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} did not resolve to any address", address),
+        );
+        for addr in address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = e,
+            }
+        }
+        Err(last_error)
+    }
+
+    /// Agree on BINARY in both directions and on COM-PORT-OPTION, failing if
+    /// the server refuses the latter
+    fn negotiate(&self) -> io::Result<()> {
+        {
+            let mut inbound = self.shared.inbound()?;
+            inbound.local.requested.extend([BINARY, COM_PORT_OPTION]);
+            inbound.remote.requested.push(BINARY);
+        }
+        self.shared.send(
+            &self.connection.0,
+            &[
+                IAC,
+                WILL,
+                BINARY,
+                IAC,
+                DO,
+                BINARY,
+                IAC,
+                WILL,
+                COM_PORT_OPTION,
+            ],
+        )?;
+
+        let inbound = self
+            .shared
+            .wait_until(
+                self.shared.inbound()?,
+                Instant::now() + NEGOTIATION_TIMEOUT,
+                |inbound| inbound.local.requested.is_empty() && inbound.remote.requested.is_empty(),
+            )
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::TimedOut => io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "The server did not answer the Telnet negotiation",
+                ),
+                _ => e,
+            })?;
+        if !inbound.local.enabled.contains(&COM_PORT_OPTION) {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "The server does not support RFC 2217",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Only writes can block on the socket; reads wait for the reader thread.
+    /// A zero socket timeout means "block forever", so use the shortest real
+    /// one.
+    fn apply_timeout(&self) -> io::Result<()> {
+        let timeout = self.timeout.max(Duration::from_millis(1));
+        self.connection.0.set_write_timeout(Some(timeout))
+    }
+
+    /// Send an RFC 2217 command; a no-op on raw TCP ports
+    fn send_command(&self, command: u8, value: &[u8]) -> io::Result<()> {
+        if !self.telnet {
+            return Ok(());
+        }
+
+        let mut message = vec![IAC, SB, COM_PORT_OPTION, command];
+        for &byte in value {
+            message.push(byte);
+            if byte == IAC {
+                message.push(IAC);
+            }
+        }
+        message.extend_from_slice(&[IAC, SE]);
+        self.shared.send(&self.connection.0, &message)
+    }
+
+    fn data_bits_value(data_bits: DataBits) -> u8 {
+        match data_bits {
+            DataBits::Five => 5,
+            DataBits::Six => 6,
+            DataBits::Seven => 7,
+            DataBits::Eight => 8,
+        }
+    }
+
+    fn stop_bits_value(stop_bits: StopBits) -> u8 {
+        match stop_bits {
+            StopBits::One => 1,
+            StopBits::Two => 2,
+        }
+    }
+
+    fn parity_value(parity: Parity) -> u8 {
+        match parity {
+            Parity::None => 1,
+            Parity::Odd => 2,
+            Parity::Even => 3,
+        }
+    }
+
+    fn flow_value(flow_control: FlowControl) -> u8 {
+        match flow_control {
+            FlowControl::None => CONTROL_NO_FLOW,
+            FlowControl::Software => CONTROL_XON_XOFF,
+            FlowControl::Hardware => CONTROL_HARDWARE,
+        }
+    }
+
+    fn modem_line(&self, bit: u8) -> serialport::Result<bool> {
+        Ok(self.shared.inbound()?.modem_state & bit != 0)
+    }
+}
+
+impl Read for TcpPort {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let mut inbound = self.shared.wait_until(
+            self.shared.inbound()?,
+            Instant::now() + self.timeout,
+            |inbound| !inbound.pending.is_empty(),
+        )?;
+        let count = buf.len().min(inbound.pending.len());
+        for (slot, byte) in buf.iter_mut().zip(inbound.pending.drain(..count)) {
+            *slot = byte;
+        }
+        Ok(count)
+    }
+}
+
+impl Write for TcpPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !self.telnet || !buf.contains(&IAC) {
+            let _writing = self.shared.writing.lock().map_err(lock_error)?;
+            return (&self.connection.0).write(buf);
+        }
+
+        // Data bytes equal to IAC are doubled on a Telnet connection
+        let mut escaped = Vec::with_capacity(buf.len() + 8);
+        for &byte in buf {
+            escaped.push(byte);
+            if byte == IAC {
+                escaped.push(IAC);
+            }
+        }
+        self.shared.send(&self.connection.0, &escaped)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (&self.connection.0).flush()
+    }
+}
+
+impl serialport::SerialPort for TcpPort {
+    fn name(&self) -> Option<String> {
+        Some(self.path.clone())
+    }
+
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        Ok(self.baud_rate)
+    }
+
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        Ok(self.data_bits)
+    }
+
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        Ok(self.flow_control)
+    }
+
+    fn parity(&self) -> serialport::Result<Parity> {
+        Ok(self.parity)
+    }
+
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        Ok(self.stop_bits)
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn set_baud_rate(&mut self, baud_rate: u32) -> serialport::Result<()> {
+        self.send_command(SET_BAUDRATE, &baud_rate.to_be_bytes())?;
+        self.baud_rate = baud_rate;
+        Ok(())
+    }
+
+    fn set_data_bits(&mut self, data_bits: DataBits) -> serialport::Result<()> {
+        self.send_command(SET_DATASIZE, &[Self::data_bits_value(data_bits)])?;
+        self.data_bits = data_bits;
+        Ok(())
+    }
+
+    fn set_flow_control(&mut self, flow_control: FlowControl) -> serialport::Result<()> {
+        self.send_command(SET_CONTROL, &[Self::flow_value(flow_control)])?;
+        self.flow_control = flow_control;
+        Ok(())
+    }
+
+    fn set_parity(&mut self, parity: Parity) -> serialport::Result<()> {
+        self.send_command(SET_PARITY, &[Self::parity_value(parity)])?;
+        self.parity = parity;
+        Ok(())
+    }
+
+    fn set_stop_bits(&mut self, stop_bits: StopBits) -> serialport::Result<()> {
+        self.send_command(SET_STOPSIZE, &[Self::stop_bits_value(stop_bits)])?;
+        self.stop_bits = stop_bits;
+        Ok(())
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        self.timeout = timeout;
+        self.apply_timeout()?;
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, level: bool) -> serialport::Result<()> {
+        let value = if level {
+            CONTROL_RTS_ON
+        } else {
+            CONTROL_RTS_OFF
+        };
+        self.send_command(SET_CONTROL, &[value])?;
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, level: bool) -> serialport::Result<()> {
+        let value = if level {
+            CONTROL_DTR_ON
+        } else {
+            CONTROL_DTR_OFF
+        };
+        self.send_command(SET_CONTROL, &[value])?;
+        Ok(())
+    }
+
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        self.modem_line(MODEM_CTS)
+    }
+
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        self.modem_line(MODEM_DSR)
+    }
+
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        self.modem_line(MODEM_RI)
+    }
+
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        self.modem_line(MODEM_CD)
+    }
+
+    /// Bytes the reader thread has taken off the socket; anything still in the
+    /// kernel buffer shows up a moment later
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        Ok(self.shared.inbound()?.pending.len() as u32)
+    }
+
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        // Unsent data sits in the kernel socket buffer, which can't be queried
+        Ok(0)
+    }
+
+    fn clear(&self, buffer_to_clear: ClearBuffer) -> serialport::Result<()> {
+        let purge = match buffer_to_clear {
+            ClearBuffer::Input => 1,
+            ClearBuffer::Output => 2,
+            ClearBuffer::All => 3,
+        };
+        self.send_command(PURGE_DATA, &[purge])?;
+
+        // Modem state notifications were already taken out by the reader
+        // thread, so only data is lost
+        if matches!(buffer_to_clear, ClearBuffer::Input | ClearBuffer::All) {
+            self.shared.inbound()?.pending.clear();
+        }
+        Ok(())
+    }
+
+    fn try_clone(&self) -> serialport::Result<Box<dyn serialport::SerialPort>> {
+        Ok(Box::new(Self {
+            path: self.path.clone(),
+            connection: self.connection.clone(),
+            shared: self.shared.clone(),
+            telnet: self.telnet,
+            baud_rate: self.baud_rate,
+            data_bits: self.data_bits,
+            flow_control: self.flow_control,
+            parity: self.parity,
+            stop_bits: self.stop_bits,
+            timeout: self.timeout,
+        }))
+    }
+
+    fn set_break(&self) -> serialport::Result<()> {
+        self.send_command(SET_CONTROL, &[CONTROL_BREAK_ON])?;
+        Ok(())
+    }
+
+    fn clear_break(&self) -> serialport::Result<()> {
+        self.send_command(SET_CONTROL, &[CONTROL_BREAK_OFF])?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serialport::SerialPort as _;
+    use std::net::TcpListener;
+
+    const ECHO: u8 = 1;
+
+    fn telnet() -> Inbound {
+        Inbound::new(true)
+    }
+
+    #[test]
+    fn telnet_commands_are_stripped_and_iac_unescaped() {
+        let mut inbound = telnet();
+        inbound.feed(&[b'a', IAC, IAC, b'b', IAC, 241, b'c', IAC]);
+        inbound.feed(&[DO, BINARY, b'd']);
+        assert_eq!(inbound.pending, b"a\xffbcd");
+        assert_eq!(inbound.replies, [IAC, WILL, BINARY]);
+    }
+
+    #[test]
+    fn raw_tcp_passes_iac_through() {
+        let mut inbound = Inbound::new(false);
+        inbound.feed(&[IAC, DO, BINARY]);
+        assert_eq!(inbound.pending, [IAC, DO, BINARY]);
+        assert!(inbound.replies.is_empty());
+    }
+
+    #[test]
+    fn modem_state_notification_with_escaped_iac() {
+        let mut inbound = telnet();
+        inbound.feed(&[
+            IAC,
+            SB,
+            COM_PORT_OPTION,
+            NOTIFY_MODEMSTATE,
+            MODEM_CTS,
+            IAC,
+            SE,
+        ]);
+        assert_eq!(inbound.modem_state, MODEM_CTS);
+
+        inbound.feed(&[IAC, SB, COM_PORT_OPTION, NOTIFY_MODEMSTATE, IAC]);
+        inbound.feed(&[IAC, IAC, SE, b'x']);
+        assert_eq!(inbound.modem_state, 0xFF);
+        assert_eq!(inbound.pending, b"x");
+    }
+
+    #[test]
+    fn server_requests_are_accepted_or_refused_once() {
+        let mut inbound = telnet();
+        inbound.feed(&[IAC, DO, BINARY, IAC, DO, ECHO, IAC, WILL, SUPPRESS_GO_AHEAD]);
+        inbound.feed(&[IAC, WILL, ECHO]);
+        assert_eq!(
+            std::mem::take(&mut inbound.replies),
+            [
+                IAC,
+                WILL,
+                BINARY,
+                IAC,
+                WONT,
+                ECHO,
+                IAC,
+                DO,
+                SUPPRESS_GO_AHEAD,
+                IAC,
+                DONT,
+                ECHO
+            ]
+        );
+
+        // Repeats of what is already in effect, and turning off what never was,
+        // need no answer
+        inbound.feed(&[IAC, DO, BINARY, IAC, WONT, ECHO]);
+        assert!(inbound.replies.is_empty());
+
+        inbound.feed(&[IAC, DONT, BINARY, IAC, WONT, SUPPRESS_GO_AHEAD]);
+        assert_eq!(
+            inbound.replies,
+            [IAC, WONT, BINARY, IAC, DONT, SUPPRESS_GO_AHEAD]
+        );
+        assert!(inbound.local.enabled.is_empty());
+        assert!(inbound.remote.enabled.is_empty());
+    }
+
+    #[test]
+    fn answers_to_our_requests_are_not_answered() {
+        let mut inbound = telnet();
+        inbound.local.requested.extend([BINARY, COM_PORT_OPTION]);
+        inbound.remote.requested.push(BINARY);
+        inbound.feed(&[
+            IAC,
+            DO,
+            COM_PORT_OPTION,
+            IAC,
+            DONT,
+            BINARY,
+            IAC,
+            WONT,
+            BINARY,
+        ]);
+        assert!(inbound.replies.is_empty());
+        assert_eq!(inbound.local.enabled, [COM_PORT_OPTION]);
+        assert!(inbound.remote.enabled.is_empty());
+        assert!(inbound.local.requested.is_empty() && inbound.remote.requested.is_empty());
+    }
+
+    #[test]
+    fn rfc2217_port_negotiates_and_configures_the_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            socket
+                .write_all(&[IAC, DO, BINARY, IAC, WILL, BINARY, IAC, DO, COM_PORT_OPTION])
+                .unwrap();
+            socket.write_all(&[b'h', b'i', IAC, IAC]).unwrap();
+            socket
+                .write_all(&[
+                    IAC,
+                    SB,
+                    COM_PORT_OPTION,
+                    NOTIFY_MODEMSTATE,
+                    MODEM_CTS,
+                    IAC,
+                    SE,
+                ])
+                .unwrap();
+            // Everything the client sends until it disconnects
+            let mut received = Vec::new();
+            socket.read_to_end(&mut received).unwrap();
+            received
+        });
+
+        let mut port = TcpPort::open(
+            &format!("{}{}", RFC2217_SCHEME, address),
+            &PortSettings::new(9600),
+        )
+        .unwrap();
+        let mut data = Vec::new();
+        while data.len() < 3 {
+            let mut buf = [0u8; 8];
+            let count = port.read(&mut buf).unwrap();
+            data.extend_from_slice(&buf[..count]);
+        }
+        assert_eq!(data, b"hi\xff");
+
+        let deadline = Instant::now() + Duration::from_secs(1);
+        while !port.read_clear_to_send().unwrap() {
+            assert!(Instant::now() < deadline, "CTS was never reported");
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        port.write_all(&[b'o', IAC]).unwrap();
+        drop(port);
+
+        let received = server.join().unwrap();
+        assert!(received.starts_with(&[
+            IAC,
+            WILL,
+            BINARY,
+            IAC,
+            DO,
+            BINARY,
+            IAC,
+            WILL,
+            COM_PORT_OPTION
+        ]));
+        let baud_rate = [
+            IAC,
+            SB,
+            COM_PORT_OPTION,
+            SET_BAUDRATE,
+            0,
+            0,
+            0x25,
+            0x80,
+            IAC,
+            SE,
+        ];
+        assert!(received.windows(baud_rate.len()).any(|w| w == baud_rate));
+        assert!(received.ends_with(&[b'o', IAC, IAC]));
+    }
+
+    #[test]
+    fn refused_com_port_option_fails_to_open() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            socket
+                .write_all(&[
+                    IAC,
+                    DO,
+                    BINARY,
+                    IAC,
+                    WILL,
+                    BINARY,
+                    IAC,
+                    DONT,
+                    COM_PORT_OPTION,
+                ])
+                .unwrap();
+            let mut received = Vec::new();
+            socket.read_to_end(&mut received).unwrap();
+        });
+
+        let result = TcpPort::open(
+            &format!("{}{}", RFC2217_SCHEME, address),
+            &PortSettings::new(9600),
+        );
+        assert!(result.is_err());
+        server.join().unwrap();
+    }
+}
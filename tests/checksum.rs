@@ -0,0 +1,59 @@
+//! Check values of the checksums, from the reveng CRC catalogue
+
+use tauri_plugin_serialplugin::checksum::{self, *};
+use tauri_plugin_serialplugin::state::ChecksumAlgorithm;
+
+/// The input every catalogue check value is computed over
+const CHECK: &[u8] = b"123456789";
+
+#[test]
+fn crcs_match_catalogue_check_values() {
+    assert_eq!(crc8(CHECK), 0xF4);
+    assert_eq!(crc8_maxim(CHECK), 0xA1);
+    assert_eq!(crc16_ccitt(CHECK), 0x29B1);
+    assert_eq!(crc16_xmodem(CHECK), 0x31C3);
+    assert_eq!(crc16_kermit(CHECK), 0x2189);
+    assert_eq!(crc16_modbus(CHECK), 0x4B37);
+    assert_eq!(crc32(CHECK), 0xCBF4_3926);
+    assert_eq!(crc32c(CHECK), 0xE306_9283);
+}
+
+#[test]
+fn simple_checksums() {
+    assert_eq!(xor8(CHECK), 0x31);
+    assert_eq!(sum8(CHECK), 0x23);
+    // The data plus its checksum sums to zero
+    let total = CHECK.iter().fold(sum8(CHECK), |sum, &byte| sum.wrapping_add(byte));
+    assert_eq!(total, 0);
+}
+
+#[test]
+fn empty_input_gives_the_initial_value() {
+    assert_eq!(crc16_modbus(&[]), 0xFFFF);
+    assert_eq!(crc32(&[]), 0);
+    assert_eq!(xor8(&[]), 0);
+}
+
+#[test]
+fn modbus_frame_with_its_crc_appended_checks_to_zero() {
+    // Read holding registers 0..10 of unit 1
+    let mut frame = vec![0x01, 0x03, 0x00, 0x00, 0x00, 0x0A];
+    let crc = crc16_modbus(&frame);
+    assert_eq!(crc, 0xCDC5);
+    frame.extend_from_slice(&crc.to_le_bytes());
+    assert_eq!(crc16_modbus(&frame), 0);
+}
+
+#[test]
+fn checksum_dispatches_to_the_helpers() {
+    assert_eq!(checksum::checksum(ChecksumAlgorithm::Crc8, CHECK), 0xF4);
+    assert_eq!(
+        checksum::checksum(ChecksumAlgorithm::Crc16Modbus, CHECK),
+        0x4B37
+    );
+    assert_eq!(
+        checksum::checksum(ChecksumAlgorithm::Crc32, CHECK),
+        0xCBF4_3926
+    );
+    assert_eq!(checksum::checksum(ChecksumAlgorithm::Sum8, CHECK), 0x23);
+}
@@ -0,0 +1,135 @@
+//! Reads made while a listener is running, against `loopback://` ports
+//!
+//! A listener reads through a clone of the port handle, and clones share the
+//! OS input buffer: every byte goes to whichever of them reads first. Loopback
+//! ports model that with one buffer per end, shared by all clones.
+
+#![cfg(feature = "loopback")]
+
+use std::sync::mpsc::Receiver;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+use tauri::{App, Manager};
+use tauri_plugin_serialplugin::state::OpenConfig;
+use tauri_plugin_serialplugin::SerialPort;
+
+fn app() -> App<MockRuntime> {
+    mock_builder()
+        .plugin(tauri_plugin_serialplugin::init())
+        .build(mock_context(noop_assets()))
+        .expect("failed to build the app")
+}
+
+/// Open both ends of a virtual cable named `name`
+fn open_pair(serial: &SerialPort<MockRuntime>, name: &str) -> (String, String) {
+    let tx = format!("loopback://{}A", name);
+    let rx = format!("loopback://{}B", name);
+    for path in [&tx, &rx] {
+        let config = OpenConfig {
+            timeout: Some(10),
+            ..OpenConfig::new(115200)
+        };
+        serial
+            .open(path.clone(), config)
+            .expect("failed to open loopback port");
+    }
+    (tx, rx)
+}
+
+/// Start listening on `path`, polling every 5 ms
+fn listen(serial: &SerialPort<MockRuntime>, path: &str, buffer_reads: bool) {
+    serial
+        .start_listening(
+            path.to_string(),
+            Some(5),
+            Some(16),
+            None,
+            Some(buffer_reads),
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+        .expect("failed to start listening");
+}
+
+/// Everything `subscriber` receives until `len` bytes arrived or a second passed
+fn collect(subscriber: &Receiver<Vec<u8>>, len: usize) -> Vec<u8> {
+    let deadline = Instant::now() + Duration::from_secs(1);
+    let mut received = Vec::new();
+    while received.len() < len {
+        let left = deadline.saturating_duration_since(Instant::now());
+        match subscriber.recv_timeout(left) {
+            Ok(chunk) => received.extend(chunk),
+            Err(_) => break,
+        }
+    }
+    received
+}
+
+/// Whether `part` appears in `whole` in order, possibly with gaps
+fn is_subsequence(part: &[u8], whole: &[u8]) -> bool {
+    let mut whole = whole.iter();
+    part.iter().all(|byte| whole.any(|other| other == byte))
+}
+
+#[test]
+fn read_competes_with_listener() {
+    let app = app();
+    let serial = app.state::<SerialPort<MockRuntime>>();
+    let (tx, rx) = open_pair(&serial, "compete");
+    let subscriber = serial.subscribe(rx.clone()).unwrap();
+    listen(&serial, &rx, false);
+
+    let sent: Vec<u8> = (0..200).collect();
+    let mut read = Vec::new();
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            for chunk in sent.chunks(2) {
+                serial.write_binary(tx.clone(), chunk.to_vec()).unwrap();
+                thread::sleep(Duration::from_millis(2));
+            }
+        });
+
+        let deadline = Instant::now() + Duration::from_millis(500);
+        while Instant::now() < deadline {
+            if let Ok(data) = serial.read_binary(rx.clone(), Some(20), Some(4), None) {
+                read.extend(data);
+            }
+        }
+    });
+    let listened = collect(&subscriber, sent.len() - read.len());
+
+    // Each byte reached exactly one of them, so neither sees the whole stream
+    assert!(!read.is_empty(), "the read got no data");
+    assert!(!listened.is_empty(), "the listener got no data");
+    assert_eq!(read.len() + listened.len(), sent.len());
+    assert!(is_subsequence(&read, &sent));
+    assert!(is_subsequence(&listened, &sent));
+
+    serial.close_all().unwrap();
+}
+
+#[test]
+fn buffered_read_sees_everything_the_listener_sees() {
+    let app = app();
+    let serial = app.state::<SerialPort<MockRuntime>>();
+    let (tx, rx) = open_pair(&serial, "buffered");
+    let subscriber = serial.subscribe(rx.clone()).unwrap();
+    listen(&serial, &rx, true);
+
+    let sent: Vec<u8> = (0..200).collect();
+    for chunk in sent.chunks(8) {
+        serial.write_binary(tx.clone(), chunk.to_vec()).unwrap();
+    }
+
+    let read = serial
+        .read_binary(rx.clone(), Some(1000), Some(sent.len()), Some(sent.len()))
+        .unwrap();
+    assert_eq!(read, sent);
+    assert_eq!(collect(&subscriber, sent.len()), sent);
+
+    serial.close_all().unwrap();
+}
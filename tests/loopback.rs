@@ -0,0 +1,74 @@
+//! The `loopback://` ports themselves, without the plugin around them
+
+#![cfg(feature = "loopback")]
+
+use serialport::SerialPort;
+use std::io::{ErrorKind, Read, Write};
+use std::time::Duration;
+use tauri_plugin_serialplugin::loopback::LoopbackPort;
+use tauri_plugin_serialplugin::PortSettings;
+
+fn open(path: &str) -> LoopbackPort {
+    let mut settings = PortSettings::new(115200);
+    settings.timeout = Duration::from_millis(20);
+    LoopbackPort::open(path, &settings).expect("failed to open loopback port")
+}
+
+/// Everything that can be read from `port` before it times out
+fn read_all(port: &mut LoopbackPort) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut buffer = [0u8; 64];
+    loop {
+        match port.read(&mut buffer) {
+            Ok(n) => data.extend_from_slice(&buffer[..n]),
+            Err(e) if e.kind() == ErrorKind::TimedOut => return data,
+            Err(e) => panic!("read failed: {}", e),
+        }
+    }
+}
+
+#[test]
+fn pair_carries_data_both_ways() {
+    let mut a = open("loopback://pairA");
+    let mut b = open("loopback://pairB");
+
+    a.write_all(b"ping").unwrap();
+    assert_eq!(read_all(&mut b), b"ping");
+    b.write_all(b"pong").unwrap();
+    assert_eq!(read_all(&mut a), b"pong");
+    assert_eq!(b.bytes_to_read().unwrap(), 0);
+}
+
+#[test]
+fn unpaired_name_echoes() {
+    let mut port = open("loopback://echo");
+    port.write_all(b"hello").unwrap();
+    assert_eq!(read_all(&mut port), b"hello");
+}
+
+#[test]
+fn data_waits_for_the_other_end_while_one_is_open() {
+    let mut a = open("loopback://waitA");
+    a.write_all(b"early").unwrap();
+
+    let mut b = open("loopback://waitB");
+    assert_eq!(read_all(&mut b), b"early");
+}
+
+#[test]
+fn unread_data_is_gone_once_both_ends_are_closed() {
+    let mut a = open("loopback://staleA");
+    let b = open("loopback://staleB");
+    a.write_all(b"left over").unwrap();
+    let clone = b.try_clone().unwrap();
+    drop((a, b));
+
+    // A clone still holds the end, so its data is kept
+    let b = open("loopback://staleB");
+    assert_eq!(b.bytes_to_read().unwrap(), 9);
+    drop(clone);
+    drop(b);
+
+    let mut b = open("loopback://staleB");
+    assert!(read_all(&mut b).is_empty());
+}
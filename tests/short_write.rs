@@ -0,0 +1,210 @@
+//! Writes to a device that accepts only a few bytes per call
+//!
+//! Slow or flow-controlled links return short writes; `write` and
+//! `write_binary` must keep going until every byte is out.
+
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, StopBits};
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::test::{mock_builder, mock_context, noop_assets, MockRuntime};
+use tauri::{App, Manager};
+use tauri_plugin_serialplugin::state::{self, PortConfig};
+use tauri_plugin_serialplugin::{PortOpener, PortSettings, SerialPort};
+
+/// Most bytes a single `write` call takes
+const ACCEPTED_PER_CALL: usize = 3;
+
+/// A port whose writes take at most `ACCEPTED_PER_CALL` bytes, recording them
+struct ShortWritePort {
+    written: Arc<Mutex<Vec<u8>>>,
+    calls: Arc<Mutex<usize>>,
+    timeout: Duration,
+}
+
+impl Read for ShortWritePort {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        Err(io::Error::new(io::ErrorKind::TimedOut, "nothing to read"))
+    }
+}
+
+impl Write for ShortWritePort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = buf.len().min(ACCEPTED_PER_CALL);
+        self.written.lock().unwrap().extend_from_slice(&buf[..n]);
+        *self.calls.lock().unwrap() += 1;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl serialport::SerialPort for ShortWritePort {
+    fn name(&self) -> Option<String> {
+        Some("short://port".to_string())
+    }
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        Ok(9600)
+    }
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        Ok(DataBits::Eight)
+    }
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        Ok(FlowControl::None)
+    }
+    fn parity(&self) -> serialport::Result<Parity> {
+        Ok(Parity::None)
+    }
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        Ok(StopBits::One)
+    }
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+    fn set_baud_rate(&mut self, _: u32) -> serialport::Result<()> {
+        Ok(())
+    }
+    fn set_data_bits(&mut self, _: DataBits) -> serialport::Result<()> {
+        Ok(())
+    }
+    fn set_flow_control(&mut self, _: FlowControl) -> serialport::Result<()> {
+        Ok(())
+    }
+    fn set_parity(&mut self, _: Parity) -> serialport::Result<()> {
+        Ok(())
+    }
+    fn set_stop_bits(&mut self, _: StopBits) -> serialport::Result<()> {
+        Ok(())
+    }
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        self.timeout = timeout;
+        Ok(())
+    }
+    fn write_request_to_send(&mut self, _: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+    fn write_data_terminal_ready(&mut self, _: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        Ok(true)
+    }
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        Ok(true)
+    }
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        Ok(true)
+    }
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        Ok(0)
+    }
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        Ok(0)
+    }
+    fn clear(&self, _: ClearBuffer) -> serialport::Result<()> {
+        Ok(())
+    }
+    fn try_clone(&self) -> serialport::Result<Box<dyn serialport::SerialPort>> {
+        Ok(Box::new(Self {
+            written: self.written.clone(),
+            calls: self.calls.clone(),
+            timeout: self.timeout,
+        }))
+    }
+    fn set_break(&self) -> serialport::Result<()> {
+        Ok(())
+    }
+    fn clear_break(&self) -> serialport::Result<()> {
+        Ok(())
+    }
+}
+
+/// Opens every path as a `ShortWritePort` sharing one record of the writes
+#[derive(Default)]
+struct ShortWriteOpener {
+    written: Arc<Mutex<Vec<u8>>>,
+    calls: Arc<Mutex<usize>>,
+}
+
+impl PortOpener for ShortWriteOpener {
+    fn open(
+        &self,
+        _path: &str,
+        settings: &PortSettings,
+    ) -> serialport::Result<Box<dyn serialport::SerialPort>> {
+        Ok(Box::new(ShortWritePort {
+            written: self.written.clone(),
+            calls: self.calls.clone(),
+            timeout: settings.timeout,
+        }))
+    }
+
+    fn enumerate(&self) -> serialport::Result<Vec<serialport::SerialPortInfo>> {
+        Ok(vec![])
+    }
+}
+
+fn app() -> App<MockRuntime> {
+    mock_builder()
+        .plugin(tauri_plugin_serialplugin::init())
+        .build(mock_context(noop_assets()))
+        .expect("failed to build the app")
+}
+
+/// Open `short://port` through a fresh `ShortWriteOpener`, returning the
+/// path it is managed under and the opener
+fn open(serial: &SerialPort<MockRuntime>) -> (String, Arc<ShortWriteOpener>) {
+    let opener = Arc::new(ShortWriteOpener::default());
+    serial.set_port_opener(opener.clone()).unwrap();
+    let path = serial
+        .open_with_config(
+            "short://port".to_string(),
+            PortConfig {
+                baud_rate: 9600,
+                data_bits: state::DataBits::Eight,
+                flow_control: state::FlowControl::None,
+                parity: state::Parity::None,
+                stop_bits: state::StopBits::One,
+                timeout: 100,
+            },
+        )
+        .expect("failed to open the port");
+    (path, opener)
+}
+
+#[test]
+fn write_binary_sends_everything_through_short_writes() {
+    let app = app();
+    let serial = app.state::<SerialPort<MockRuntime>>();
+    let (path, opener) = open(&serial);
+
+    let data: Vec<u8> = (0..=255).collect();
+    let written = serial.write_binary(path, data.clone()).unwrap();
+
+    assert_eq!(written, data.len());
+    assert_eq!(*opener.written.lock().unwrap(), data);
+    // Proves the device really did split the data up
+    assert!(*opener.calls.lock().unwrap() >= data.len() / ACCEPTED_PER_CALL);
+
+    serial.close_all().unwrap();
+}
+
+#[test]
+fn write_sends_the_whole_string_through_short_writes() {
+    let app = app();
+    let serial = app.state::<SerialPort<MockRuntime>>();
+    let (path, opener) = open(&serial);
+
+    let text = "AT+GMR\r\nAT+CWMODE=1\r\n";
+    let written = serial.write(path, text.to_string()).unwrap();
+
+    assert_eq!(written, text.len());
+    assert_eq!(*opener.written.lock().unwrap(), text.as_bytes());
+
+    serial.close_all().unwrap();
+}